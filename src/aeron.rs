@@ -21,7 +21,7 @@ use std::time::Duration;
 use rand::distributions::Uniform;
 
 use crate::{
-    client_conductor::ClientConductor,
+    client_conductor::{ClientConductor, PublicationInfo, SubscriptionInfo},
     cnc_file_descriptor,
     concurrent::{
         agent_invoker::AgentInvoker,
@@ -79,7 +79,6 @@ pub struct Aeron {
     conductor_invoker: AgentInvoker<ClientConductor>,
 }
 
-const IDLE_SLEEP_MS: Moment = 4;
 const IDLE_SLEEP_MS_1: Moment = 1;
 const IDLE_SLEEP_MS_16: Moment = 16;
 const IDLE_SLEEP_MS_100: Moment = 100;
@@ -103,11 +102,17 @@ impl Aeron {
         let local_to_driver_ring_buffer = Arc::new(ManyToOneRingBuffer::new(local_to_driver_atomic_buffer)?);
         let local_to_clients_broadcast_receiver = Arc::new(Mutex::new(BroadcastReceiver::new(local_to_clients_atomic_buffer)?));
         let local_driver_proxy = Arc::new(DriverProxy::new(local_to_driver_ring_buffer.clone()));
-        let local_idle_strategy = Arc::new(SleepingIdleStrategy::new(IDLE_SLEEP_MS));
+        let local_idle_strategy = Arc::new(Self::conductor_idle_strategy(&context));
         let local_copy_broadcast_receiver = Arc::new(Mutex::new(CopyBroadcastReceiver::new(
             local_to_clients_broadcast_receiver.clone(),
         )));
 
+        let inter_service_timeout_ns = if context.inter_service_timeout() > 0 {
+            context.inter_service_timeout() * 1_000_000
+        } else {
+            cnc_file_descriptor::client_liveness_timeout(&cnc_buf) as u64
+        };
+
         let local_conductor = ClientConductor::new(
             unix_time_ms,
             local_driver_proxy.clone(),
@@ -123,10 +128,23 @@ impl Aeron {
             context.close_client_handler(),
             context.media_driver_timeout(),
             context.resource_linger_timeout(),
-            cnc_file_descriptor::client_liveness_timeout(&cnc_buf) as u64,
+            inter_service_timeout_ns,
             context.pre_touch_mapped_memory(),
         );
 
+        local_conductor
+            .lock()
+            .expect("Mutex poisoned")
+            .set_session_id_supplier(context.session_id_supplier());
+        local_conductor
+            .lock()
+            .expect("Mutex poisoned")
+            .set_sparse_term_buffers(context.sparse_term_buffers());
+        local_conductor
+            .lock()
+            .expect("Mutex poisoned")
+            .set_shared_position_group(context.shared_position_group());
+
         let use_agent_invoker = context.use_conductor_agent_invoker();
 
         let mut aeronchik = Self {
@@ -492,6 +510,35 @@ impl Aeron {
         self.context.use_conductor_agent_invoker()
     }
 
+    /**
+     * List the publications and exclusive publications added by this client and not yet released, e.g. for
+     * management tools or debugging.
+     *
+     * @return snapshot of channel, stream, session, registration id and status of each publication.
+     */
+    pub fn active_publications(&self) -> Vec<PublicationInfo> {
+        self.conductor.lock().expect("Mutex poisoned").active_publications()
+    }
+
+    /**
+     * List the subscriptions added by this client and not yet released, e.g. for management tools or debugging.
+     *
+     * @return snapshot of channel, stream, image sessions, registration id and status of each subscription.
+     */
+    pub fn active_subscriptions(&self) -> Vec<SubscriptionInfo> {
+        self.conductor.lock().expect("Mutex poisoned").active_subscriptions()
+    }
+
+    /**
+     * Number of frames read by the Images of this client with an unsupported version, which indicates version skew
+     * between the media driver and the client. Only the first mismatch of each Image is passed to the error handler.
+     *
+     * @return the number of frame version mismatches.
+     */
+    pub fn version_mismatch_count(&self) -> u64 {
+        self.conductor.lock().expect("Mutex poisoned").version_mismatch_count()
+    }
+
     /**
      * Get the CountersReader for the Aeron media driver counters.
      *
@@ -510,6 +557,16 @@ impl Aeron {
         self.driver_proxy.client_id()
     }
 
+    /**
+     * Request the media driver this client is connected to to terminate.
+     *
+     * @param token optional token to be validated by the driver before it terminates.
+     */
+    pub fn terminate_driver(&self, token: Option<&[u8]>) -> Result<(), AeronError> {
+        self.conductor.lock().expect("Mutex poisoned").ensure_open()?;
+        self.driver_proxy.terminate_driver(token)
+    }
+
     /**
      * Get the Aeron Context object used in construction of the Aeron instance.
      *
@@ -528,6 +585,14 @@ impl Aeron {
         String::from("aeron version 0.1")
     }
 
+    /**
+     * Idle strategy for the conductor thread, sleeping for the conductor min idle period of the context after a
+     * duty cycle which did no work.
+     */
+    fn conductor_idle_strategy(context: &Context) -> SleepingIdleStrategy {
+        SleepingIdleStrategy::new(context.conductor_min_idle_period())
+    }
+
     pub fn map_cnc_file(context: &Context) -> Result<MemoryMappedFile, AeronError> {
         let start_ms = unix_time_ms();
 
@@ -603,3 +668,17 @@ impl Drop for Aeron {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_build_conductor_idle_strategy_from_context_min_idle_period() {
+        let mut context = Context::new();
+        assert_eq!(Aeron::conductor_idle_strategy(&context).duration(), 4);
+
+        context.set_conductor_min_idle_period(25);
+        assert_eq!(Aeron::conductor_idle_strategy(&context).duration(), 25);
+    }
+}
@@ -14,6 +14,8 @@
  * limitations under the License.
  */
 
+use std::sync::Arc;
+
 use crate::{
     concurrent::{
         atomic_buffer::AtomicBuffer,
@@ -27,7 +29,37 @@ use crate::{
     },
 };
 
-const BUFFER_BUILDER_MAX_CAPACITY: Index = std::u32::MAX as Index - 8;
+const BUFFER_BUILDER_MAX_CAPACITY: Index = Index::MAX - 8;
+
+/// Source of the backing memory for a BufferBuilder. Allows real-time applications to control where
+/// assembly buffers live, e.g. pre-faulted or NUMA-local memory.
+///
+/// # Safety
+///
+/// BufferBuilder writes through the returned pointer without further checks. allocate() must return a non-null
+/// pointer to len bytes which are valid for reads and writes, zeroed, aligned on a cache line and not in use
+/// elsewhere until the region is passed to deallocate(). The allocator is shared between threads.
+pub unsafe trait BufferAllocator: Send + Sync {
+    /// Allocate a zeroed region of len bytes aligned on a cache line.
+    fn allocate(&self, len: Index) -> *mut u8;
+
+    /// Release a region previously returned by allocate() for the same len.
+    fn deallocate(&self, ptr: *mut u8, len: Index);
+}
+
+/// Default allocator which takes cache line aligned memory from the global allocator.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemAllocator;
+
+unsafe impl BufferAllocator for SystemAllocator {
+    fn allocate(&self, len: Index) -> *mut u8 {
+        alloc_buffer_aligned(len)
+    }
+
+    fn deallocate(&self, ptr: *mut u8, len: Index) {
+        dealloc_buffer_aligned(ptr, len)
+    }
+}
 
 /// This type must not impl Copy! Only move semantics is allowed.
 /// BufferBuilder owns memory (allocates / deallocates it)
@@ -35,22 +67,32 @@ pub struct BufferBuilder {
     capacity: Index,
     limit: Index,
     buffer: *mut u8,
+    allocator: Arc<dyn BufferAllocator>,
 }
 
 impl Drop for BufferBuilder {
     fn drop(&mut self) {
         // Free the memory we own
-        dealloc_buffer_aligned(self.buffer, self.capacity)
+        self.allocator.deallocate(self.buffer, self.capacity)
     }
 }
 
 impl BufferBuilder {
     pub fn new(initial_length: isize) -> Self {
-        let len = bit_utils::find_next_power_of_two_i64(initial_length as i64) as Index;
+        Self::with_allocator(initial_length, Arc::new(SystemAllocator))
+    }
+
+    /// Creates the builder with its backing memory taken from (and returned to) the given allocator.
+    pub fn with_allocator(initial_length: isize, allocator: Arc<dyn BufferAllocator>) -> Self {
+        let len = std::cmp::min(
+            bit_utils::find_next_power_of_two_i64(initial_length as i64),
+            BUFFER_BUILDER_MAX_CAPACITY as i64,
+        ) as Index;
         Self {
             capacity: len,
             limit: data_frame_header::LENGTH,
-            buffer: alloc_buffer_aligned(len),
+            buffer: allocator.allocate(len),
+            allocator,
         }
     }
 
@@ -80,13 +122,8 @@ impl BufferBuilder {
         self
     }
 
-    pub fn append(
-        &mut self,
-        buffer: &AtomicBuffer,
-        offset: Index,
-        length: Index,
-        _header: &Header,
-    ) -> Result<&BufferBuilder, AeronError> {
+    /// Appends given part of the buffer and returns new limit of the builder.
+    pub fn append(&mut self, buffer: &AtomicBuffer, offset: Index, length: Index, _header: &Header) -> Result<Index, AeronError> {
         self.ensure_capacity(length)?;
 
         unsafe {
@@ -99,6 +136,18 @@ impl BufferBuilder {
 
         self.limit += length;
 
+        Ok(self.limit)
+    }
+
+    /// Same as append() but returns the builder itself to allow chained calls.
+    pub fn append_chained(
+        &mut self,
+        buffer: &AtomicBuffer,
+        offset: Index,
+        length: Index,
+        header: &Header,
+    ) -> Result<&mut BufferBuilder, AeronError> {
+        self.append(buffer, offset, length, header)?;
         Ok(self)
     }
 
@@ -106,9 +155,10 @@ impl BufferBuilder {
         let mut capacity = current_capacity;
 
         loop {
-            let new_capacity = capacity + (capacity >> 1);
+            // Grow in i64 so that capacities beyond 2/3 of Index::MAX cannot overflow.
+            let new_capacity = capacity as i64 + (capacity as i64 >> 1);
 
-            if new_capacity < capacity || new_capacity > BUFFER_BUILDER_MAX_CAPACITY {
+            if new_capacity > BUFFER_BUILDER_MAX_CAPACITY as i64 {
                 if capacity == BUFFER_BUILDER_MAX_CAPACITY {
                     return Err(AeronError::IllegalStateException(format!(
                         "max capacity reached:  {}",
@@ -118,7 +168,7 @@ impl BufferBuilder {
 
                 capacity = BUFFER_BUILDER_MAX_CAPACITY;
             } else {
-                capacity = new_capacity;
+                capacity = new_capacity as Index;
             }
 
             if capacity >= required_capacity {
@@ -131,16 +181,18 @@ impl BufferBuilder {
 
     /// This fn resizes (if needed) the buffer keeping all the data in it.
     fn ensure_capacity(&mut self, additional_capacity: Index) -> Result<(), AeronError> {
-        let required_capacity = self.limit + additional_capacity;
+        let required_capacity = self.limit.checked_add(additional_capacity).ok_or_else(|| {
+            AeronError::IllegalStateException(format!("max capacity reached:  {}", BUFFER_BUILDER_MAX_CAPACITY))
+        })?;
 
         if required_capacity > self.capacity {
             let new_capacity = BufferBuilder::find_suitable_capacity(self.capacity, required_capacity)?;
-            let new_buffer = alloc_buffer_aligned(new_capacity);
+            let new_buffer = self.allocator.allocate(new_capacity);
 
             unsafe {
                 std::ptr::copy(self.buffer, new_buffer, self.limit as usize);
-                dealloc_buffer_aligned(self.buffer, self.capacity)
             }
+            self.allocator.deallocate(self.buffer, self.capacity);
 
             self.buffer = new_buffer;
             self.capacity = new_capacity;
@@ -148,3 +200,133 @@ impl BufferBuilder {
         Ok(())
     }
 }
+
+/// Allocator for tests which counts the regions it hands out and takes back.
+#[cfg(test)]
+pub(crate) mod counting_allocator {
+    use std::sync::atomic::{AtomicI32, Ordering};
+
+    use crate::{
+        buffer_builder::{BufferAllocator, SystemAllocator},
+        utils::types::Index,
+    };
+
+    #[derive(Default)]
+    pub(crate) struct CountingAllocator {
+        pub allocations: AtomicI32,
+        pub deallocations: AtomicI32,
+    }
+
+    unsafe impl BufferAllocator for CountingAllocator {
+        fn allocate(&self, len: Index) -> *mut u8 {
+            self.allocations.fetch_add(1, Ordering::SeqCst);
+            SystemAllocator.allocate(len)
+        }
+
+        fn deallocate(&self, ptr: *mut u8, len: Index) {
+            self.deallocations.fetch_add(1, Ordering::SeqCst);
+            SystemAllocator.deallocate(ptr, len)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::Ordering;
+    use std::sync::Arc;
+
+    use crate::{
+        buffer_builder::{counting_allocator::CountingAllocator, BufferBuilder, BUFFER_BUILDER_MAX_CAPACITY},
+        concurrent::{
+            atomic_buffer::{AlignedBuffer, AtomicBuffer},
+            logbuffer::{data_frame_header, header::Header},
+        },
+        utils::types::Index,
+    };
+
+    const SRC_LENGTH: Index = 1024;
+
+    #[test]
+    fn should_return_new_limit_after_each_append() {
+        let src = AlignedBuffer::with_capacity(SRC_LENGTH);
+        let src_buffer = AtomicBuffer::from_aligned(&src);
+        let header = Header::new(0, SRC_LENGTH);
+        let mut builder = BufferBuilder::new(16);
+
+        let mut total = 0;
+        for length in &[7, 100, 0, 513, 64] {
+            let limit = builder.append(&src_buffer, 0, *length, &header).unwrap();
+            total += length;
+            assert_eq!(limit, total + data_frame_header::LENGTH);
+            assert_eq!(limit, builder.limit());
+        }
+    }
+
+    #[test]
+    fn should_chain_appends_and_keep_content() {
+        let src = AlignedBuffer::with_capacity(SRC_LENGTH);
+        let src_buffer = AtomicBuffer::from_aligned(&src);
+        src_buffer.set_memory(0, 10, 1);
+        src_buffer.set_memory(10, 10, 2);
+        let header = Header::new(0, SRC_LENGTH);
+        let mut builder = BufferBuilder::new(16);
+
+        let limit = builder
+            .reset()
+            .append_chained(&src_buffer, 0, 10, &header)
+            .unwrap()
+            .append_chained(&src_buffer, 10, 10, &header)
+            .unwrap()
+            .limit();
+
+        assert_eq!(limit, data_frame_header::LENGTH + 20);
+
+        let built = AtomicBuffer::new(builder.buffer(), builder.limit());
+        assert_eq!(built.get::<u8>(data_frame_header::LENGTH), 1);
+        assert_eq!(built.get::<u8>(data_frame_header::LENGTH + 9), 1);
+        assert_eq!(built.get::<u8>(data_frame_header::LENGTH + 10), 2);
+        assert_eq!(built.get::<u8>(data_frame_header::LENGTH + 19), 2);
+    }
+
+    #[test]
+    fn should_use_allocator_across_growth_and_reset() {
+        let src = AlignedBuffer::with_capacity(SRC_LENGTH);
+        let src_buffer = AtomicBuffer::from_aligned(&src);
+        let header = Header::new(0, SRC_LENGTH);
+        let allocator = Arc::new(CountingAllocator::default());
+
+        {
+            let mut builder = BufferBuilder::with_allocator(64, allocator.clone());
+            assert_eq!(allocator.allocations.load(Ordering::SeqCst), 1);
+
+            // Fits into the initial capacity
+            builder.append(&src_buffer, 0, 16, &header).unwrap();
+            assert_eq!(allocator.allocations.load(Ordering::SeqCst), 1);
+            assert_eq!(allocator.deallocations.load(Ordering::SeqCst), 0);
+
+            // Each growth allocates the new buffer and releases the old one
+            builder.append(&src_buffer, 0, 64, &header).unwrap();
+            assert_eq!(allocator.allocations.load(Ordering::SeqCst), 2);
+            assert_eq!(allocator.deallocations.load(Ordering::SeqCst), 1);
+
+            // Reset keeps the grown buffer
+            builder.reset();
+            builder.append(&src_buffer, 0, 64, &header).unwrap();
+            assert_eq!(allocator.allocations.load(Ordering::SeqCst), 2);
+            assert_eq!(allocator.deallocations.load(Ordering::SeqCst), 1);
+        }
+
+        assert_eq!(allocator.allocations.load(Ordering::SeqCst), 2);
+        assert_eq!(allocator.deallocations.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn should_grow_to_max_capacity_without_overflow() {
+        let large_capacity = (Index::MAX / 3) * 2 + 1;
+
+        let capacity = BufferBuilder::find_suitable_capacity(large_capacity, large_capacity + 1).unwrap();
+        assert_eq!(capacity, BUFFER_BUILDER_MAX_CAPACITY);
+
+        assert!(BufferBuilder::find_suitable_capacity(BUFFER_BUILDER_MAX_CAPACITY, Index::MAX).is_err());
+    }
+}
@@ -18,40 +18,44 @@ use std::{
     collections::HashMap,
     ffi::{CStr, CString},
     sync::{
-        atomic::{AtomicBool, Ordering},
+        atomic::{AtomicBool, AtomicU64, Ordering},
         Arc, Mutex, Weak,
     },
+    time::Duration,
 };
 
 use crate::{
+    channel_uri::{
+        ChannelUri, IPC_MEDIA, MTU_LENGTH_PARAM_NAME, SESSION_ID_PARAM_NAME, SPARSE_PARAM_NAME, TERM_LENGTH_PARAM_NAME,
+    },
     concurrent::{
         agent_runner::Agent,
         atomic_buffer::AtomicBuffer,
         atomic_counter::AtomicCounter,
         broadcast::copy_broadcast_receiver::CopyBroadcastReceiver,
         counters::{self, CountersReader},
-        logbuffer::term_reader::ErrorHandler,
+        logbuffer::{frame_descriptor::VersionMismatchCounter, log_buffer_descriptor, term_reader::ErrorHandler},
         position::UnsafeBufferPosition,
         status::status_indicator_reader,
     },
     context::{
         OnAvailableCounter, OnAvailableImage, OnCloseClient, OnNewPublication, OnNewSubscription, OnUnavailableCounter,
-        OnUnavailableImage,
+        OnUnavailableImage, SessionIdSupplier,
     },
     counter::Counter,
     driver_listener_adapter::{DriverListener, DriverListenerAdapter},
     driver_proxy::DriverProxy,
     exclusive_publication::ExclusivePublication,
     heartbeat_timestamp,
-    image::Image,
+    image::{Image, SharedPosition},
     publication::Publication,
     subscription::Subscription,
     ttrace,
     utils::{
         errors::AeronError::{self, ChannelEndpointException, ClientTimeoutException},
         log_buffers::LogBuffers,
-        misc::CallbackGuard,
-        types::{Moment, MAX_MOMENT},
+        misc::{parse_size, CallbackGuard},
+        types::{Index, Moment, MAX_MOMENT},
     },
 };
 
@@ -59,15 +63,39 @@ type EpochClock = fn() -> Moment;
 
 const KEEPALIVE_TIMEOUT_MS: Moment = 500;
 const RESOURCE_TIMEOUT_MS: Moment = 1000;
+const CLOSE_DRAIN_TIMEOUT_MS: Moment = 100;
 
 /// MediaDriver
-#[derive(PartialEq, Debug)]
-enum RegistrationStatus {
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum RegistrationStatus {
     Awaiting,
     Registered,
     Errored,
 }
 
+/// Snapshot of a publication added by this client, see ClientConductor::active_publications.
+#[derive(Clone, PartialEq, Debug)]
+pub struct PublicationInfo {
+    pub channel: CString,
+    pub stream_id: i32,
+    /// Session id assigned by the media driver, -1 until the publication is registered.
+    pub session_id: i32,
+    pub registration_id: i64,
+    pub is_exclusive: bool,
+    pub status: RegistrationStatus,
+}
+
+/// Snapshot of a subscription added by this client, see ClientConductor::active_subscriptions.
+#[derive(Clone, PartialEq, Debug)]
+pub struct SubscriptionInfo {
+    pub channel: CString,
+    pub stream_id: i32,
+    /// Session ids of the Images currently available to the subscription.
+    pub image_session_ids: Vec<i32>,
+    pub registration_id: i64,
+    pub status: RegistrationStatus,
+}
+
 struct PublicationStateDefn {
     error_message: CString,
     buffers: Option<Arc<LogBuffers>>, // PublicationStateDefn could be created without it
@@ -152,6 +180,7 @@ struct SubscriptionStateDefn {
     stream_id: i32,
     error_code: i32,
     status: RegistrationStatus,
+    shared_position_group: Option<String>,
 }
 
 impl SubscriptionStateDefn {
@@ -175,10 +204,14 @@ impl SubscriptionStateDefn {
             stream_id,
             error_code: -1,
             status: RegistrationStatus::Awaiting,
+            shared_position_group: None,
         }
     }
 }
 
+// Shared consumption positions are held per group tag, channel, stream id and Image correlation id.
+type SharedPositionKey = (String, CString, i32, i64);
+
 struct CounterStateDefn {
     error_message: CString,
     counter_cache: Option<Arc<Counter>>,
@@ -266,6 +299,7 @@ pub struct ClientConductor {
     destination_state_by_correlation_id: HashMap<i64, DestinationStateDefn>,
 
     log_buffers_by_registration_id: HashMap<i64, LogBuffersDefn>,
+    shared_position_by_key: HashMap<SharedPositionKey, Arc<SharedPosition>>,
     lingering_image_lists: Vec<ImageListLingerDefn>,
 
     driver_proxy: Arc<DriverProxy>,
@@ -287,6 +321,10 @@ pub struct ClientConductor {
     driver_timeout_ms: Moment,
     resource_linger_timeout_ms: Moment,
     inter_service_timeout_ms: Moment,
+    session_id_supplier: Option<SessionIdSupplier>,
+    sparse_term_buffers: Option<bool>,
+    shared_position_group: Option<String>,
+    version_mismatch_count: Arc<AtomicU64>,
     pre_touch_mapped_memory: bool,
     is_in_callback: bool,
     driver_active: AtomicBool,
@@ -330,6 +368,7 @@ impl ClientConductor {
             counter_by_registration_id: Default::default(),
             destination_state_by_correlation_id: Default::default(),
             log_buffers_by_registration_id: Default::default(),
+            shared_position_by_key: Default::default(),
             lingering_image_lists: vec![],
             driver_proxy,
             driver_listener_adapter: None,
@@ -346,6 +385,10 @@ impl ClientConductor {
             driver_timeout_ms,
             resource_linger_timeout_ms,
             inter_service_timeout_ms: inter_service_timeout_ns / 1_000_000,
+            session_id_supplier: None,
+            sparse_term_buffers: None,
+            shared_position_group: None,
+            version_mismatch_count: Arc::new(AtomicU64::new(0)),
             pre_touch_mapped_memory,
             is_in_callback: false,
             driver_active: AtomicBool::from(true),
@@ -376,6 +419,49 @@ impl ClientConductor {
         self.epoch_clock = new_provider;
     }
 
+    /**
+     * Set the supplier of session ids for exclusive publications whose channel does not specify a session-id.
+     *
+     * @param supplier of session ids or None to let the driver assign them.
+     */
+    pub fn set_session_id_supplier(&mut self, supplier: Option<SessionIdSupplier>) {
+        self.session_id_supplier = supplier;
+    }
+
+    /**
+     * Set the sparse parameter added to the channels of new publications and subscriptions which do not specify it.
+     *
+     * @param sparse true for sparse term buffers, false for pre-zeroed ones or None for the media driver default.
+     */
+    pub fn set_sparse_term_buffers(&mut self, sparse: Option<bool>) {
+        self.sparse_term_buffers = sparse;
+    }
+
+    /**
+     * Set the tag of the shared position group joined by new subscriptions. The Images of a group's Subscriptions
+     * which carry the same stream from the same publication share a single consumption position so each message
+     * is delivered to exactly one of them. Subscriptions keep the group set when they were added.
+     *
+     * @param group tag of the shared position group or None for subscriptions to keep their own position.
+     */
+    pub fn set_shared_position_group(&mut self, group: Option<String>) {
+        self.shared_position_group = group;
+    }
+
+    pub fn sparse_term_buffers(&self) -> Option<bool> {
+        self.sparse_term_buffers
+    }
+
+    /**
+     * Number of frames read by the Images of this client with a version other than
+     * data_frame_header::CURRENT_VERSION, which indicates version skew between the media driver and the client.
+     *
+     * @return the number of frame version mismatches.
+     */
+    pub fn version_mismatch_count(&self) -> u64 {
+        self.version_mismatch_count.load(Ordering::Relaxed)
+    }
+
     pub fn set_error_handler(&mut self, new_handler: ErrorHandler) {
         self.error_handler = new_handler;
     }
@@ -396,6 +482,72 @@ impl ClientConductor {
         self.on_unavailable_counter_handlers.push(handler);
     }
 
+    /**
+     * Snapshot the publications and exclusive publications this client has added and not yet released,
+     * ordered by registration id.
+     *
+     * @return info on each publication.
+     */
+    pub fn active_publications(&self) -> Vec<PublicationInfo> {
+        let publications = self.publication_by_registration_id.values().map(|state| PublicationInfo {
+            channel: state.channel.clone(),
+            stream_id: state.stream_id,
+            session_id: state.session_id,
+            registration_id: state.registration_id,
+            is_exclusive: false,
+            status: state.status,
+        });
+        let exclusive_publications = self
+            .exclusive_publication_by_registration_id
+            .values()
+            .map(|state| PublicationInfo {
+                channel: state.channel.clone(),
+                stream_id: state.stream_id,
+                session_id: state.session_id,
+                registration_id: state.registration_id,
+                is_exclusive: true,
+                status: state.status,
+            });
+
+        let mut infos: Vec<PublicationInfo> = publications.chain(exclusive_publications).collect();
+        infos.sort_by_key(|info| info.registration_id);
+        infos
+    }
+
+    /**
+     * Snapshot the subscriptions this client has added and not yet released, ordered by registration id.
+     *
+     * @return info on each subscription.
+     */
+    pub fn active_subscriptions(&self) -> Vec<SubscriptionInfo> {
+        let mut infos: Vec<SubscriptionInfo> = self
+            .subscription_by_registration_id
+            .values()
+            .map(|state| {
+                let image_session_ids = state
+                    .subscription
+                    .as_ref()
+                    .and_then(|subscription| subscription.upgrade())
+                    .map(|subscription| {
+                        let subscription = subscription.lock().expect("Mutex poisoned");
+                        subscription.images().iter().map(|image| image.session_id()).collect()
+                    })
+                    .unwrap_or_default();
+
+                SubscriptionInfo {
+                    channel: state.channel.clone(),
+                    stream_id: state.stream_id,
+                    image_session_ids,
+                    registration_id: state.registration_id,
+                    status: state.status,
+                }
+            })
+            .collect();
+
+        infos.sort_by_key(|info| info.registration_id);
+        infos
+    }
+
     pub fn counters_reader(&self) -> Result<Arc<CountersReader>, AeronError> {
         self.ensure_open()?;
         Ok(self.counters_reader.clone())
@@ -536,8 +688,12 @@ impl ClientConductor {
             lb.time_of_last_state_change_ms = MAX_MOMENT;
             Ok(lb.log_buffers.clone())
         } else {
-            let touch = self.pre_touch_mapped_memory && !channel.to_string_lossy().contains("sparse=true");
-            let log_buffer = LogBuffers::from_existing(log_filename.into_string().expect("CString conv error"), touch)?;
+            let touch = self.pre_touch_mapped_memory && !self.is_sparse(&channel);
+            let log_buffer = LogBuffers::from_existing(
+                log_filename.into_string().expect("CString conv error"),
+                touch,
+                registration_id,
+            )?;
 
             let log_buffers = Arc::new(log_buffer);
             self.log_buffers_by_registration_id
@@ -583,7 +739,10 @@ impl ClientConductor {
         self.ensure_not_reentrant();
         self.ensure_open()?;
 
-        let registration_id = self.driver_proxy.add_publication(channel.clone(), stream_id)?;
+        Self::validate_mtu_length(&channel)?;
+
+        let driver_channel = self.with_sparse_term_buffers(channel.clone())?;
+        let registration_id = self.driver_proxy.add_publication(driver_channel, stream_id)?;
 
         self.publication_by_registration_id.insert(
             registration_id,
@@ -735,7 +894,11 @@ impl ClientConductor {
         self.ensure_not_reentrant();
         self.ensure_open()?;
 
-        let registration_id = self.driver_proxy.add_exclusive_publication(channel.clone(), stream_id)?;
+        Self::validate_mtu_length(&channel)?;
+
+        let driver_channel = self.with_supplied_session_id(channel.clone())?;
+        let driver_channel = self.with_sparse_term_buffers(driver_channel)?;
+        let registration_id = self.driver_proxy.add_exclusive_publication(driver_channel, stream_id)?;
 
         self.exclusive_publication_by_registration_id.insert(
             registration_id,
@@ -750,6 +913,102 @@ impl ClientConductor {
         Ok(registration_id)
     }
 
+    /// Rejects a channel setting an mtu of more than 1/8 of the term length, which the media driver would refuse
+    /// without a clear reason. When the channel does not set term-length the driver's default for the media is used.
+    fn validate_mtu_length(channel: &CString) -> Result<(), AeronError> {
+        let channel_str = channel
+            .to_str()
+            .map_err(|err| AeronError::IllegalArgumentException(format!("invalid channel: {}", err)))?;
+
+        // Most channels leave mtu to the driver, spare them the parse.
+        if !channel_str.contains(MTU_LENGTH_PARAM_NAME) {
+            return Ok(());
+        }
+
+        let channel_uri = ChannelUri::parse(channel_str)?;
+        let channel_uri = channel_uri.lock().expect("Mutex poisoned");
+
+        if channel_uri.contains_key(MTU_LENGTH_PARAM_NAME) {
+            let parse = |name: &str| {
+                let value = channel_uri.get(name);
+                let size = parse_size(name, value)?;
+                if size > Index::MAX as i64 {
+                    return Err(AeronError::IllegalArgumentException(format!("invalid {}: {}", name, value)));
+                }
+                Ok(size as Index)
+            };
+
+            let term_length = if channel_uri.contains_key(TERM_LENGTH_PARAM_NAME) {
+                parse(TERM_LENGTH_PARAM_NAME)?
+            } else if channel_uri.media() == IPC_MEDIA {
+                log_buffer_descriptor::IPC_TERM_DEFAULT_LENGTH
+            } else {
+                log_buffer_descriptor::TERM_DEFAULT_LENGTH
+            };
+
+            log_buffer_descriptor::check_mtu_length(parse(MTU_LENGTH_PARAM_NAME)?, term_length)?;
+        }
+
+        Ok(())
+    }
+
+    fn with_supplied_session_id(&self, channel: CString) -> Result<CString, AeronError> {
+        if let Some(supplier) = self.session_id_supplier {
+            let channel_str = channel
+                .to_str()
+                .map_err(|err| AeronError::IllegalArgumentException(format!("invalid channel: {}", err)))?;
+
+            if !ChannelUri::parse(channel_str)?
+                .lock()
+                .expect("Mutex poisoned")
+                .contains_key(SESSION_ID_PARAM_NAME)
+            {
+                let channel_with_session_id = ChannelUri::add_session_id(channel_str, supplier())?;
+                return CString::new(channel_with_session_id)
+                    .map_err(|err| AeronError::IllegalArgumentException(format!("invalid channel: {}", err)));
+            }
+        }
+
+        Ok(channel)
+    }
+
+    fn with_sparse_term_buffers(&self, channel: CString) -> Result<CString, AeronError> {
+        if let Some(sparse) = self.sparse_term_buffers {
+            let channel_str = channel
+                .to_str()
+                .map_err(|err| AeronError::IllegalArgumentException(format!("invalid channel: {}", err)))?;
+            let channel_uri = ChannelUri::parse(channel_str)?;
+            let mut channel_uri = channel_uri.lock().expect("Mutex poisoned");
+
+            if !channel_uri.contains_key(SPARSE_PARAM_NAME) {
+                channel_uri.put(SPARSE_PARAM_NAME, sparse.to_string());
+                return CString::new(channel_uri.to_string())
+                    .map_err(|err| AeronError::IllegalArgumentException(format!("invalid channel: {}", err)));
+            }
+        }
+
+        Ok(channel)
+    }
+
+    // The channel kept for a resource is the one the application gave, when it does not set sparse the media
+    // driver was sent the sparse_term_buffers setting instead.
+    fn is_sparse(&self, channel: &CString) -> bool {
+        let sparse = channel
+            .to_str()
+            .ok()
+            .and_then(|channel_str| ChannelUri::parse(channel_str).ok())
+            .and_then(|channel_uri| {
+                let channel_uri = channel_uri.lock().expect("Mutex poisoned");
+                if channel_uri.contains_key(SPARSE_PARAM_NAME) {
+                    Some(channel_uri.get(SPARSE_PARAM_NAME) == "true")
+                } else {
+                    None
+                }
+            });
+
+        sparse.or(self.sparse_term_buffers).unwrap_or(false)
+    }
+
     pub(crate) fn find_exclusive_publication(
         &mut self,
         registration_id: i64,
@@ -884,19 +1143,20 @@ impl ClientConductor {
         self.ensure_not_reentrant();
         self.ensure_open()?;
 
-        let registration_id = self.driver_proxy.add_subscription(channel.clone(), stream_id)?;
+        let driver_channel = self.with_sparse_term_buffers(channel.clone())?;
+        let registration_id = self.driver_proxy.add_subscription(driver_channel, stream_id)?;
 
-        self.subscription_by_registration_id.insert(
+        let mut subscription_state = SubscriptionStateDefn::new(
+            channel,
             registration_id,
-            SubscriptionStateDefn::new(
-                channel,
-                registration_id,
-                stream_id,
-                (self.epoch_clock)(),
-                on_available_image_handler,
-                on_unavailable_image_handler,
-            ),
+            stream_id,
+            (self.epoch_clock)(),
+            on_available_image_handler,
+            on_unavailable_image_handler,
         );
+        subscription_state.shared_position_group = self.shared_position_group.clone();
+        self.subscription_by_registration_id
+            .insert(registration_id, subscription_state);
 
         ttrace!(
             "add_subscription: subscription ADDED with registration_id {}",
@@ -1318,6 +1578,39 @@ impl ClientConductor {
         Ok(())
     }
 
+    /**
+     * Process the responses pending in the to-clients broadcast until none remain or the timeout expires, so that
+     * in-flight registrations are resolved, e.g. before closing.
+     *
+     * @param timeout after which draining stops even if responses remain.
+     * @return the number of responses processed.
+     */
+    pub fn drain_responses(&mut self, timeout: Duration) -> i32 {
+        let deadline_ms = (self.epoch_clock)() + timeout.as_millis() as Moment;
+        let mut responses = 0;
+
+        if let Some(dla) = self.driver_listener_adapter.take() {
+            loop {
+                match dla.receive_messages(self) {
+                    Ok(0) => break,
+                    Ok(count) => responses += count as i32,
+                    Err(err) => {
+                        (self.error_handler)(err);
+                        break;
+                    }
+                }
+
+                if (self.epoch_clock)() >= deadline_ms {
+                    break;
+                }
+            }
+
+            self.driver_listener_adapter.replace(dla);
+        }
+
+        responses
+    }
+
     pub fn close_all_resources(&mut self, now_ms: Moment) {
         ttrace!("close_all_resources: closing all resources");
 
@@ -1449,12 +1742,14 @@ impl Agent for ClientConductor {
         work_count += dla.receive_messages(self)?;
         self.driver_listener_adapter.replace(dla);
         work_count += self.on_heartbeat_check_timeouts()? as usize;
+
         Ok(work_count as i32)
     }
 
     fn on_close(&mut self) -> Result<(), AeronError> {
         ttrace!("on_close: stopping as agent");
         if !self.is_closed.load(Ordering::SeqCst) {
+            self.drain_responses(Duration::from_millis(CLOSE_DRAIN_TIMEOUT_MS));
             self.close_all_resources((self.epoch_clock)());
         }
 
@@ -1813,7 +2108,7 @@ impl DriverListener for ClientConductor {
             if let Some(maybe_subscription) = &subscr_defn.subscription {
                 if let Some(subscription) = maybe_subscription.upgrade() {
                     let subscriber_position = UnsafeBufferPosition::new(self.counter_values_buffer, subscriber_position_id);
-                    let image = Image::create(
+                    let mut image = Image::create(
                         session_id,
                         correlation_id,
                         subscription_registration_id,
@@ -1822,6 +2117,22 @@ impl DriverListener for ClientConductor {
                         log_buffers.unwrap(),
                         self.error_handler,
                     );
+                    image.set_version_mismatch_counter(VersionMismatchCounter::new(self.version_mismatch_count.clone()));
+
+                    if let Some(group) = &subscr_defn.shared_position_group {
+                        let join_position = image.join_position();
+                        let key = (
+                            group.clone(),
+                            subscr_defn.channel.clone(),
+                            subscr_defn.stream_id,
+                            correlation_id,
+                        );
+                        let shared_position = self
+                            .shared_position_by_key
+                            .entry(key)
+                            .or_insert_with(|| Arc::new(SharedPosition::new(join_position)));
+                        image.set_shared_position(shared_position.clone());
+                    }
 
                     let _callback_guard = CallbackGuard::new(&mut self.is_in_callback);
                     (subscr_defn.on_available_image_handler)(&image);
@@ -1867,6 +2178,10 @@ impl DriverListener for ClientConductor {
         if let Some(images) = linger_images {
             self.linger_resource(now_ms, images);
         }
+
+        // Drop shared positions which are no longer held by any Image.
+        self.shared_position_by_key
+            .retain(|_, shared_position| Arc::strong_count(shared_position) > 1);
     }
 
     fn on_available_counter(&mut self, registration_id: i64, counter_id: i32) {
@@ -1935,7 +2250,7 @@ unsafe impl Sync for ClientConductor {}
 
 #[cfg(test)]
 mod tests {
-    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::atomic::{AtomicBool, AtomicI32, Ordering};
 
     use nix::unistd;
 
@@ -1944,17 +2259,26 @@ mod tests {
     use crate::command::control_protocol_events::AeronCommand;
     use crate::command::counter_message_flyweight::CounterMessageFlyweight;
     use crate::command::error_response_flyweight::{ERROR_CODE_GENERIC_ERROR, ERROR_CODE_INVALID_CHANNEL};
+    use crate::command::operation_succeeded_flyweight::{OperationSucceededFlyweight, OPERATION_SUCCEEDED_LENGTH};
     use crate::command::publication_message_flyweight::PublicationMessageFlyweight;
     use crate::command::remove_message_flyweight::RemoveMessageFlyweight;
     use crate::command::subscription_message_flyweight::SubscriptionMessageFlyweight;
     use crate::concurrent::atomic_buffer::AlignedBuffer;
     use crate::concurrent::broadcast::broadcast_buffer_descriptor;
     use crate::concurrent::broadcast::broadcast_receiver::BroadcastReceiver;
-    use crate::concurrent::logbuffer::log_buffer_descriptor;
+    use crate::concurrent::broadcast::broadcast_transmitter::BroadcastTransmitter;
+    use crate::concurrent::logbuffer::header::Header;
+    use crate::concurrent::logbuffer::{
+        data_frame_header::{self, DataFrameHeaderDefn},
+        frame_descriptor, log_buffer_descriptor,
+    };
     use crate::concurrent::ring_buffer;
     use crate::concurrent::ring_buffer::ManyToOneRingBuffer;
+    use crate::fragment_assembler::FragmentAssembler;
+    use crate::utils::bit_utils::align;
     use crate::utils::memory_mapped_file::MemoryMappedFile;
     use crate::utils::misc::unix_time_ms;
+    use crate::utils::types::Index;
 
     use super::*;
 
@@ -2188,6 +2512,64 @@ mod tests {
         assert!(publication.is_err());
     }
 
+    #[test]
+    fn should_validate_mtu_against_term_length_when_adding_publication() {
+        let test = ClientConductorTest::new();
+        let mut conductor = test.conductor.lock().unwrap();
+
+        let at_limit = "aeron:udp?endpoint=localhost:40123|mtu=8192|term-length=65536";
+        assert!(conductor.add_publication(str_to_c(at_limit), STREAM_ID).is_ok());
+        assert!(conductor.add_exclusive_publication(str_to_c(at_limit), STREAM_ID).is_ok());
+
+        let above_limit = "aeron:udp?endpoint=localhost:40123|mtu=8224|term-length=65536";
+        assert!(conductor.add_publication(str_to_c(above_limit), STREAM_ID).is_err());
+        assert!(conductor.add_exclusive_publication(str_to_c(above_limit), STREAM_ID).is_err());
+    }
+
+    #[test]
+    fn should_validate_mtu_given_with_size_suffix() {
+        let test = ClientConductorTest::new();
+        let mut conductor = test.conductor.lock().unwrap();
+
+        let at_limit = "aeron:udp?endpoint=localhost:40123|mtu=8k|term-length=64k";
+        assert!(conductor.add_publication(str_to_c(at_limit), STREAM_ID).is_ok());
+
+        let within_limit = "aeron:ipc?mtu=1M|term-length=64m";
+        assert!(conductor.add_exclusive_publication(str_to_c(within_limit), STREAM_ID).is_ok());
+
+        let above_limit = "aeron:udp?endpoint=localhost:40123|mtu=16k|term-length=64k";
+        assert!(matches!(
+            conductor.add_publication(str_to_c(above_limit), STREAM_ID),
+            Err(AeronError::IllegalArgumentException(_))
+        ));
+
+        let not_a_size = "aeron:udp?endpoint=localhost:40123|mtu=8x";
+        assert!(conductor.add_publication(str_to_c(not_a_size), STREAM_ID).is_err());
+    }
+
+    #[test]
+    fn should_validate_mtu_against_default_term_length_when_term_length_not_set() {
+        let test = ClientConductorTest::new();
+        let mut conductor = test.conductor.lock().unwrap();
+
+        let within_default = "aeron:udp?endpoint=localhost:40123|mtu=8192";
+        assert!(conductor.add_publication(str_to_c(within_default), STREAM_ID).is_ok());
+
+        let above_udp_default = format!(
+            "aeron:udp?endpoint=localhost:40123|mtu={}",
+            log_buffer_descriptor::TERM_DEFAULT_LENGTH / 8 + 32
+        );
+        assert!(matches!(
+            conductor.add_publication(str_to_c(&above_udp_default), STREAM_ID),
+            Err(AeronError::IllegalArgumentException(_))
+        ));
+
+        let within_ipc_default = format!("aeron:ipc?mtu={}", log_buffer_descriptor::TERM_DEFAULT_LENGTH / 8 + 32);
+        assert!(conductor
+            .add_exclusive_publication(str_to_c(&within_ipc_default), STREAM_ID)
+            .is_ok());
+    }
+
     #[test]
     fn should_send_publication_to_driver() {
         let test = ClientConductorTest::new();
@@ -2480,6 +2862,183 @@ mod tests {
         assert_eq!(publication.session_id(), SESSION_ID);
     }
 
+    fn supply_session_id() -> i32 {
+        SESSION_ID + 7
+    }
+
+    #[test]
+    fn should_use_supplied_session_id_for_exclusive_publication() {
+        let test = ClientConductorTest::new();
+        test.conductor
+            .lock()
+            .unwrap()
+            .set_session_id_supplier(Some(supply_session_id));
+
+        let id = test
+            .conductor
+            .lock()
+            .unwrap()
+            .add_exclusive_publication(str_to_c(CHANNEL), STREAM_ID)
+            .expect("failed to add publication");
+
+        let count = test.many_to_one_ring_buffer.read(
+            |msg_type_id, buffer| {
+                let message = PublicationMessageFlyweight::new(buffer, 0);
+
+                assert_eq!(msg_type_id, AeronCommand::AddExclusivePublication);
+                let channel = message.channel();
+                let channel = channel.to_str().unwrap();
+                assert!(channel.starts_with("aeron:udp?"));
+                assert!(channel.contains(&format!("session-id={}", SESSION_ID + 7)));
+                assert!(channel.contains("endpoint=localhost:40123"));
+            },
+            1000,
+        );
+        assert_eq!(count, 1);
+
+        test.conductor.lock().unwrap().on_new_exclusive_publication(
+            id,
+            id,
+            STREAM_ID,
+            SESSION_ID + 7,
+            PUBLICATION_LIMIT_COUNTER_ID,
+            CHANNEL_STATUS_INDICATOR_ID,
+            str_to_c(&test.log_file_name),
+        );
+
+        let publication = test.conductor.lock().unwrap().find_exclusive_publication(id).unwrap();
+        assert_eq!(publication.lock().unwrap().session_id(), SESSION_ID + 7);
+        assert_eq!(publication.lock().unwrap().channel(), str_to_c(CHANNEL));
+    }
+
+    #[test]
+    fn should_not_override_session_id_given_in_channel() {
+        let test = ClientConductorTest::new();
+        test.conductor
+            .lock()
+            .unwrap()
+            .set_session_id_supplier(Some(supply_session_id));
+        let channel = "aeron:udp?endpoint=localhost:40123|session-id=5";
+
+        test.conductor
+            .lock()
+            .unwrap()
+            .add_exclusive_publication(str_to_c(channel), STREAM_ID)
+            .expect("failed to add publication");
+
+        let count = test.many_to_one_ring_buffer.read(
+            |_msg_type_id, buffer| {
+                let message = PublicationMessageFlyweight::new(buffer, 0);
+                assert_eq!(message.channel(), str_to_c(channel));
+            },
+            1000,
+        );
+        assert_eq!(count, 1);
+    }
+
+    #[test]
+    fn should_add_sparse_flag_to_publication_and_subscription_commands() {
+        let test = ClientConductorTest::new();
+        test.conductor.lock().unwrap().set_sparse_term_buffers(Some(true));
+
+        test.conductor
+            .lock()
+            .unwrap()
+            .add_publication(str_to_c(CHANNEL), STREAM_ID)
+            .expect("failed to add publication");
+        test.conductor
+            .lock()
+            .unwrap()
+            .add_exclusive_publication(str_to_c(CHANNEL), STREAM_ID)
+            .expect("failed to add exclusive publication");
+        test.conductor
+            .lock()
+            .unwrap()
+            .add_subscription(
+                str_to_c(CHANNEL),
+                STREAM_ID,
+                on_available_image_handler,
+                on_unavailable_image_handler,
+            )
+            .expect("failed to add subscription");
+
+        let count = test.many_to_one_ring_buffer.read(
+            |msg_type_id, buffer| {
+                let channel = if msg_type_id == AeronCommand::AddSubscription {
+                    SubscriptionMessageFlyweight::new(buffer, 0).channel()
+                } else {
+                    PublicationMessageFlyweight::new(buffer, 0).channel()
+                };
+                let channel = channel.to_str().unwrap();
+                assert!(channel.contains("sparse=true"));
+                assert!(channel.contains("endpoint=localhost:40123"));
+            },
+            1000,
+        );
+        assert_eq!(count, 3);
+    }
+
+    #[test]
+    fn should_keep_channel_given_for_publication_with_sparse_flag() {
+        let test = ClientConductorTest::new();
+        test.conductor.lock().unwrap().set_sparse_term_buffers(Some(true));
+
+        let id = test
+            .conductor
+            .lock()
+            .unwrap()
+            .add_publication(str_to_c(CHANNEL), STREAM_ID)
+            .expect("failed to add publication");
+
+        test.conductor.lock().unwrap().on_new_publication(
+            id,
+            id,
+            STREAM_ID,
+            SESSION_ID,
+            PUBLICATION_LIMIT_COUNTER_ID,
+            CHANNEL_STATUS_INDICATOR_ID,
+            str_to_c(&test.log_file_name),
+        );
+
+        let publication = test.conductor.lock().unwrap().find_publication(id).unwrap();
+        assert_eq!(publication.lock().unwrap().channel(), str_to_c(CHANNEL));
+    }
+
+    #[test]
+    fn should_decide_sparse_from_channel_before_sparse_flag() {
+        let test = ClientConductorTest::new();
+        let mut conductor = test.conductor.lock().unwrap();
+
+        assert!(!conductor.is_sparse(&str_to_c(CHANNEL)));
+        assert!(conductor.is_sparse(&str_to_c("aeron:ipc?sparse=true")));
+
+        conductor.set_sparse_term_buffers(Some(true));
+        assert!(conductor.is_sparse(&str_to_c(CHANNEL)));
+        assert!(!conductor.is_sparse(&str_to_c("aeron:ipc?sparse=false")));
+    }
+
+    #[test]
+    fn should_not_override_sparse_flag_given_in_channel() {
+        let test = ClientConductorTest::new();
+        test.conductor.lock().unwrap().set_sparse_term_buffers(Some(true));
+        let channel = "aeron:udp?endpoint=localhost:40123|sparse=false";
+
+        test.conductor
+            .lock()
+            .unwrap()
+            .add_publication(str_to_c(channel), STREAM_ID)
+            .expect("failed to add publication");
+
+        let count = test.many_to_one_ring_buffer.read(
+            |_msg_type_id, buffer| {
+                let message = PublicationMessageFlyweight::new(buffer, 0);
+                assert_eq!(message.channel(), str_to_c(channel));
+            },
+            1000,
+        );
+        assert_eq!(count, 1);
+    }
+
     #[test]
     fn should_release_exclusive_publication_after_going_out_of_scope() {
         let test = ClientConductorTest::new();
@@ -2744,6 +3303,89 @@ mod tests {
         assert_eq!(subscription.stream_id(), STREAM_ID);
     }
 
+    #[test]
+    fn should_list_active_publications_and_subscriptions() {
+        let test = ClientConductorTest::new();
+        let other_channel = "aeron:ipc";
+        let mut conductor = test.conductor.lock().unwrap();
+
+        let publication_id = conductor
+            .add_publication(str_to_c(CHANNEL), STREAM_ID)
+            .expect("failed to add publication");
+        let exclusive_publication_id = conductor
+            .add_exclusive_publication(str_to_c(other_channel), STREAM_ID + 1)
+            .expect("failed to add exclusive publication");
+        let subscription_id = conductor
+            .add_subscription(
+                str_to_c(CHANNEL),
+                STREAM_ID,
+                on_available_image_handler,
+                on_unavailable_image_handler,
+            )
+            .unwrap();
+        let other_subscription_id = conductor
+            .add_subscription(
+                str_to_c(other_channel),
+                STREAM_ID + 1,
+                on_available_image_handler,
+                on_unavailable_image_handler,
+            )
+            .unwrap();
+
+        conductor.on_new_publication(
+            publication_id,
+            publication_id,
+            STREAM_ID,
+            SESSION_ID,
+            PUBLICATION_LIMIT_COUNTER_ID,
+            CHANNEL_STATUS_INDICATOR_ID,
+            str_to_c(&test.log_file_name),
+        );
+        conductor.on_subscription_ready(subscription_id, CHANNEL_STATUS_INDICATOR_ID);
+
+        assert_eq!(
+            conductor.active_publications(),
+            vec![
+                PublicationInfo {
+                    channel: str_to_c(CHANNEL),
+                    stream_id: STREAM_ID,
+                    session_id: SESSION_ID,
+                    registration_id: publication_id,
+                    is_exclusive: false,
+                    status: RegistrationStatus::Registered,
+                },
+                PublicationInfo {
+                    channel: str_to_c(other_channel),
+                    stream_id: STREAM_ID + 1,
+                    session_id: -1,
+                    registration_id: exclusive_publication_id,
+                    is_exclusive: true,
+                    status: RegistrationStatus::Awaiting,
+                },
+            ]
+        );
+
+        assert_eq!(
+            conductor.active_subscriptions(),
+            vec![
+                SubscriptionInfo {
+                    channel: str_to_c(CHANNEL),
+                    stream_id: STREAM_ID,
+                    image_session_ids: vec![],
+                    registration_id: subscription_id,
+                    status: RegistrationStatus::Registered,
+                },
+                SubscriptionInfo {
+                    channel: str_to_c(other_channel),
+                    stream_id: STREAM_ID + 1,
+                    image_session_ids: vec![],
+                    registration_id: other_subscription_id,
+                    status: RegistrationStatus::Awaiting,
+                },
+            ]
+        );
+    }
+
     #[test]
     fn should_release_subscription_after_going_out_of_scope() {
         let test = ClientConductorTest::new();
@@ -4186,6 +4828,342 @@ mod tests {
         let val = ON_UNAV_COUNTER_CALLED1.load(Ordering::SeqCst);
         assert_eq!(val, 1);
     }
+    const GROUP_IMAGE_CORRELATION_ID: i64 = 1000;
+
+    fn add_shared_position_group_subscriptions(test: &ClientConductorTest) -> Vec<Arc<Mutex<Subscription>>> {
+        let mut subscriptions = Vec::new();
+        test.conductor
+            .lock()
+            .unwrap()
+            .set_shared_position_group(Some(String::from("group")));
+        for subscriber_position_id in 4..6 {
+            let id = test
+                .conductor
+                .lock()
+                .unwrap()
+                .add_subscription(
+                    str_to_c(CHANNEL),
+                    STREAM_ID,
+                    on_available_image_handler,
+                    on_unavailable_image_handler,
+                )
+                .unwrap();
+            test.conductor
+                .lock()
+                .unwrap()
+                .on_subscription_ready(id, CHANNEL_STATUS_INDICATOR_ID);
+            test.conductor.lock().unwrap().on_available_image(
+                GROUP_IMAGE_CORRELATION_ID,
+                SESSION_ID,
+                subscriber_position_id,
+                id,
+                str_to_c(&test.log_file_name),
+                str_to_c(SOURCE_IDENTITY),
+            );
+            subscriptions.push(test.conductor.lock().unwrap().find_subscription(id).unwrap());
+        }
+
+        for subscription in &subscriptions {
+            assert!(subscription.lock().unwrap().images()[0].is_position_shared());
+        }
+
+        subscriptions
+    }
+
+    #[test]
+    fn should_deliver_each_message_once_across_shared_position_group() {
+        const MESSAGE_COUNT: i32 = 10;
+        let test = ClientConductorTest::new();
+        let subscriptions = add_shared_position_group_subscriptions(&test);
+
+        let mut log_file = MemoryMappedFile::map_existing(&test.log_file_name, false).unwrap();
+        let term_buffer = AtomicBuffer::new(log_file.memory_mut_ptr().as_mut_ptr(), TERM_LENGTH);
+        let aligned_length = align(data_frame_header::LENGTH + 4, frame_descriptor::FRAME_ALIGNMENT);
+        for i in 0..MESSAGE_COUNT {
+            term_buffer.put::<i32>(i * aligned_length + data_frame_header::LENGTH, i);
+            write_data_frame(&term_buffer, i * aligned_length, frame_descriptor::UNFRAGMENTED, 4);
+        }
+
+        let mut received = Vec::new();
+        let mut fragments_read = [0; 2];
+        for _ in 0..MESSAGE_COUNT {
+            for (index, subscription) in subscriptions.iter().enumerate() {
+                fragments_read[index] += subscription.lock().unwrap().poll(
+                    &mut |buffer: &AtomicBuffer, offset: Index, _length: Index, _header: &Header| {
+                        received.push(buffer.get::<i32>(offset))
+                    },
+                    1,
+                );
+            }
+        }
+
+        received.sort_unstable();
+        assert_eq!(received, (0..MESSAGE_COUNT).collect::<Vec<i32>>());
+        assert_eq!(fragments_read, [MESSAGE_COUNT / 2, MESSAGE_COUNT / 2]);
+
+        for subscription in &subscriptions {
+            assert_eq!(
+                subscription.lock().unwrap().images()[0].position(),
+                (MESSAGE_COUNT * aligned_length) as i64
+            );
+        }
+    }
+
+    #[test]
+    fn should_not_publish_position_past_message_still_being_read_by_another_member() {
+        let test = ClientConductorTest::new();
+        let subscriptions = add_shared_position_group_subscriptions(&test);
+
+        let mut log_file = MemoryMappedFile::map_existing(&test.log_file_name, false).unwrap();
+        let term_buffer = AtomicBuffer::new(log_file.memory_mut_ptr().as_mut_ptr(), TERM_LENGTH);
+        let first_end = write_data_frame(&term_buffer, 0, frame_descriptor::UNFRAGMENTED, 4);
+        let second_end = write_data_frame(&term_buffer, first_end, frame_descriptor::UNFRAGMENTED, 4);
+
+        // While the first member reads the first message the second member claims and reads the second one.
+        let mut positions_while_reading = Vec::new();
+        let fragments = subscriptions[0].lock().unwrap().poll(
+            &mut |_buffer: &AtomicBuffer, _offset: Index, _length: Index, _header: &Header| {
+                let mut other = subscriptions[1].lock().unwrap();
+                assert_eq!(
+                    other.poll(
+                        &mut |_buffer: &AtomicBuffer, _offset: Index, _length: Index, _header: &Header| {},
+                        1
+                    ),
+                    1
+                );
+                positions_while_reading.push(other.images()[0].position());
+            },
+            1,
+        );
+
+        assert_eq!(fragments, 1);
+        assert_eq!(positions_while_reading, vec![0]);
+        assert_eq!(subscriptions[0].lock().unwrap().images()[0].position(), second_end as i64);
+    }
+
+    fn write_data_frame(term_buffer: &AtomicBuffer, offset: Index, flags: u8, payload_length: Index) -> Index {
+        let frame = term_buffer.overlay_struct::<DataFrameHeaderDefn>(offset);
+        unsafe {
+            (*frame).version = data_frame_header::CURRENT_VERSION;
+            (*frame).flags = flags;
+            (*frame).frame_type = data_frame_header::HDR_TYPE_DATA;
+            (*frame).term_offset = offset;
+            (*frame).session_id = SESSION_ID;
+            (*frame).stream_id = STREAM_ID;
+        }
+        term_buffer.put_ordered::<i32>(offset, data_frame_header::LENGTH + payload_length);
+
+        offset + align(data_frame_header::LENGTH + payload_length, frame_descriptor::FRAME_ALIGNMENT)
+    }
+
+    #[test]
+    fn should_share_position_only_within_the_same_group() {
+        const MESSAGE_COUNT: i32 = 2;
+        let test = ClientConductorTest::new();
+
+        let groups = [Some("a"), Some("b"), None];
+        let mut subscriptions = Vec::new();
+        for (subscriber_position_id, group) in (4..).zip(groups.iter()) {
+            test.conductor
+                .lock()
+                .unwrap()
+                .set_shared_position_group(group.map(String::from));
+            let id = test
+                .conductor
+                .lock()
+                .unwrap()
+                .add_subscription(
+                    str_to_c(CHANNEL),
+                    STREAM_ID,
+                    on_available_image_handler,
+                    on_unavailable_image_handler,
+                )
+                .unwrap();
+            test.conductor
+                .lock()
+                .unwrap()
+                .on_subscription_ready(id, CHANNEL_STATUS_INDICATOR_ID);
+            test.conductor.lock().unwrap().on_available_image(
+                GROUP_IMAGE_CORRELATION_ID,
+                SESSION_ID,
+                subscriber_position_id,
+                id,
+                str_to_c(&test.log_file_name),
+                str_to_c(SOURCE_IDENTITY),
+            );
+            subscriptions.push(test.conductor.lock().unwrap().find_subscription(id).unwrap());
+        }
+
+        assert!(!subscriptions[2].lock().unwrap().images()[0].is_position_shared());
+
+        let mut log_file = MemoryMappedFile::map_existing(&test.log_file_name, false).unwrap();
+        let term_buffer = AtomicBuffer::new(log_file.memory_mut_ptr().as_mut_ptr(), TERM_LENGTH);
+        let mut offset = 0;
+        for _ in 0..MESSAGE_COUNT {
+            offset = write_data_frame(&term_buffer, offset, frame_descriptor::UNFRAGMENTED, 4);
+        }
+
+        // Each group and the ungrouped Subscription consume the stream independently.
+        for subscription in &subscriptions {
+            assert_eq!(
+                subscription.lock().unwrap().poll(
+                    &mut |_buffer: &AtomicBuffer, _offset: Index, _length: Index, _header: &Header| {},
+                    10
+                ),
+                MESSAGE_COUNT
+            );
+        }
+    }
+
+    #[test]
+    fn should_deliver_whole_fragmented_messages_across_shared_position_group() {
+        const PAYLOAD_LENGTH: Index = 64;
+        const MESSAGE_COUNT: usize = 4;
+        let test = ClientConductorTest::new();
+        let subscriptions = add_shared_position_group_subscriptions(&test);
+
+        let mut log_file = MemoryMappedFile::map_existing(&test.log_file_name, false).unwrap();
+        let term_buffer = AtomicBuffer::new(log_file.memory_mut_ptr().as_mut_ptr(), TERM_LENGTH);
+        let mut offset = 0;
+        for _ in 0..MESSAGE_COUNT {
+            offset = write_data_frame(&term_buffer, offset, frame_descriptor::BEGIN_FRAG, PAYLOAD_LENGTH);
+            offset = write_data_frame(&term_buffer, offset, 0, PAYLOAD_LENGTH);
+            offset = write_data_frame(&term_buffer, offset, frame_descriptor::END_FRAG, PAYLOAD_LENGTH);
+        }
+
+        // Each member polls a single fragment at a time yet must see every message it claims from BEGIN to END.
+        let mut flags_by_member = [Vec::new(), Vec::new()];
+        for _ in 0..MESSAGE_COUNT {
+            for (index, subscription) in subscriptions.iter().enumerate() {
+                subscription.lock().unwrap().poll(
+                    &mut |_buffer: &AtomicBuffer, _offset: Index, _length: Index, header: &Header| {
+                        flags_by_member[index].push(header.flags())
+                    },
+                    1,
+                );
+            }
+        }
+
+        let message_flags = [frame_descriptor::BEGIN_FRAG, 0, frame_descriptor::END_FRAG];
+        for flags in &flags_by_member {
+            assert_eq!(flags.len(), message_flags.len() * MESSAGE_COUNT / 2);
+            for message in flags.chunks(message_flags.len()) {
+                assert_eq!(message, message_flags);
+            }
+        }
+        assert_eq!(subscriptions[0].lock().unwrap().images()[0].position(), offset as i64);
+    }
+
+    #[test]
+    fn should_discard_stale_partial_message_when_session_rejoins() {
+        const PAYLOAD_LENGTH: Index = 100;
+        let test = ClientConductorTest::new();
+
+        let id = test
+            .conductor
+            .lock()
+            .unwrap()
+            .add_subscription(
+                str_to_c(CHANNEL),
+                STREAM_ID,
+                on_available_image_handler,
+                on_unavailable_image_handler,
+            )
+            .unwrap();
+        test.conductor
+            .lock()
+            .unwrap()
+            .on_subscription_ready(id, CHANNEL_STATUS_INDICATOR_ID);
+        test.conductor.lock().unwrap().on_available_image(
+            id + 1,
+            SESSION_ID,
+            4,
+            id,
+            str_to_c(&test.log_file_name),
+            str_to_c(SOURCE_IDENTITY),
+        );
+        let subscription = test.conductor.lock().unwrap().find_subscription(id).unwrap();
+
+        let mut old_log_file = MemoryMappedFile::map_existing(&test.log_file_name, false).unwrap();
+        let old_term_buffer = AtomicBuffer::new(old_log_file.memory_mut_ptr().as_mut_ptr(), TERM_LENGTH);
+        let mut new_log_file = MemoryMappedFile::map_existing(&test.log_file_name2, false).unwrap();
+        let new_term_buffer = AtomicBuffer::new(new_log_file.memory_mut_ptr().as_mut_ptr(), TERM_LENGTH);
+
+        let calls = AtomicI32::new(0);
+        let last_length = AtomicI32::new(0);
+        let mut delegate = |_buffer: &AtomicBuffer, _offset: Index, length: Index, _header: &Header| {
+            calls.fetch_add(1, Ordering::SeqCst);
+            last_length.store(length, Ordering::SeqCst);
+        };
+        let mut assembler = FragmentAssembler::new(&mut delegate, None);
+        let mut handler = assembler.handler();
+
+        write_data_frame(&old_term_buffer, 0, frame_descriptor::BEGIN_FRAG, PAYLOAD_LENGTH);
+        assert_eq!(subscription.lock().unwrap().poll(&mut handler, 10), 1);
+
+        // the old image goes away and the same session rejoins on a new image
+        test.conductor.lock().unwrap().on_unavailable_image(id + 1, id);
+        test.conductor.lock().unwrap().on_available_image(
+            id + 2,
+            SESSION_ID,
+            5,
+            id,
+            str_to_c(&test.log_file_name2),
+            str_to_c(SOURCE_IDENTITY),
+        );
+
+        let mut offset = write_data_frame(&new_term_buffer, 0, frame_descriptor::END_FRAG, PAYLOAD_LENGTH);
+        assert_eq!(subscription.lock().unwrap().poll(&mut handler, 10), 1);
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+
+        offset = write_data_frame(&new_term_buffer, offset, frame_descriptor::BEGIN_FRAG, PAYLOAD_LENGTH);
+        write_data_frame(&new_term_buffer, offset, frame_descriptor::END_FRAG, PAYLOAD_LENGTH);
+        assert_eq!(subscription.lock().unwrap().poll(&mut handler, 10), 2);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert_eq!(last_length.load(Ordering::SeqCst), PAYLOAD_LENGTH * 2);
+    }
+
+    #[test]
+    fn should_keep_old_image_until_unavailable_when_session_rejoins() {
+        let test = ClientConductorTest::new();
+
+        let id = test
+            .conductor
+            .lock()
+            .unwrap()
+            .add_subscription(
+                str_to_c(CHANNEL),
+                STREAM_ID,
+                on_available_image_handler,
+                on_unavailable_image_handler,
+            )
+            .unwrap();
+        test.conductor
+            .lock()
+            .unwrap()
+            .on_subscription_ready(id, CHANNEL_STATUS_INDICATOR_ID);
+
+        for (image_correlation_id, log_file_name) in [(id + 1, &test.log_file_name), (id + 2, &test.log_file_name2)].iter() {
+            test.conductor.lock().unwrap().on_available_image(
+                *image_correlation_id,
+                SESSION_ID,
+                4,
+                id,
+                str_to_c(log_file_name),
+                str_to_c(SOURCE_IDENTITY),
+            );
+        }
+
+        // The old Image stays until the driver reports it unavailable.
+        let subscription = test.conductor.lock().unwrap().find_subscription(id).unwrap();
+        assert_eq!(subscription.lock().unwrap().image_count(), 2);
+
+        test.conductor.lock().unwrap().on_unavailable_image(id + 1, id);
+        assert_eq!(subscription.lock().unwrap().image_count(), 1);
+        assert!(!subscription.lock().unwrap().has_image(id + 1));
+        assert!(subscription.lock().unwrap().has_image(id + 2));
+    }
+
     /*
     fn on_available_counter3(_counters_reader: &CountersReader, _registration_id: i64, _counter_id: i32) {
         let mut val = ON_AV_COUNTER_CALLED3.load(Ordering::SeqCst);
@@ -4246,4 +5224,70 @@ mod tests {
         assert!(err_handler_called);
     }
     */
+
+    fn transmit_operation_success(to_clients_buffer: AtomicBuffer, correlation_ids: &[i64]) {
+        let mut transmitter = BroadcastTransmitter::new(to_clients_buffer).unwrap();
+        let message = AlignedBuffer::with_capacity(OPERATION_SUCCEEDED_LENGTH);
+        let message_buffer = AtomicBuffer::from_aligned(&message);
+
+        for correlation_id in correlation_ids {
+            let mut operation_succeeded = OperationSucceededFlyweight::new(message_buffer, 0);
+            operation_succeeded.set_correlation_id(*correlation_id);
+            transmitter
+                .transmit(
+                    AeronCommand::ResponseOnOperationSuccess as i32,
+                    &message_buffer,
+                    0,
+                    OPERATION_SUCCEEDED_LENGTH,
+                )
+                .unwrap();
+        }
+    }
+
+    #[test]
+    fn should_drain_pending_responses_within_timeout() {
+        let test = ClientConductorTest::new();
+        let mut conductor = test.conductor.lock().unwrap();
+
+        let correlation_ids: Vec<i64> = (0..5)
+            .map(|_| conductor.add_destination(1, str_to_c(CHANNEL)).unwrap())
+            .collect();
+        for correlation_id in &correlation_ids {
+            assert!(!conductor.find_destination_response(*correlation_id).unwrap());
+        }
+
+        transmit_operation_success(test.to_clients_buffer, &correlation_ids);
+
+        assert_eq!(conductor.drain_responses(Duration::from_millis(100)), 5);
+        for correlation_id in &correlation_ids {
+            assert!(conductor.find_destination_response(*correlation_id).unwrap());
+        }
+
+        assert_eq!(conductor.drain_responses(Duration::from_millis(100)), 0);
+    }
+
+    #[test]
+    fn should_stop_draining_responses_when_timeout_expires() {
+        let test = ClientConductorTest::new();
+        let mut conductor = test.conductor.lock().unwrap();
+
+        let correlation_ids: Vec<i64> = (0..5)
+            .map(|_| conductor.add_destination(1, str_to_c(CHANNEL)).unwrap())
+            .collect();
+        transmit_operation_success(test.to_clients_buffer, &correlation_ids);
+
+        // Every read of the clock advances it by 10 ms so the deadline passes after two responses.
+        let current_time = test.current_time.clone();
+        conductor.set_epoch_clock_provider(Box::new(move || {
+            let mut now = current_time.lock().unwrap();
+            *now += 10;
+            *now
+        }));
+
+        assert_eq!(conductor.drain_responses(Duration::from_millis(15)), 2);
+        assert!(conductor.find_destination_response(correlation_ids[1]).unwrap());
+        assert!(!conductor.find_destination_response(correlation_ids[2]).unwrap());
+
+        assert_eq!(conductor.drain_responses(Duration::from_millis(100)), 3);
+    }
 }
@@ -2,11 +2,12 @@ use std::{
     ffi::{CStr, CString},
     fmt::{Debug, Error, Formatter},
     io::Write,
-    slice,
+    ptr, slice,
     sync::atomic::{fence, AtomicI32, AtomicI64, Ordering},
 };
 
 use crate::utils::{
+    errors::AeronError,
     misc::{alloc_buffer_aligned, dealloc_buffer_aligned},
     types::{Index, I32_SIZE, I64_SIZE},
 };
@@ -41,21 +42,12 @@ pub struct AtomicBuffer {
 
 impl Debug for AtomicBuffer {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
-        let mut slice = self.as_slice();
         const TAKE_LIMIT: usize = 40;
-        let mut bytes_counter = 0;
-        loop {
-            write!(f, "{}: ", bytes_counter)?;
-            bytes_counter += TAKE_LIMIT;
-
-            let (head, tail) = slice.split_at(TAKE_LIMIT);
-            if tail.len() > TAKE_LIMIT {
-                writeln!(f, "{:?}", head)?;
-                slice = tail;
-            } else {
-                write!(f, "{:?}", tail)?;
-                break;
+        for (i, chunk) in self.as_slice().chunks(TAKE_LIMIT).enumerate() {
+            if i > 0 {
+                writeln!(f)?;
             }
+            write!(f, "{}: {:?}", i * TAKE_LIMIT, chunk)?;
         }
         Ok(())
     }
@@ -122,9 +114,30 @@ impl AtomicBuffer {
         self.len
     }
 
+    /// Panics unless idx..idx + len lies within the buffer. Any access to a zero-length buffer is out of bounds.
     #[inline]
     pub fn bounds_check(&self, idx: Index, len: Index) {
-        assert!((idx + len as Index) <= self.len)
+        assert!(
+            idx >= 0 && len >= 0 && idx as i64 + len as i64 <= self.len as i64,
+            "index out of bounds: index={} length={} capacity={}",
+            idx,
+            len,
+            self.len
+        )
+    }
+
+    /// Check that the range of length bytes from offset lies within this buffer, so a caller can validate
+    /// before taking an action that cannot be undone.
+    #[inline]
+    pub fn check_range(&self, offset: Index, length: Index) -> Result<(), AeronError> {
+        if offset < 0 || length < 0 || offset as i64 + length as i64 > self.len as i64 {
+            return Err(AeronError::IllegalArgumentException(format!(
+                "range out of bounds: offset={} length={} capacity={}",
+                offset, length, self.len
+            )));
+        }
+
+        Ok(())
     }
 
     #[inline]
@@ -133,6 +146,23 @@ impl AtomicBuffer {
         unsafe { *(self.at(position) as *mut T) }
     }
 
+    /// Like get but returns IllegalArgumentException rather than panicking when the value does not fit in
+    /// the buffer.
+    #[inline]
+    pub fn try_get<T: Copy>(&self, position: Index) -> Result<T, AeronError> {
+        self.check_range(position, std::mem::size_of::<T>() as Index)?;
+        Ok(unsafe { *(self.at(position) as *mut T) })
+    }
+
+    /// Like put but returns IllegalArgumentException rather than panicking when the value does not fit in
+    /// the buffer.
+    #[inline]
+    pub fn try_put<T>(&self, position: Index, val: T) -> Result<(), AeronError> {
+        self.check_range(position, std::mem::size_of::<T>() as Index)?;
+        unsafe { *(self.at(position) as *mut T) = val }
+        Ok(())
+    }
+
     #[inline]
     pub fn overlay_struct<T>(&self, position: Index) -> *mut T {
         self.bounds_check(position, std::mem::size_of::<T>() as Index);
@@ -164,7 +194,8 @@ impl AtomicBuffer {
     #[inline]
     pub fn get_volatile<T: Copy>(&self, position: Index) -> T {
         self.bounds_check(position, std::mem::size_of::<T>() as Index);
-        let read = self.get(position);
+        // A volatile read can't be hoisted out of a spin loop, so flags written by the driver are re-read each time
+        let read = unsafe { ptr::read_volatile(self.at(position) as *const T) };
         fence(Ordering::Acquire);
         read
     }
@@ -173,7 +204,7 @@ impl AtomicBuffer {
     pub fn put_ordered<T>(&self, position: Index, val: T) {
         self.bounds_check(position, std::mem::size_of::<T>() as Index);
         fence(Ordering::Release);
-        self.put(position, val);
+        unsafe { ptr::write_volatile(self.at(position) as *mut T, val) }
     }
 
     #[inline]
@@ -252,32 +283,53 @@ impl AtomicBuffer {
     }
 
     /// Copy "length" bytes from "src_buffer" starting from "src_offset" in to this buffer at given "offset"
-    /// offset - offset in current (self) buffer to start coping from
+    /// Both ranges are validated before copying and may overlap, e.g. when the buffers are views of the same memory.
+    /// offset - offset in current (self) buffer to start coping to
     /// src_buffer - atomic buffer to copy data from
     /// src_offset - offset in src_buffer to start coping from
     /// length - number of bytes to copy
     #[inline]
-    pub fn copy_from(&self, offset: Index, src_buffer: &AtomicBuffer, src_offset: Index, length: Index) {
-        self.bounds_check(offset, length);
-        src_buffer.bounds_check(src_offset, length);
+    pub fn copy_from(
+        &self,
+        offset: Index,
+        src_buffer: &AtomicBuffer,
+        src_offset: Index,
+        length: Index,
+    ) -> Result<(), AeronError> {
+        self.check_range(offset, length)?;
+        src_buffer.check_range(src_offset, length)?;
+        if length == 0 {
+            return Ok(());
+        }
+
         unsafe {
             let src_ptr = src_buffer.at(src_offset);
             let dest_ptr = self.at(offset);
-            // TODO: check that memory regions are actually not overlapping, otherwise UB!
-            std::ptr::copy_nonoverlapping(src_ptr, dest_ptr, length as usize);
+            std::ptr::copy(src_ptr, dest_ptr, length as usize);
         }
+
+        Ok(())
     }
 
     pub fn as_mutable_slice(&mut self) -> &mut [u8] {
+        if self.len == 0 {
+            return &mut [];
+        }
         unsafe { slice::from_raw_parts_mut(self.ptr, self.len as usize) }
     }
 
     pub fn as_slice(&self) -> &[u8] {
+        if self.len == 0 {
+            return &[];
+        }
         unsafe { slice::from_raw_parts(self.ptr, self.len as usize) }
     }
 
     pub fn as_sub_slice(&self, index: Index, len: Index) -> &[u8] {
         self.bounds_check(index, len);
+        if len == 0 {
+            return &[];
+        }
         unsafe { slice::from_raw_parts(self.at(index), len as usize) }
     }
 
@@ -352,6 +404,7 @@ impl AtomicBuffer {
 #[cfg(test)]
 mod tests {
     use crate::concurrent::atomic_buffer::{AlignedBuffer, AtomicBuffer};
+    use crate::utils::errors::AeronError;
     use crate::utils::types::Index;
     use std::io::Write;
 
@@ -446,6 +499,38 @@ mod tests {
         assert_eq!(sub_slice, &[3, 4])
     }
 
+    #[test]
+    fn atomic_buffer_zero_length_is_empty_and_rejects_any_access() {
+        let aligned = AlignedBuffer::with_capacity(0);
+        let buffers = [
+            AtomicBuffer::new(std::ptr::null_mut(), 0),
+            AtomicBuffer::from_aligned(&aligned),
+        ];
+
+        for buffer in buffers.iter() {
+            assert_eq!(buffer.capacity(), 0);
+            assert!(buffer.as_slice().is_empty());
+            assert!(matches!(
+                buffer.try_get::<u8>(0),
+                Err(AeronError::IllegalArgumentException(_))
+            ));
+            assert!(matches!(
+                buffer.try_put::<i64>(0, 7),
+                Err(AeronError::IllegalArgumentException(_))
+            ));
+            assert!(buffer.copy_from(0, buffer, 0, 0).is_ok());
+            assert!(buffer.copy_from(0, buffer, 0, 1).is_err());
+            assert_eq!(format!("{:?}", buffer), "");
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn atomic_buffer_zero_length_get_panics_on_bounds_check() {
+        let buffer = AtomicBuffer::new(std::ptr::null_mut(), 0);
+        let _value = buffer.get::<u8>(0);
+    }
+
     #[test]
     #[should_panic]
     fn atomic_buffer_get_sub_slice_out_of_bounds() {
@@ -469,4 +554,36 @@ mod tests {
         assert_eq!(read_str.as_bytes().len(), 9);
         assert_eq!(read_str.as_bytes(), test_string); // as_bytes() returns string body without trailing zero
     }
+
+    #[test]
+    fn atomic_buffer_copy_from_overlapping_views() {
+        let src = AlignedBuffer::with_capacity(16);
+        let buffer = AtomicBuffer::from_aligned(&src);
+        buffer.put_bytes(0, &[1, 2, 3, 4, 5, 6, 7, 8]);
+
+        // Destination view starts inside the source view so the copied ranges overlap
+        let src_view = buffer.view(0, 8);
+        let dest_view = buffer.view(4, 12);
+        dest_view.copy_from(0, &src_view, 0, 8).unwrap();
+
+        assert_eq!(buffer.as_sub_slice(4, 8), &[1, 2, 3, 4, 5, 6, 7, 8]);
+    }
+
+    #[test]
+    fn atomic_buffer_copy_from_rejects_out_of_bounds_ranges() {
+        let src = AlignedBuffer::with_capacity(16);
+        let dest = AlignedBuffer::with_capacity(8);
+        let src_buffer = AtomicBuffer::from_aligned(&src);
+        let dest_buffer = AtomicBuffer::from_aligned(&dest);
+
+        assert!(dest_buffer.copy_from(0, &src_buffer, 0, 8).is_ok());
+        assert_eq!(
+            dest_buffer.copy_from(0, &src_buffer, 0, 9),
+            Err(AeronError::IllegalArgumentException(String::new()))
+        );
+        assert!(dest_buffer.copy_from(4, &src_buffer, 0, 5).is_err());
+        assert!(dest_buffer.copy_from(0, &src_buffer, 12, 8).is_err());
+        assert!(dest_buffer.copy_from(-1, &src_buffer, 0, 1).is_err());
+        assert!(dest_buffer.copy_from(0, &src_buffer, 0, -1).is_err());
+    }
 }
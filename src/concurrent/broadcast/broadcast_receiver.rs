@@ -125,6 +125,16 @@ impl BroadcastReceiver {
         is_available
     }
 
+    /// Moves the cursor to the latest record written by the transmitter so that receiving can carry on
+    /// after being lapped. Any records between the old cursor and the latest record are skipped.
+    pub fn reset_to_latest(&mut self) {
+        let latest = self.buffer.get_volatile::<i64>(self.latest_counter_index);
+
+        self.cursor = latest;
+        self.next_record = latest;
+        self.record_offset = latest as Index & self.mask;
+    }
+
     pub fn validate(&self) -> bool {
         atomics::acquire();
         self.do_validate(self.cursor as Index)
@@ -96,7 +96,8 @@ impl BroadcastTransmitter {
             .put::<i32>(record_descriptor::type_offset(record_offset), msg_type_id);
 
         self.buffer
-            .copy_from(record_descriptor::msg_offset(record_offset), src_buffer, src_index, length);
+            .copy_from(record_descriptor::msg_offset(record_offset), src_buffer, src_index, length)
+            .expect("copy_from failed");
 
         self.buffer.put::<i64>(self.latest_counter_index, current_tail);
         self.buffer
@@ -45,6 +45,12 @@ impl CopyBroadcastReceiver {
         }
     }
 
+    /// Skips to the latest record in the broadcast buffer. Called by receive() when the receiver has been
+    /// lapped by the transmitter.
+    pub fn reset_to_latest(&self) {
+        self.receiver.lock().expect("Mutex poisoned").reset_to_latest();
+    }
+
     pub fn receive<F>(&mut self, mut handler: F) -> Result<usize, BroadcastTransmitError>
     where
         F: FnMut(AeronCommand, AtomicBuffer, Index, Index),
@@ -55,6 +61,8 @@ impl CopyBroadcastReceiver {
 
         if receiver.receive_next() {
             if last_seen_lapped_count != receiver.lapped_count() {
+                // Report the gap and carry on from the latest record on the next receive rather than getting stuck
+                receiver.reset_to_latest();
                 return Err(BroadcastTransmitError::UnableToKeepUpWithBroadcastBuffer);
             }
 
@@ -68,9 +76,12 @@ impl CopyBroadcastReceiver {
 
             let msg = AeronCommand::from_command_id(receiver.type_id());
 
-            self.scratch_buffer.copy_from(0, receiver.buffer(), receiver.offset(), length);
+            self.scratch_buffer
+                .copy_from(0, receiver.buffer(), receiver.offset(), length)
+                .expect("copy_from failed");
 
             if !receiver.validate() {
+                receiver.reset_to_latest();
                 return Err(BroadcastTransmitError::UnableToKeepUpWithBroadcastBuffer);
             }
 
@@ -82,3 +93,63 @@ impl CopyBroadcastReceiver {
         Ok(messages_received)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::concurrent::broadcast::{broadcast_buffer_descriptor, broadcast_transmitter::BroadcastTransmitter};
+
+    const CAPACITY: Index = 1024;
+    const MSG_TYPE_ID: i32 = AeronCommand::ResponseOnOperationSuccess as i32;
+
+    fn transmit_value(transmitter: &mut BroadcastTransmitter, value: i64) {
+        let src = AlignedBuffer::with_capacity(16);
+        let src_buffer = AtomicBuffer::from_aligned(&src);
+        src_buffer.put::<i64>(0, value);
+        transmitter.transmit(MSG_TYPE_ID, &src_buffer, 0, 8).unwrap();
+    }
+
+    #[test]
+    fn should_resume_from_latest_record_after_being_lapped() {
+        let data = AlignedBuffer::with_capacity(CAPACITY + broadcast_buffer_descriptor::TRAILER_LENGTH);
+        let buffer = AtomicBuffer::from_aligned(&data);
+        let mut transmitter = BroadcastTransmitter::new(buffer).unwrap();
+        let mut receiver = CopyBroadcastReceiver::new(Arc::new(Mutex::new(BroadcastReceiver::new(buffer).unwrap())));
+
+        // Write several times the capacity of the buffer without receiving so the receiver is lapped.
+        let records = 4 * CAPACITY as i64 / 16;
+        for value in 0..records {
+            transmit_value(&mut transmitter, value);
+        }
+
+        let mut received = vec![];
+        match receiver.receive(|_msg, buffer, offset, _length| received.push(buffer.get::<i64>(offset))) {
+            Err(BroadcastTransmitError::UnableToKeepUpWithBroadcastBuffer) => {}
+            other => panic!("expected lap to be reported, got {:?}", other),
+        }
+        assert!(received.is_empty());
+
+        // The receiver carries on from the latest record and then reads new records as they arrive.
+        assert_eq!(
+            receiver
+                .receive(|_msg, buffer, offset, _length| received.push(buffer.get::<i64>(offset)))
+                .unwrap(),
+            1
+        );
+        transmit_value(&mut transmitter, records);
+        assert_eq!(
+            receiver
+                .receive(|_msg, buffer, offset, _length| received.push(buffer.get::<i64>(offset)))
+                .unwrap(),
+            1
+        );
+        assert_eq!(
+            receiver
+                .receive(|_msg, buffer, offset, _length| received.push(buffer.get::<i64>(offset)))
+                .unwrap(),
+            0
+        );
+
+        assert_eq!(received, vec![records - 1, records]);
+    }
+}
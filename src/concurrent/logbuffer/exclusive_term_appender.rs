@@ -24,11 +24,12 @@ use crate::{
             data_frame_header, frame_descriptor,
             header::HeaderWriter,
             log_buffer_descriptor,
-            term_appender::{OnReservedValueSupplier, TERM_APPENDER_FAILED},
+            term_appender::{OnReservedValueSupplier, TermAppender, TERM_APPENDER_FAILED},
         },
     },
     utils::{
         bit_utils,
+        errors::AeronError,
         types::{Index, I64_SIZE},
     },
 };
@@ -72,7 +73,7 @@ impl ExclusiveTermAppender {
         buffer_claim: &mut BufferClaim,
     ) -> Index {
         let frame_length = length + data_frame_header::LENGTH;
-        let aligned_length = bit_utils::align(frame_length, frame_descriptor::FRAME_ALIGNMENT);
+        let aligned_length = frame_descriptor::aligned_frame_length(frame_length);
 
         let term_length = self.term_buffer.capacity();
         let mut resulting_offset = term_offset + aligned_length;
@@ -88,6 +89,7 @@ impl ExclusiveTermAppender {
         resulting_offset
     }
 
+    /// The extra_flags are OR-ed into the BEGIN/END flags of the frame, see frame_descriptor::check_extra_flags.
     #[allow(clippy::too_many_arguments)]
     pub fn append_unfragmented_message(
         &mut self,
@@ -98,9 +100,12 @@ impl ExclusiveTermAppender {
         src_offset: Index,
         length: Index,
         reserved_value_supplier: OnReservedValueSupplier,
-    ) -> Index {
+        extra_flags: u8,
+    ) -> Result<Index, AeronError> {
+        src_buffer.check_range(src_offset, length)?;
+
         let frame_length = length + data_frame_header::LENGTH;
-        let aligned_length = bit_utils::align(frame_length, frame_descriptor::FRAME_ALIGNMENT);
+        let aligned_length = frame_descriptor::aligned_frame_length(frame_length);
 
         let term_length = self.term_buffer.capacity();
 
@@ -111,8 +116,11 @@ impl ExclusiveTermAppender {
             resulting_offset = Self::handle_end_of_log_condition(&self.term_buffer, term_id, term_offset, header, term_length);
         } else {
             header.write(&self.term_buffer, term_offset, frame_length, term_id);
+            if extra_flags != 0 {
+                frame_descriptor::set_frame_flags(&self.term_buffer, term_offset, frame_descriptor::UNFRAGMENTED | extra_flags);
+            }
             self.term_buffer
-                .copy_from(term_offset + data_frame_header::LENGTH, &src_buffer, src_offset, length);
+                .copy_from(term_offset + data_frame_header::LENGTH, &src_buffer, src_offset, length)?;
 
             let reserved_value = reserved_value_supplier(self.term_buffer, term_offset, frame_length);
             self.term_buffer
@@ -121,7 +129,7 @@ impl ExclusiveTermAppender {
             frame_descriptor::set_frame_length_ordered(&self.term_buffer, term_offset, frame_length);
         }
 
-        resulting_offset
+        Ok(resulting_offset)
     }
 
     //TODO: Not sure that its used! Port it if actually used or remove from here!
@@ -133,9 +141,11 @@ impl ExclusiveTermAppender {
         buffers: Vec<AtomicBuffer>,
         length: Index,
         reserved_value_supplier: OnReservedValueSupplier,
-    ) -> Index {
+    ) -> Result<Index, AeronError> {
+        TermAppender::check_buffers_length(&buffers, length)?;
+
         let frame_length: Index = length + data_frame_header::LENGTH;
-        let aligned_length: Index = bit_utils::align(frame_length, frame_descriptor::FRAME_ALIGNMENT);
+        let aligned_length: Index = frame_descriptor::aligned_frame_length(frame_length);
 
         let term_length = self.term_buffer.capacity();
         let mut resulting_offset = term_offset + aligned_length;
@@ -149,15 +159,18 @@ impl ExclusiveTermAppender {
             header.write(&self.term_buffer, term_offset, frame_length, term_id);
 
             let mut offset = term_offset + data_frame_header::LENGTH;
+            let mut remaining = length;
 
             for buf in buffers.iter() {
-                let ending_offset = offset + length;
-                if offset >= ending_offset {
+                if remaining <= 0 {
                     break;
                 }
-                offset += buf.capacity();
+                let num_bytes = std::cmp::min(remaining, buf.capacity());
+
+                self.term_buffer.copy_from(offset, buf, 0, num_bytes)?;
 
-                self.term_buffer.copy_from(offset, buf, 0, buf.capacity());
+                offset += num_bytes;
+                remaining -= num_bytes;
             }
 
             let reserved_value = reserved_value_supplier(self.term_buffer, term_offset, frame_length);
@@ -167,9 +180,10 @@ impl ExclusiveTermAppender {
             frame_descriptor::set_frame_length_ordered(&self.term_buffer, term_offset, frame_length);
         }
 
-        resulting_offset
+        Ok(resulting_offset)
     }
 
+    /// The extra_flags are OR-ed into the BEGIN/END flags of each frame, see frame_descriptor::check_extra_flags.
     #[allow(clippy::too_many_arguments)]
     pub fn append_fragmented_message(
         &mut self,
@@ -181,7 +195,10 @@ impl ExclusiveTermAppender {
         length: Index,
         max_payload_length: Index,
         reserved_value_supplier: OnReservedValueSupplier,
-    ) -> Index {
+        extra_flags: u8,
+    ) -> Result<Index, AeronError> {
+        src_buffer.check_range(src_offset, length)?;
+
         let num_max_payloads = length / max_payload_length;
         let remaining_payload = length % max_payload_length;
         let last_frame_length = if remaining_payload > 0 {
@@ -203,14 +220,14 @@ impl ExclusiveTermAppender {
         if resulting_offset > term_length {
             resulting_offset = Self::handle_end_of_log_condition(&self.term_buffer, term_id, term_offset, header, term_length);
         } else {
-            let mut flags = frame_descriptor::BEGIN_FRAG;
+            let mut flags = frame_descriptor::BEGIN_FRAG | extra_flags;
             let mut remaining = length;
             let mut offset = term_offset;
 
             loop {
                 let bytes_to_write = std::cmp::min(remaining, max_payload_length);
                 let frame_length = bytes_to_write + data_frame_header::LENGTH;
-                let aligned_length = bit_utils::align(frame_length, frame_descriptor::FRAME_ALIGNMENT);
+                let aligned_length = frame_descriptor::aligned_frame_length(frame_length);
 
                 header.write(&self.term_buffer, offset, frame_length, term_id);
                 self.term_buffer.copy_from(
@@ -218,7 +235,7 @@ impl ExclusiveTermAppender {
                     &src_buffer,
                     src_offset + (length - remaining),
                     bytes_to_write,
-                );
+                )?;
 
                 if remaining <= max_payload_length {
                     flags |= frame_descriptor::END_FRAG;
@@ -232,7 +249,7 @@ impl ExclusiveTermAppender {
 
                 frame_descriptor::set_frame_length_ordered(&self.term_buffer, offset, frame_length);
 
-                flags = 0;
+                flags = extra_flags;
                 offset += aligned_length;
                 remaining -= bytes_to_write;
 
@@ -242,7 +259,7 @@ impl ExclusiveTermAppender {
             }
         }
 
-        resulting_offset
+        Ok(resulting_offset)
     }
 
     /* TODO: Not sure that its used! Port it if actually used or remove from here!
@@ -330,6 +347,27 @@ impl ExclusiveTermAppender {
     }
     */
 
+    /// Append a padding frame (HDR_TYPE_PAD) of length bytes plus the frame header at term_offset so the next
+    /// message starts at a chosen alignment. Readers skip the padding without delivering it.
+    /// Returns the resulting term offset after the padding. If the padding does not fit in the term, the rest of
+    /// the term is padded instead and TERM_APPENDER_FAILED is returned so the publication rotates.
+    pub fn append_padding(&mut self, term_id: i32, term_offset: Index, header: &HeaderWriter, length: Index) -> Index {
+        let frame_length = length + data_frame_header::LENGTH;
+        let aligned_length = frame_descriptor::aligned_frame_length(frame_length);
+
+        let term_length = self.term_buffer.capacity();
+        let mut resulting_offset = term_offset + aligned_length;
+        self.put_raw_tail_ordered(term_id as i64, resulting_offset);
+
+        if resulting_offset > term_length {
+            resulting_offset = Self::handle_end_of_log_condition(&self.term_buffer, term_id, term_offset, header, term_length);
+        } else {
+            Self::write_padding(&self.term_buffer, term_id, term_offset, header, frame_length);
+        }
+
+        resulting_offset
+    }
+
     fn handle_end_of_log_condition(
         term_buffer: &AtomicBuffer,
         term_id: i32,
@@ -338,15 +376,18 @@ impl ExclusiveTermAppender {
         term_length: Index,
     ) -> Index {
         if term_offset < term_length {
-            let padding_length = term_length - term_offset;
-            header.write(term_buffer, term_offset, padding_length, term_id);
-            frame_descriptor::set_frame_type(term_buffer, term_offset, data_frame_header::HDR_TYPE_PAD);
-            frame_descriptor::set_frame_length_ordered(term_buffer, term_offset, padding_length);
+            Self::write_padding(term_buffer, term_id, term_offset, header, term_length - term_offset);
         }
 
         TERM_APPENDER_FAILED
     }
 
+    fn write_padding(term_buffer: &AtomicBuffer, term_id: i32, term_offset: Index, header: &HeaderWriter, padding_length: Index) {
+        header.write(term_buffer, term_offset, padding_length, term_id);
+        frame_descriptor::set_frame_type(term_buffer, term_offset, data_frame_header::HDR_TYPE_PAD);
+        frame_descriptor::set_frame_length_ordered(term_buffer, term_offset, padding_length);
+    }
+
     fn put_raw_tail_ordered(&mut self, term_id: i64, term_offset: Index) {
         unsafe {
             fence(Ordering::Release);
@@ -354,3 +395,163 @@ impl ExclusiveTermAppender {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::concurrent::{
+        atomic_buffer::AlignedBuffer,
+        logbuffer::{
+            frame_descriptor::VersionMismatchCounter,
+            header::Header,
+            log_buffer_descriptor::{LOG_META_DATA_LENGTH, TERM_MIN_LENGTH},
+            term_reader,
+        },
+    };
+
+    const TERM_ID: i32 = 7;
+
+    #[test]
+    fn should_append_padding_skipped_by_reader() {
+        let padding_length: Index = 100;
+        let aligned_padding_length =
+            bit_utils::align(padding_length + data_frame_header::LENGTH, frame_descriptor::FRAME_ALIGNMENT);
+        let msg_length: Index = 20;
+        let aligned_frame_length = bit_utils::align(msg_length + data_frame_header::LENGTH, frame_descriptor::FRAME_ALIGNMENT);
+
+        let t_buff = AlignedBuffer::with_capacity(TERM_MIN_LENGTH);
+        let term_buffer = AtomicBuffer::from_aligned(&t_buff);
+        let m_buff = AlignedBuffer::with_capacity(LOG_META_DATA_LENGTH);
+        let meta_data_buffer = AtomicBuffer::from_aligned(&m_buff);
+        let h_buff = AlignedBuffer::with_capacity(data_frame_header::LENGTH);
+        let header_writer = HeaderWriter::new(AtomicBuffer::from_aligned(&h_buff));
+        let s_buff = AlignedBuffer::with_capacity(msg_length);
+        let src_buffer = AtomicBuffer::from_aligned(&s_buff);
+
+        let mut term_appender = ExclusiveTermAppender::new(term_buffer, meta_data_buffer, 0);
+
+        let resulting_offset = term_appender.append_padding(TERM_ID, 0, &header_writer, padding_length);
+        assert_eq!(resulting_offset, aligned_padding_length);
+        assert_eq!(
+            term_buffer.get::<u16>(frame_descriptor::type_offset(0)),
+            data_frame_header::HDR_TYPE_PAD
+        );
+        assert_eq!(
+            term_appender.raw_tail(),
+            ((TERM_ID as i64) << 32) | aligned_padding_length as i64
+        );
+
+        let resulting_offset = term_appender
+            .append_unfragmented_message(
+                TERM_ID,
+                resulting_offset,
+                &header_writer,
+                src_buffer,
+                0,
+                msg_length,
+                |_term_buffer, _term_offset, _length| 0,
+                0,
+            )
+            .unwrap();
+        assert_eq!(resulting_offset, aligned_padding_length + aligned_frame_length);
+
+        let mut offsets = vec![];
+        let mut header = Header::new(TERM_ID, TERM_MIN_LENGTH);
+        let outcome = term_reader::read(
+            term_buffer,
+            0,
+            &mut |_buffer: &AtomicBuffer, offset: Index, length: Index, _header: &Header| offsets.push((offset, length)),
+            10,
+            &mut header,
+            &VersionMismatchCounter::default(),
+            |_err| {},
+        );
+
+        // The reader steps over exactly the padded length to the message that follows.
+        assert_eq!(outcome.fragments_read, 1);
+        assert_eq!(outcome.offset, aligned_padding_length + aligned_frame_length);
+        assert_eq!(
+            offsets,
+            vec![(aligned_padding_length + data_frame_header::LENGTH, msg_length)]
+        );
+    }
+
+    #[test]
+    fn should_reject_source_out_of_range_without_moving_tail() {
+        let msg_length: Index = 20;
+
+        let t_buff = AlignedBuffer::with_capacity(TERM_MIN_LENGTH);
+        let term_buffer = AtomicBuffer::from_aligned(&t_buff);
+        let m_buff = AlignedBuffer::with_capacity(LOG_META_DATA_LENGTH);
+        let meta_data_buffer = AtomicBuffer::from_aligned(&m_buff);
+        let h_buff = AlignedBuffer::with_capacity(data_frame_header::LENGTH);
+        let header_writer = HeaderWriter::new(AtomicBuffer::from_aligned(&h_buff));
+        let s_buff = AlignedBuffer::with_capacity(msg_length);
+        let src_buffer = AtomicBuffer::from_aligned(&s_buff);
+
+        let mut term_appender = ExclusiveTermAppender::new(term_buffer, meta_data_buffer, 0);
+
+        let result = term_appender.append_unfragmented_message(
+            TERM_ID,
+            0,
+            &header_writer,
+            src_buffer,
+            8,
+            msg_length,
+            |_term_buffer, _term_offset, _length| 0,
+            0,
+        );
+        assert!(result.is_err());
+
+        let result = term_appender.append_unfragmented_message_bulk(
+            TERM_ID,
+            0,
+            &header_writer,
+            vec![src_buffer],
+            msg_length + 1,
+            |_term_buffer, _term_offset, _length| 0,
+        );
+        assert!(result.is_err());
+
+        assert_eq!(term_appender.raw_tail(), 0);
+    }
+
+    #[test]
+    fn should_copy_each_buffer_of_bulk_append_in_turn() {
+        let t_buff = AlignedBuffer::with_capacity(TERM_MIN_LENGTH);
+        let term_buffer = AtomicBuffer::from_aligned(&t_buff);
+        let m_buff = AlignedBuffer::with_capacity(LOG_META_DATA_LENGTH);
+        let meta_data_buffer = AtomicBuffer::from_aligned(&m_buff);
+        let h_buff = AlignedBuffer::with_capacity(data_frame_header::LENGTH);
+        let header_writer = HeaderWriter::new(AtomicBuffer::from_aligned(&h_buff));
+        let first_buff = AlignedBuffer::with_capacity(8);
+        let first = AtomicBuffer::from_aligned(&first_buff);
+        let second_buff = AlignedBuffer::with_capacity(16);
+        let second = AtomicBuffer::from_aligned(&second_buff);
+        first.put::<i64>(0, 1);
+        second.put::<i64>(0, 2);
+        second.put::<i64>(8, 3);
+
+        let mut term_appender = ExclusiveTermAppender::new(term_buffer, meta_data_buffer, 0);
+
+        let resulting_offset = term_appender
+            .append_unfragmented_message_bulk(
+                TERM_ID,
+                0,
+                &header_writer,
+                vec![first, second],
+                24,
+                |_term_buffer, _term_offset, _length| 0,
+            )
+            .unwrap();
+
+        assert_eq!(
+            resulting_offset,
+            bit_utils::align(24 + data_frame_header::LENGTH, frame_descriptor::FRAME_ALIGNMENT)
+        );
+        assert_eq!(term_buffer.get::<i64>(data_frame_header::LENGTH), 1);
+        assert_eq!(term_buffer.get::<i64>(data_frame_header::LENGTH + 8), 2);
+        assert_eq!(term_buffer.get::<i64>(data_frame_header::LENGTH + 16), 3);
+        assert_eq!(term_buffer.get::<i32>(0), 24 + data_frame_header::LENGTH);
+    }
+}
@@ -14,8 +14,13 @@
  * limitations under the License.
  */
 
+use std::sync::{
+    atomic::{AtomicBool, AtomicU64, Ordering},
+    Arc,
+};
+
 use crate::concurrent::{atomic_buffer::AtomicBuffer, logbuffer::data_frame_header};
-use crate::utils::{errors::AeronError, types::Index};
+use crate::utils::{bit_utils, errors::AeronError, types::Index};
 
 /**
 * Description of the structure for message framing in a log buffer.
@@ -86,6 +91,10 @@ pub fn compute_max_message_length(capacity: Index) -> Index {
     std::cmp::min(capacity / 8, MAX_MESSAGE_LENGTH)
 }
 
+pub fn aligned_frame_length(frame_length: Index) -> Index {
+    bit_utils::align(frame_length, FRAME_ALIGNMENT)
+}
+
 pub fn type_offset(frame_offset: Index) -> Index {
     frame_offset + *data_frame_header::TYPE_FIELD_OFFSET
 }
@@ -114,10 +123,105 @@ pub fn set_frame_flags(log_buffer: &AtomicBuffer, frame_offset: Index, flags: u8
     log_buffer.put::<u8>(flags_offset(frame_offset), flags);
 }
 
+/**
+ * Check that extra flags to be OR-ed into the computed fragmentation flags of a frame leave the BEGIN and END
+ * bits to the appender.
+ *
+ * @param extra_flags to be set on each frame of a message.
+ * @return IllegalArgumentException if the extra flags contain BEGIN_FRAG or END_FRAG.
+ */
+pub fn check_extra_flags(extra_flags: u8) -> Result<(), AeronError> {
+    if extra_flags & UNFRAGMENTED != 0 {
+        return Err(AeronError::IllegalArgumentException(format!(
+            "extra flags 0x{:02x} must not contain the BEGIN_FRAG or END_FRAG bits 0x{:02x}",
+            extra_flags, UNFRAGMENTED
+        )));
+    }
+    Ok(())
+}
+
 pub fn set_frame_term_offset(log_buffer: &AtomicBuffer, frame_offset: Index, term_offset: i32) {
     log_buffer.put::<i32>(term_offset_offset(frame_offset), term_offset);
 }
 
+/**
+ * Check the version of the frame is the one supported by this client. A mismatch indicates version skew between
+ * the media driver and the client.
+ *
+ * @param log_buffer   containing the frame.
+ * @param frame_offset at which the frame begins.
+ * @return error describing the unsupported version if it does not match data_frame_header::CURRENT_VERSION.
+ */
+pub fn check_version(log_buffer: &AtomicBuffer, frame_offset: Index) -> Result<(), AeronError> {
+    let version = log_buffer.get::<u8>(frame_offset + VERSION_OFFSET);
+
+    if version != data_frame_header::CURRENT_VERSION {
+        return Err(AeronError::IllegalStateException(format!(
+            "unsupported frame version {} at offset {}, expected {}",
+            version,
+            frame_offset,
+            data_frame_header::CURRENT_VERSION
+        )));
+    }
+
+    Ok(())
+}
+
+/**
+ * Counts frames read with a version other than data_frame_header::CURRENT_VERSION. The count may be shared, e.g. by
+ * all the images of a client, while the error is reported only for the first mismatch checked through each
+ * VersionMismatchCounter so that reading a skewed stream does not build an error for every frame.
+ */
+#[derive(Clone, Default)]
+pub struct VersionMismatchCounter {
+    count: Arc<AtomicU64>,
+    reported: Arc<AtomicBool>,
+}
+
+impl VersionMismatchCounter {
+    /**
+     * Create a counter adding to the given count.
+     *
+     * @param count to add frame version mismatches to.
+     */
+    pub fn new(count: Arc<AtomicU64>) -> Self {
+        Self {
+            count,
+            reported: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /**
+     * Check the version of the frame, counting a mismatch and passing the first one to the exception_handler.
+     * The frame is left to be delivered either way.
+     *
+     * @param log_buffer        containing the frame.
+     * @param frame_offset      at which the frame begins.
+     * @param exception_handler to which the first mismatch is reported.
+     */
+    #[inline]
+    pub fn check(&self, log_buffer: &AtomicBuffer, frame_offset: Index, exception_handler: impl Fn(AeronError)) {
+        if log_buffer.get::<u8>(frame_offset + VERSION_OFFSET) != data_frame_header::CURRENT_VERSION {
+            self.count.fetch_add(1, Ordering::Relaxed);
+
+            if !self.reported.swap(true, Ordering::Relaxed) {
+                if let Err(err) = check_version(log_buffer, frame_offset) {
+                    exception_handler(err);
+                }
+            }
+        }
+    }
+
+    /**
+     * Number of frame version mismatches added to the count.
+     *
+     * @return the number of frame version mismatches.
+     */
+    pub fn count(&self) -> u64 {
+        self.count.load(Ordering::Relaxed)
+    }
+}
+
 pub fn is_padding_frame(log_buffer: &AtomicBuffer, frame_offset: Index) -> bool {
     log_buffer.get::<u16>(type_offset(frame_offset)) == data_frame_header::HDR_TYPE_PAD
 }
@@ -133,3 +237,21 @@ pub fn set_frame_length_ordered(log_buffer: &AtomicBuffer, frame_offset: Index,
 pub fn frame_version(log_buffer: &AtomicBuffer, frame_offset: Index) -> u8 {
     log_buffer.get::<u8>(frame_offset)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_align_frame_length_to_frame_alignment() {
+        assert_eq!(aligned_frame_length(0), 0);
+        assert_eq!(aligned_frame_length(1), FRAME_ALIGNMENT);
+        assert_eq!(aligned_frame_length(data_frame_header::LENGTH), FRAME_ALIGNMENT);
+        assert_eq!(aligned_frame_length(FRAME_ALIGNMENT - 1), FRAME_ALIGNMENT);
+        assert_eq!(aligned_frame_length(FRAME_ALIGNMENT), FRAME_ALIGNMENT);
+        assert_eq!(aligned_frame_length(FRAME_ALIGNMENT + 1), FRAME_ALIGNMENT * 2);
+        assert_eq!(aligned_frame_length(FRAME_ALIGNMENT * 4), FRAME_ALIGNMENT * 4);
+        assert_eq!(aligned_frame_length(FRAME_ALIGNMENT * 4 + 1), FRAME_ALIGNMENT * 5);
+        assert_eq!(aligned_frame_length(1408), 1408);
+    }
+}
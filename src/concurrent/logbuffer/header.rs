@@ -22,10 +22,7 @@ use crate::{
             frame_descriptor, log_buffer_descriptor,
         },
     },
-    utils::{
-        bit_utils::{align, number_of_trailing_zeroes},
-        types::Index,
-    },
+    utils::{bit_utils::number_of_trailing_zeroes, types::Index},
 };
 
 /**
@@ -40,6 +37,7 @@ pub struct Header {
     offset: Index,
     initial_term_id: i32,
     position_bits_to_shift: i32,
+    image_correlation_id: i64,
 }
 
 impl Header {
@@ -50,9 +48,24 @@ impl Header {
             offset: 0,
             position_bits_to_shift: number_of_trailing_zeroes(capacity),
             buffer: None,
+            image_correlation_id: -1,
         }
     }
 
+    /**
+     * Get the correlation id of the Image the frame was read from. A different value for the same session id
+     * means the session has rejoined as a new Image, e.g. after a reconnection.
+     *
+     * @return the correlation id of the Image or -1 if the header is not associated with an Image.
+     */
+    pub fn image_correlation_id(&self) -> i64 {
+        self.image_correlation_id
+    }
+
+    pub fn set_image_correlation_id(&mut self, image_correlation_id: i64) {
+        self.image_correlation_id = image_correlation_id;
+    }
+
     /**
      * Get the initial term id this stream started at.
      *
@@ -155,6 +168,38 @@ impl Header {
             .get::<u16>(self.offset + *data_frame_header::TYPE_FIELD_OFFSET)
     }
 
+    /**
+     * The version of the protocol the frame was written with.
+     *
+     * @return the version field of the frame.
+     */
+    pub fn version(&self) -> u8 {
+        self.buffer
+            .expect("Buffer not set")
+            .get::<u8>(self.offset + *data_frame_header::VERSION_FIELD_OFFSET)
+    }
+
+    /**
+     * The raw frame length field as written in the header. Unlike {@link #frame_length()} this is read
+     * with volatile semantics and may be zero or negative while the frame is still being written.
+     *
+     * @return the raw frame length field of the frame.
+     */
+    pub fn frame_length_field(&self) -> i32 {
+        self.buffer
+            .expect("Buffer not set")
+            .get_volatile::<i32>(self.offset + *data_frame_header::FRAME_LENGTH_FIELD_OFFSET)
+    }
+
+    /**
+     * The offset in the buffer at which the payload of the frame begins.
+     *
+     * @return offset at which the payload of the frame begins.
+     */
+    pub fn data_offset(&self) -> Index {
+        self.offset + data_frame_header::DATA_OFFSET
+    }
+
     /**
      * The flags for this frame. Valid flags are {@link DataFrameHeader::BEGIN_FLAG}
      * and {@link DataFrameHeader::END_FLAG}. A convenience flag {@link DataFrameHeader::BEGIN_AND_END_FLAGS}
@@ -174,7 +219,7 @@ impl Header {
      * @return the current position to which the Image has advanced on reading this message.
      */
     pub fn position(&self) -> i64 {
-        let resulting_offset = align(self.term_offset() + self.frame_length(), frame_descriptor::FRAME_ALIGNMENT);
+        let resulting_offset = self.term_offset() + frame_descriptor::aligned_frame_length(self.frame_length());
         log_buffer_descriptor::compute_position(
             self.term_id(),
             resulting_offset,
@@ -193,6 +238,41 @@ impl Header {
             .expect("Buffer not set")
             .get::<i64>(self.offset + *data_frame_header::RESERVED_VALUE_FIELD_OFFSET)
     }
+
+    /**
+     * Copy the fields of the frame so they can be kept after the buffer the header points into moves on.
+     *
+     * @return snapshot of the header fields.
+     */
+    pub fn snapshot(&self) -> HeaderSnapshot {
+        HeaderSnapshot {
+            session_id: self.session_id(),
+            stream_id: self.stream_id(),
+            term_id: self.term_id(),
+            term_offset: self.term_offset(),
+            frame_length: self.frame_length(),
+            frame_type: self.frame_type(),
+            flags: self.flags(),
+            reserved_value: self.reserved_value(),
+            position: self.position(),
+        }
+    }
+}
+
+/**
+ * Owned copy of the header fields of a data frame, e.g. for messages copied out of a poll.
+ */
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct HeaderSnapshot {
+    pub session_id: i32,
+    pub stream_id: i32,
+    pub term_id: i32,
+    pub term_offset: Index,
+    pub frame_length: Index,
+    pub frame_type: u16,
+    pub flags: u8,
+    pub reserved_value: i64,
+    pub position: i64,
 }
 
 pub struct HeaderWriter {
@@ -227,3 +307,62 @@ impl HeaderWriter {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::concurrent::atomic_buffer::AlignedBuffer;
+
+    use super::*;
+
+    const TERM_LENGTH: Index = log_buffer_descriptor::TERM_MIN_LENGTH;
+    const INITIAL_TERM_ID: i32 = 3;
+    const FRAME_OFFSET: Index = 256;
+    const PAYLOAD_LENGTH: Index = 44;
+
+    #[test]
+    fn should_read_every_frame_field() {
+        let aligned = AlignedBuffer::with_capacity(TERM_LENGTH);
+        let buffer = AtomicBuffer::from_aligned(&aligned);
+
+        let frame = buffer.overlay_struct::<DataFrameHeaderDefn>(FRAME_OFFSET);
+        unsafe {
+            (*frame).frame_length = data_frame_header::LENGTH + PAYLOAD_LENGTH;
+            (*frame).version = 7;
+            (*frame).flags = frame_descriptor::BEGIN_FRAG;
+            (*frame).frame_type = data_frame_header::HDR_TYPE_DATA;
+            (*frame).term_offset = FRAME_OFFSET;
+            (*frame).session_id = 11;
+            (*frame).stream_id = 22;
+            (*frame).term_id = INITIAL_TERM_ID + 1;
+            (*frame).reserved_value = 0x0102_0304_0506_0708;
+        }
+
+        let mut header = Header::new(INITIAL_TERM_ID, TERM_LENGTH);
+        header.set_buffer(buffer);
+        header.set_offset(FRAME_OFFSET);
+
+        assert_eq!(header.frame_length_field(), data_frame_header::LENGTH + PAYLOAD_LENGTH);
+        assert_eq!(header.frame_length(), data_frame_header::LENGTH + PAYLOAD_LENGTH);
+        assert_eq!(header.version(), 7);
+        assert_eq!(header.flags(), frame_descriptor::BEGIN_FRAG);
+        assert_eq!(header.frame_type(), data_frame_header::HDR_TYPE_DATA);
+        assert_eq!(header.term_offset(), FRAME_OFFSET);
+        assert_eq!(header.session_id(), 11);
+        assert_eq!(header.stream_id(), 22);
+        assert_eq!(header.term_id(), INITIAL_TERM_ID + 1);
+        assert_eq!(header.reserved_value(), 0x0102_0304_0506_0708);
+        assert_eq!(header.data_offset(), FRAME_OFFSET + data_frame_header::DATA_OFFSET);
+    }
+
+    #[test]
+    fn should_read_raw_frame_length_of_frame_in_progress() {
+        let aligned = AlignedBuffer::with_capacity(TERM_LENGTH);
+        let buffer = AtomicBuffer::from_aligned(&aligned);
+        buffer.put::<i32>(0, -(data_frame_header::LENGTH + PAYLOAD_LENGTH));
+
+        let mut header = Header::new(INITIAL_TERM_ID, TERM_LENGTH);
+        header.set_buffer(buffer);
+
+        assert_eq!(header.frame_length_field(), -(data_frame_header::LENGTH + PAYLOAD_LENGTH));
+    }
+}
@@ -17,7 +17,7 @@
 use lazy_static::lazy_static;
 
 use crate::{
-    concurrent::atomic_buffer::AtomicBuffer,
+    concurrent::{atomic_buffer::AtomicBuffer, logbuffer::data_frame_header},
     offset_of,
     utils::{
         bit_utils::is_power_of_two,
@@ -29,6 +29,10 @@ use crate::{
 
 pub const TERM_MIN_LENGTH: Index = 64 * 1024;
 pub const TERM_MAX_LENGTH: Index = 1024 * 1024 * 1024;
+/// Term length the media driver uses for publications on network channels which do not set term-length.
+pub const TERM_DEFAULT_LENGTH: Index = 16 * 1024 * 1024;
+/// Term length the media driver uses for publications on IPC channels which do not set term-length.
+pub const IPC_TERM_DEFAULT_LENGTH: Index = 64 * 1024 * 1024;
 pub const AERON_PAGE_MIN_SIZE: Index = 4 * 1024;
 pub const AERON_PAGE_MAX_SIZE: Index = 1024 * 1024 * 1024;
 
@@ -165,6 +169,24 @@ pub fn check_term_length(term_length: Index) -> Result<(), AeronError> {
     Ok(())
 }
 
+/**
+ * Check the MTU is at most 1/8 of the term length as required by the media driver.
+ *
+ * @param mtu_length  to be checked.
+ * @param term_length of the log the MTU is used with.
+ * @return error if the MTU is too large for the term length.
+ */
+pub fn check_mtu_length(mtu_length: Index, term_length: Index) -> Result<(), AeronError> {
+    if mtu_length > term_length / 8 {
+        return Err(AeronError::IllegalArgumentException(format!(
+            "MTU greater than term length / 8, mtu= {}, term length= {}",
+            mtu_length, term_length
+        )));
+    }
+
+    Ok(())
+}
+
 pub fn check_page_size(page_size: Index) -> Result<(), AeronError> {
     if page_size < AERON_PAGE_MIN_SIZE {
         return Err(AeronError::IllegalStateException(format!(
@@ -190,6 +212,27 @@ pub fn check_page_size(page_size: Index) -> Result<(), AeronError> {
     Ok(())
 }
 
+/**
+ * Compute the total length of a log file given the term length.
+ *
+ * Logs with term lengths below 1 GB are page aligned as a whole, larger logs only have the meta data
+ * section aligned so that the total length does not overflow.
+ *
+ * @param term_length   on which to base the calculation.
+ * @param file_page_size to use for log.
+ * @return the total length of the log file.
+ */
+pub fn compute_log_length(term_length: Index, file_page_size: Index) -> i64 {
+    let page_size = file_page_size as i64;
+    let align_to_page = |value: i64| (value + page_size - 1) & !(page_size - 1);
+
+    if term_length < 1024 * 1024 * 1024 {
+        align_to_page(PARTITION_COUNT as i64 * term_length as i64 + LOG_META_DATA_LENGTH as i64)
+    } else {
+        PARTITION_COUNT as i64 * term_length as i64 + align_to_page(LOG_META_DATA_LENGTH as i64)
+    }
+}
+
 pub fn initial_term_id(log_meta_data_buffer: &AtomicBuffer) -> i32 {
     log_meta_data_buffer.get::<i32>(*LOG_INITIAL_TERM_ID_OFFSET)
 }
@@ -311,12 +354,48 @@ pub fn cas_raw_tail(
     )
 }
 
+/**
+ * Get a view over the default frame header template stored in the log meta data.
+ *
+ * @param log_meta_data_buffer containing the default frame header.
+ * @return buffer covering the default frame header template.
+ */
 pub fn default_frame_header(log_meta_data_buffer: &AtomicBuffer) -> AtomicBuffer {
-    let ptr: *mut u8 = unsafe { log_meta_data_buffer.buffer().offset(LOG_DEFAULT_FRAME_HEADER_OFFSET as isize) };
-    AtomicBuffer::new(ptr, crate::concurrent::logbuffer::data_frame_header::LENGTH)
+    log_meta_data_buffer.view(LOG_DEFAULT_FRAME_HEADER_OFFSET, data_frame_header::LENGTH)
 }
 
-pub fn rotate_log(log_meta_data_buffer: &AtomicBuffer, current_term_count: i32, current_term_id: i32) {
+/**
+ * Store the default frame header template in the log meta data.
+ *
+ * @param log_meta_data_buffer into which the default header is written.
+ * @param default_header       template to be copied.
+ */
+pub fn store_default_frame_header(log_meta_data_buffer: &AtomicBuffer, default_header: &AtomicBuffer) -> Result<(), AeronError> {
+    let length = default_header.capacity();
+
+    if length != data_frame_header::LENGTH {
+        return Err(AeronError::IllegalArgumentException(format!(
+            "default header length not equal to DATA_FRAME_HEADER_LENGTH: length={}",
+            length
+        )));
+    }
+
+    log_meta_data_buffer.put::<i32>(*LOG_DEFAULT_FRAME_HEADER_LENGTH_OFFSET, length);
+    log_meta_data_buffer.copy_from(LOG_DEFAULT_FRAME_HEADER_OFFSET, default_header, 0, length)?;
+
+    Ok(())
+}
+
+/**
+ * Rotate the log and update the tail counter for the new term. Several publishers may race to rotate the same
+ * term, only one of them succeeds in moving the active term count on.
+ *
+ * @param log_meta_data_buffer for the meta data.
+ * @param current_term_count   from which to rotate.
+ * @param current_term_id      to be used in the default headers.
+ * @return true if this caller rotated the log or false if another caller already had.
+ */
+pub fn rotate_log(log_meta_data_buffer: &AtomicBuffer, current_term_count: i32, current_term_id: i32) -> bool {
     let next_term_id = current_term_id + 1;
     let next_term_count = current_term_count + 1;
     let next_index = index_by_term_count(next_term_count as i64);
@@ -335,10 +414,125 @@ pub fn rotate_log(log_meta_data_buffer: &AtomicBuffer, current_term_count: i32,
         }
     }
 
-    cas_active_term_count(log_meta_data_buffer, current_term_count, next_term_count);
+    cas_active_term_count(log_meta_data_buffer, current_term_count, next_term_count)
 }
 
 pub fn initialize_tail_with_term_id(log_meta_data_buffer: &AtomicBuffer, partition_index: Index, term_id: i32) {
     let raw_tail: i64 = term_id as i64 * (1_i64 << 32);
     log_meta_data_buffer.put::<i64>(*TERM_TAIL_COUNTER_OFFSET + (partition_index * I64_SIZE), raw_tail);
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::concurrent::{
+        atomic_buffer::AlignedBuffer,
+        logbuffer::{data_frame_header, frame_descriptor, log_buffer_descriptor::*},
+    };
+
+    #[test]
+    fn should_accept_mtu_at_one_eighth_of_term_length() {
+        assert!(check_mtu_length(TERM_MIN_LENGTH / 8, TERM_MIN_LENGTH).is_ok());
+        assert!(check_mtu_length(1408, TERM_MIN_LENGTH).is_ok());
+    }
+
+    #[test]
+    fn should_reject_mtu_above_one_eighth_of_term_length() {
+        assert!(check_mtu_length(TERM_MIN_LENGTH / 8 + frame_descriptor::FRAME_ALIGNMENT, TERM_MIN_LENGTH).is_err());
+        assert!(check_mtu_length(TERM_MIN_LENGTH, TERM_MIN_LENGTH).is_err());
+    }
+
+    #[test]
+    fn should_compute_log_length_for_min_term_and_page() {
+        assert_eq!(
+            compute_log_length(TERM_MIN_LENGTH, AERON_PAGE_MIN_SIZE),
+            3 * TERM_MIN_LENGTH as i64 + LOG_META_DATA_LENGTH as i64
+        );
+    }
+
+    #[test]
+    fn should_align_log_length_to_page_size() {
+        let page_size = 64 * 1024;
+
+        assert_eq!(compute_log_length(TERM_MIN_LENGTH, page_size), 4 * 64 * 1024);
+        assert_eq!(compute_log_length(16 * 1024 * 1024, 2 * 1024 * 1024), 50 * 1024 * 1024);
+        assert_eq!(
+            compute_log_length(16 * 1024 * 1024, AERON_PAGE_MIN_SIZE),
+            48 * 1024 * 1024 + 4096
+        );
+    }
+
+    #[test]
+    fn should_compute_log_length_for_max_term_length() {
+        let page_size = 2 * 1024 * 1024;
+
+        assert_eq!(
+            compute_log_length(TERM_MAX_LENGTH, page_size),
+            3 * TERM_MAX_LENGTH as i64 + page_size as i64
+        );
+        assert_eq!(
+            compute_log_length(TERM_MAX_LENGTH, AERON_PAGE_MIN_SIZE),
+            3 * TERM_MAX_LENGTH as i64 + LOG_META_DATA_LENGTH as i64
+        );
+    }
+
+    #[test]
+    fn should_read_back_stored_default_frame_header() {
+        let md = AlignedBuffer::with_capacity(LOG_META_DATA_LENGTH);
+        let meta_data_buffer = AtomicBuffer::from_aligned(&md);
+        let hdr = AlignedBuffer::with_capacity(data_frame_header::LENGTH);
+        let template = AtomicBuffer::from_aligned(&hdr);
+
+        template.put::<u8>(*data_frame_header::VERSION_FIELD_OFFSET, data_frame_header::CURRENT_VERSION);
+        template.put::<u8>(*data_frame_header::FLAGS_FIELD_OFFSET, frame_descriptor::UNFRAGMENTED);
+        template.put::<u16>(*data_frame_header::TYPE_FIELD_OFFSET, data_frame_header::HDR_TYPE_DATA);
+        template.put::<i32>(*data_frame_header::SESSION_ID_FIELD_OFFSET, 200);
+        template.put::<i32>(*data_frame_header::STREAM_ID_FIELD_OFFSET, 10);
+
+        store_default_frame_header(&meta_data_buffer, &template).unwrap();
+
+        let default_header = default_frame_header(&meta_data_buffer);
+        assert_eq!(default_header.capacity(), data_frame_header::LENGTH);
+        assert_eq!(
+            meta_data_buffer.get::<i32>(*LOG_DEFAULT_FRAME_HEADER_LENGTH_OFFSET),
+            data_frame_header::LENGTH
+        );
+        assert_eq!(
+            default_header.get::<u8>(*data_frame_header::FLAGS_FIELD_OFFSET),
+            frame_descriptor::UNFRAGMENTED
+        );
+        assert_eq!(
+            default_header.get::<u16>(*data_frame_header::TYPE_FIELD_OFFSET),
+            data_frame_header::HDR_TYPE_DATA
+        );
+        assert_eq!(default_header.get::<i32>(*data_frame_header::SESSION_ID_FIELD_OFFSET), 200);
+        assert_eq!(default_header.get::<i32>(*data_frame_header::STREAM_ID_FIELD_OFFSET), 10);
+    }
+
+    #[test]
+    fn should_reject_default_frame_header_of_wrong_length() {
+        let md = AlignedBuffer::with_capacity(LOG_META_DATA_LENGTH);
+        let meta_data_buffer = AtomicBuffer::from_aligned(&md);
+        let hdr = AlignedBuffer::with_capacity(data_frame_header::LENGTH * 2);
+        let template = AtomicBuffer::from_aligned(&hdr);
+
+        assert!(store_default_frame_header(&meta_data_buffer, &template).is_err());
+    }
+
+    #[test]
+    fn should_report_rotation_only_to_the_caller_that_rotated() {
+        let md = AlignedBuffer::with_capacity(LOG_META_DATA_LENGTH);
+        let meta_data_buffer = AtomicBuffer::from_aligned(&md);
+        let current_term_id = 7;
+
+        initialize_tail_with_term_id(&meta_data_buffer, 0, current_term_id);
+        initialize_tail_with_term_id(&meta_data_buffer, 1, current_term_id + 1 - PARTITION_COUNT);
+
+        assert!(rotate_log(&meta_data_buffer, 0, current_term_id));
+        assert!(!rotate_log(&meta_data_buffer, 0, current_term_id));
+        assert_eq!(active_term_count(&meta_data_buffer), 1);
+        assert_eq!(
+            term_id(raw_tail_by_partition_index(&meta_data_buffer, 1)),
+            current_term_id + 1
+        );
+    }
+}
@@ -24,6 +24,7 @@ use crate::{
     utils::{
         bit_utils,
         errors::AeronError,
+        misc::unix_time_ns,
         types::{Index, I64_SIZE},
     },
 };
@@ -47,6 +48,11 @@ pub fn default_reserved_value_supplier(_term_buffer: AtomicBuffer, _term_offset:
     0
 }
 
+/// Stamps each frame with the current time in nanoseconds so that subscribers can measure latency.
+pub fn nano_time_reserved_value_supplier(_term_buffer: AtomicBuffer, _term_offset: Index, _length: Index) -> i64 {
+    unix_time_ns() as i64
+}
+
 pub struct TermAppender {
     term_buffer: AtomicBuffer,
     tail_buffer: AtomicBuffer,
@@ -90,7 +96,7 @@ impl TermAppender {
         active_term_id: i32,
     ) -> Result<Index, AeronError> {
         let frame_length: Index = length + data_frame_header::LENGTH;
-        let aligned_length: Index = bit_utils::align(frame_length, frame_descriptor::FRAME_ALIGNMENT);
+        let aligned_length: Index = frame_descriptor::aligned_frame_length(frame_length);
         let raw_tail: i64 = self.get_and_add_raw_tail(aligned_length);
         let term_offset: i64 = raw_tail & 0xFFFF_FFFF;
         let term_id: i32 = log_buffer_descriptor::term_id(raw_tail);
@@ -112,7 +118,9 @@ impl TermAppender {
         Ok(resulting_offset as Index)
     }
 
-    /// This fn copy supplied (in msg_body_buffer) message in to internal term_buffer
+    /// This fn copy supplied (in msg_body_buffer) message in to internal term_buffer. The extra_flags are OR-ed
+    /// into the BEGIN/END flags of the frame and must not contain those bits.
+    #[allow(clippy::too_many_arguments)]
     pub fn append_unfragmented_message(
         &self,
         header: &HeaderWriter,
@@ -121,9 +129,13 @@ impl TermAppender {
         length: Index,
         reserved_value_supplier: OnReservedValueSupplier,
         active_term_id: i32,
+        extra_flags: u8,
     ) -> Result<Index, AeronError> {
+        frame_descriptor::check_extra_flags(extra_flags)?;
+        msg_body_buffer.check_range(msg_body_offset, length)?;
+
         let frame_length: Index = length + data_frame_header::LENGTH;
-        let aligned_length: Index = bit_utils::align(frame_length, frame_descriptor::FRAME_ALIGNMENT);
+        let aligned_length: Index = frame_descriptor::aligned_frame_length(frame_length);
         let raw_tail: i64 = self.get_and_add_raw_tail(aligned_length);
         let term_offset: i64 = raw_tail & 0xFFFF_FFFF;
         let term_id: i32 = log_buffer_descriptor::term_id(raw_tail);
@@ -140,12 +152,15 @@ impl TermAppender {
         } else {
             let frame_offset = term_offset as Index;
             header.write(&self.term_buffer, frame_offset, frame_length, term_id);
+            if extra_flags != 0 {
+                frame_descriptor::set_frame_flags(&self.term_buffer, frame_offset, frame_descriptor::UNFRAGMENTED | extra_flags);
+            }
             self.term_buffer.copy_from(
                 frame_offset + data_frame_header::LENGTH,
                 msg_body_buffer,
                 msg_body_offset,
                 length,
-            );
+            )?;
 
             let reserved_value: i64 = reserved_value_supplier(self.term_buffer, frame_offset, frame_length);
             self.term_buffer
@@ -166,8 +181,10 @@ impl TermAppender {
         reserved_value_supplier: OnReservedValueSupplier,
         active_term_id: i32,
     ) -> Result<Index, AeronError> {
+        TermAppender::check_buffers_length(&buffers, length)?;
+
         let frame_length: Index = length + data_frame_header::LENGTH;
-        let aligned_length: Index = bit_utils::align(frame_length, frame_descriptor::FRAME_ALIGNMENT);
+        let aligned_length: Index = frame_descriptor::aligned_frame_length(frame_length);
         let raw_tail: i64 = self.get_and_add_raw_tail(aligned_length);
         let term_offset: i64 = raw_tail & 0xFFFF_FFFF;
         let term_id: i32 = log_buffer_descriptor::term_id(raw_tail);
@@ -186,15 +203,18 @@ impl TermAppender {
             header.write(&self.term_buffer, frame_offset, frame_length, term_id);
 
             let mut offset = frame_offset + data_frame_header::LENGTH;
+            let mut remaining = length;
 
             for buf in buffers.iter() {
-                let ending_offset = offset + length;
-                if offset >= ending_offset {
+                if remaining <= 0 {
                     break;
                 }
-                offset += buf.capacity();
+                let num_bytes = std::cmp::min(remaining, buf.capacity());
 
-                self.term_buffer.copy_from(offset, buf, 0, buf.capacity());
+                self.term_buffer.copy_from(offset, buf, 0, num_bytes)?;
+
+                offset += num_bytes;
+                remaining -= num_bytes;
             }
 
             let reserved_value = reserved_value_supplier(self.term_buffer, frame_offset, frame_length);
@@ -207,7 +227,8 @@ impl TermAppender {
         Ok(resulting_offset as Index)
     }
 
-    /// This fn copy supplied (in msg_body_buffer) message in to internal term_buffer
+    /// This fn copy supplied (in msg_body_buffer) message in to internal term_buffer. The extra_flags are OR-ed
+    /// into the BEGIN/END flags of each frame and must not contain those bits.
     #[allow(clippy::too_many_arguments)]
     pub fn append_fragmented_message(
         &self,
@@ -218,7 +239,11 @@ impl TermAppender {
         max_payload_length: Index,
         reserved_value_supplier: OnReservedValueSupplier,
         active_term_id: i32,
+        extra_flags: u8,
     ) -> Result<Index, AeronError> {
+        frame_descriptor::check_extra_flags(extra_flags)?;
+        msg_body_buffer.check_range(msg_body_offset, length)?;
+
         let num_max_payloads = length / max_payload_length;
         let remaining_payload = length % max_payload_length;
         let last_frame_length = if remaining_payload > 0 {
@@ -245,14 +270,14 @@ impl TermAppender {
             resulting_offset =
                 TermAppender::handle_end_of_log_condition(&self.term_buffer, term_offset, header, term_length, term_id) as i64;
         } else {
-            let mut flags: u8 = frame_descriptor::BEGIN_FRAG;
+            let mut flags: u8 = frame_descriptor::BEGIN_FRAG | extra_flags;
             let mut remaining: Index = length;
             let mut frame_offset = term_offset as Index;
 
             loop {
                 let bytes_to_write: Index = std::cmp::min(remaining, max_payload_length);
                 let frame_length: Index = bytes_to_write + data_frame_header::LENGTH;
-                let aligned_length: Index = bit_utils::align(frame_length, frame_descriptor::FRAME_ALIGNMENT);
+                let aligned_length: Index = frame_descriptor::aligned_frame_length(frame_length);
 
                 header.write(&self.term_buffer, frame_offset, frame_length, term_id);
 
@@ -261,7 +286,7 @@ impl TermAppender {
                     msg_body_buffer,
                     msg_body_offset + (length - remaining),
                     bytes_to_write,
-                );
+                )?;
 
                 if remaining <= max_payload_length {
                     flags |= frame_descriptor::END_FRAG;
@@ -275,7 +300,7 @@ impl TermAppender {
 
                 frame_descriptor::set_frame_length_ordered(&self.term_buffer, frame_offset, frame_length);
 
-                flags = 0;
+                flags = extra_flags;
                 frame_offset += aligned_length;
                 remaining -= bytes_to_write;
 
@@ -297,6 +322,8 @@ impl TermAppender {
         reserved_value_supplier: OnReservedValueSupplier,
         active_term_id: i32,
     ) -> Result<Index, AeronError> {
+        TermAppender::check_buffers_length(&buffers, length)?;
+
         let num_max_payloads = length / max_payload_length;
         let remaining_payload = length % max_payload_length;
         let last_frame_length = if remaining_payload > 0 {
@@ -330,7 +357,7 @@ impl TermAppender {
             loop {
                 let bytes_to_write = std::cmp::min(remaining, max_payload_length);
                 let frame_length = bytes_to_write + data_frame_header::LENGTH;
-                let aligned_length: Index = bit_utils::align(frame_length, frame_descriptor::FRAME_ALIGNMENT);
+                let aligned_length: Index = frame_descriptor::aligned_frame_length(frame_length);
 
                 header.write(&self.term_buffer, frame_offset, frame_length, term_id);
 
@@ -345,7 +372,7 @@ impl TermAppender {
                     let num_bytes = std::cmp::min(bytes_to_write - bytes_written, current_buffer_remaining);
 
                     self.term_buffer
-                        .copy_from(payload_offset, curr_buffer, current_buffer_offset, num_bytes);
+                        .copy_from(payload_offset, curr_buffer, current_buffer_offset, num_bytes)?;
 
                     bytes_written += num_bytes;
                     payload_offset += num_bytes;
@@ -390,6 +417,32 @@ impl TermAppender {
         Ok(resulting_offset as i32)
     }
 
+    /// This fn appends a padding frame (HDR_TYPE_PAD) of given length at the current tail so that
+    /// the next message starts at a chosen alignment. Readers skip the padding without delivering it.
+    /// length - length of the padding excluding the frame header
+    /// active_term_id - the term to write padding to
+    pub fn append_padding(&self, header: &HeaderWriter, length: Index, active_term_id: i32) -> Result<Index, AeronError> {
+        let frame_length: Index = length + data_frame_header::LENGTH;
+        let aligned_length: Index = frame_descriptor::aligned_frame_length(frame_length);
+        let raw_tail: i64 = self.get_and_add_raw_tail(aligned_length);
+        let term_offset: i64 = raw_tail & 0xFFFF_FFFF;
+        let term_id: i32 = log_buffer_descriptor::term_id(raw_tail);
+
+        let term_length = self.term_buffer.capacity();
+
+        TermAppender::check_term(active_term_id, term_id)?;
+
+        let mut resulting_offset = term_offset + aligned_length as i64;
+        if resulting_offset > term_length as i64 {
+            resulting_offset =
+                TermAppender::handle_end_of_log_condition(&self.term_buffer, term_offset, header, term_length, term_id) as i64;
+        } else {
+            TermAppender::write_padding(&self.term_buffer, term_offset as Index, header, frame_length, term_id);
+        }
+
+        Ok(resulting_offset as Index)
+    }
+
     fn check_term(expected_term_id: i32, term_id: i32) -> Result<(), AeronError> {
         if term_id != expected_term_id {
             return Err(AeronError::IllegalStateException(format!(
@@ -409,15 +462,32 @@ impl TermAppender {
     ) -> Index {
         if term_offset < term_length as i64 {
             let offset = term_offset as Index;
-            let padding_length = term_length - offset;
-            header.write(term_buffer, offset, padding_length, term_id);
-            frame_descriptor::set_frame_type(term_buffer, offset, data_frame_header::HDR_TYPE_PAD);
-            frame_descriptor::set_frame_length_ordered(term_buffer, offset, padding_length);
+            TermAppender::write_padding(term_buffer, offset, header, term_length - offset, term_id);
         }
 
         TERM_APPENDER_FAILED
     }
 
+    fn write_padding(term_buffer: &AtomicBuffer, offset: Index, header: &HeaderWriter, padding_length: Index, term_id: i32) {
+        header.write(term_buffer, offset, padding_length, term_id);
+        frame_descriptor::set_frame_type(term_buffer, offset, data_frame_header::HDR_TYPE_PAD);
+        frame_descriptor::set_frame_length_ordered(term_buffer, offset, padding_length);
+    }
+
+    // Appends validate their source before the tail is claimed, failing afterwards would leave a hole in the log.
+    // Bulk appends copy length bytes from the buffers in turn, which must hold at least that many bytes.
+    pub(crate) fn check_buffers_length(buffers: &[AtomicBuffer], length: Index) -> Result<(), AeronError> {
+        let buffers_length: i64 = buffers.iter().map(|buffer| buffer.capacity() as i64).sum();
+        if length < 0 || buffers_length < length as i64 {
+            return Err(AeronError::IllegalArgumentException(format!(
+                "buffers too short: length={} buffers_length={}",
+                length, buffers_length
+            )));
+        }
+
+        Ok(())
+    }
+
     fn get_and_add_raw_tail(&self, aligned_length: Index) -> i64 {
         self.tail_buffer.get_and_add_i64(self.tail_offset, aligned_length as i64)
     }
@@ -428,7 +498,10 @@ mod tests {
     use lazy_static::lazy_static;
 
     use super::*;
-    use crate::concurrent::atomic_buffer::AlignedBuffer;
+    use crate::concurrent::{
+        atomic_buffer::AlignedBuffer,
+        logbuffer::{frame_descriptor::VersionMismatchCounter, header::Header, term_reader},
+    };
 
     const TERM_BUFFER_CAPACITY: Index = log_buffer_descriptor::TERM_MIN_LENGTH;
     const META_DATA_BUFFER_CAPACITY: Index = log_buffer_descriptor::LOG_META_DATA_LENGTH;
@@ -527,20 +600,111 @@ mod tests {
         // Mark this message as pending
         term_buffer.put_ordered::<i32>(frame_descriptor::length_offset(tail), -frame_length);
         // Copy the message from msg_body in to term buffer
-        term_buffer.copy_from(data_frame_header::LENGTH, &msg_body, 0, msg_length);
+        term_buffer
+            .copy_from(data_frame_header::LENGTH, &msg_body, 0, msg_length)
+            .unwrap();
         // Write reserved value i.e. reserve needed amount of term buffer
         term_buffer.put::<i64>(tail + *data_frame_header::RESERVED_VALUE_FIELD_OFFSET, RESERVED_VALUE);
         // Mark message as ready for transmission
         term_buffer.put_ordered::<i32>(frame_descriptor::length_offset(tail), frame_length);
 
-        let resulting_offset =
-            term_appender.append_unfragmented_message(&header_writer, &msg_body, 0, msg_length, reserved_value_supplier, TERM_ID);
+        let resulting_offset = term_appender.append_unfragmented_message(
+            &header_writer,
+            &msg_body,
+            0,
+            msg_length,
+            reserved_value_supplier,
+            TERM_ID,
+            0,
+        );
 
         assert!(resulting_offset.is_ok());
 
         assert_eq!(resulting_offset.unwrap(), aligned_frame_length);
     }
 
+    #[test]
+    #[allow(unused_variables)]
+    fn test_term_appender_reject_source_out_of_range_without_claiming_tail() {
+        gen_test_data!(
+            metadata_buffer,
+            term_buffer,
+            hdr,
+            msg_body,
+            term_appender,
+            header_writer,
+            hidden_metadata_buffer
+        );
+
+        let packed_tail = pack_raw_tail(TERM_ID, 0);
+        let _prev_tail = hidden_metadata_buffer.get_and_add_i64(*TERM_TAIL_OFFSET, packed_tail);
+        let src_offset = SRC_BUFFER_CAPACITY - 10;
+
+        assert!(term_appender
+            .append_unfragmented_message(&header_writer, &msg_body, src_offset, 20, reserved_value_supplier, TERM_ID, 0)
+            .is_err());
+        assert!(term_appender
+            .append_fragmented_message(
+                &header_writer,
+                &msg_body,
+                src_offset,
+                20,
+                8,
+                reserved_value_supplier,
+                TERM_ID,
+                0
+            )
+            .is_err());
+        assert!(term_appender
+            .append_unfragmented_message_bulk(
+                &header_writer,
+                vec![msg_body],
+                SRC_BUFFER_CAPACITY + 1,
+                reserved_value_supplier,
+                TERM_ID
+            )
+            .is_err());
+
+        // Nothing was claimed so no hole is left in the log.
+        assert_eq!(term_appender.raw_tail_volatile(), packed_tail);
+    }
+
+    #[test]
+    #[allow(unused_variables)]
+    fn test_term_appender_append_zero_length_frame() {
+        gen_test_data!(
+            metadata_buffer,
+            term_buffer,
+            hdr,
+            msg_body,
+            term_appender,
+            header_writer,
+            hidden_metadata_buffer
+        );
+
+        let packed_tail = pack_raw_tail(TERM_ID, 0);
+        let _prev_tail = hidden_metadata_buffer.get_and_add_i64(*TERM_TAIL_OFFSET, packed_tail);
+
+        let resulting_offset =
+            term_appender.append_unfragmented_message(&header_writer, &msg_body, 0, 0, reserved_value_supplier, TERM_ID, 0);
+
+        assert_eq!(resulting_offset.unwrap(), data_frame_header::LENGTH);
+
+        let appended = term_appender.term_buffer();
+        assert_eq!(
+            frame_descriptor::frame_length_volatile(&appended, 0),
+            data_frame_header::LENGTH
+        );
+        assert_eq!(
+            appended.get::<u8>(frame_descriptor::flags_offset(0)),
+            frame_descriptor::UNFRAGMENTED
+        );
+        assert_eq!(
+            hidden_metadata_buffer.get::<i64>(*TERM_TAIL_OFFSET),
+            pack_raw_tail(TERM_ID, data_frame_header::LENGTH)
+        );
+    }
+
     #[test]
     #[allow(unused_variables)]
     fn test_term_appender_append_frame_twice() {
@@ -564,24 +728,42 @@ mod tests {
         let _prev_tail = hidden_metadata_buffer.get_and_add_i64(*TERM_TAIL_OFFSET, packed_tail);
 
         term_buffer.put_ordered::<i32>(frame_descriptor::length_offset(tail), -frame_length);
-        term_buffer.copy_from(tail + data_frame_header::LENGTH, &msg_body, 0, msg_length);
+        term_buffer
+            .copy_from(tail + data_frame_header::LENGTH, &msg_body, 0, msg_length)
+            .unwrap();
         term_buffer.put::<i64>(tail + *data_frame_header::RESERVED_VALUE_FIELD_OFFSET, RESERVED_VALUE);
         term_buffer.put_ordered::<i32>(frame_descriptor::length_offset(tail), frame_length);
 
-        let resulting_offset0 =
-            term_appender.append_unfragmented_message(&header_writer, &msg_body, 0, msg_length, reserved_value_supplier, TERM_ID);
+        let resulting_offset0 = term_appender.append_unfragmented_message(
+            &header_writer,
+            &msg_body,
+            0,
+            msg_length,
+            reserved_value_supplier,
+            TERM_ID,
+            0,
+        );
         assert!(resulting_offset0.is_ok());
         assert_eq!(resulting_offset0.unwrap(), aligned_frame_length);
 
         tail = aligned_frame_length;
 
         term_buffer.put_ordered::<i32>(frame_descriptor::length_offset(tail), -frame_length);
-        term_buffer.copy_from(tail + data_frame_header::LENGTH, &msg_body, 0, msg_length);
+        term_buffer
+            .copy_from(tail + data_frame_header::LENGTH, &msg_body, 0, msg_length)
+            .unwrap();
         term_buffer.put::<i64>(tail + *data_frame_header::RESERVED_VALUE_FIELD_OFFSET, RESERVED_VALUE);
         term_buffer.put_ordered::<i32>(frame_descriptor::length_offset(tail), frame_length);
 
-        let resulting_offset1 =
-            term_appender.append_unfragmented_message(&header_writer, &msg_body, 0, msg_length, reserved_value_supplier, TERM_ID);
+        let resulting_offset1 = term_appender.append_unfragmented_message(
+            &header_writer,
+            &msg_body,
+            0,
+            msg_length,
+            reserved_value_supplier,
+            TERM_ID,
+            0,
+        );
         assert!(resulting_offset1.is_ok());
         assert_eq!(resulting_offset1.unwrap(), aligned_frame_length * 2);
     }
@@ -613,12 +795,85 @@ mod tests {
         term_buffer.put::<u16>(frame_descriptor::type_offset(tail_value), data_frame_header::HDR_TYPE_PAD);
         term_buffer.put_ordered::<i32>(frame_descriptor::length_offset(tail_value), frame_length);
 
-        let resulting_offset =
-            term_appender.append_unfragmented_message(&header_writer, &msg_body, 0, msg_length, reserved_value_supplier, TERM_ID);
+        let resulting_offset = term_appender.append_unfragmented_message(
+            &header_writer,
+            &msg_body,
+            0,
+            msg_length,
+            reserved_value_supplier,
+            TERM_ID,
+            0,
+        );
         assert!(resulting_offset.is_ok());
         assert_eq!(resulting_offset.unwrap(), TERM_APPENDER_FAILED);
     }
 
+    #[test]
+    #[allow(unused_variables)]
+    fn test_term_appender_append_padding_skipped_by_reader() {
+        let padding_length: Index = 100;
+        let aligned_padding_length =
+            bit_utils::align(padding_length + data_frame_header::LENGTH, frame_descriptor::FRAME_ALIGNMENT);
+        let msg_length: Index = 20;
+        let aligned_frame_length = bit_utils::align(msg_length + data_frame_header::LENGTH, frame_descriptor::FRAME_ALIGNMENT);
+
+        gen_test_data!(
+            metadata_buffer,
+            term_buffer,
+            hdr,
+            msg_body,
+            term_appender,
+            header_writer,
+            hidden_metadata_buffer
+        );
+
+        let packed_tail = pack_raw_tail(TERM_ID, 0);
+        let _prev_tail = hidden_metadata_buffer.get_and_add_i64(*TERM_TAIL_OFFSET, packed_tail);
+
+        let resulting_offset = term_appender.append_padding(&header_writer, padding_length, TERM_ID);
+        assert_eq!(resulting_offset.unwrap(), aligned_padding_length);
+
+        let appended = term_appender.term_buffer();
+        assert_eq!(
+            appended.get::<u16>(frame_descriptor::type_offset(0)),
+            data_frame_header::HDR_TYPE_PAD
+        );
+        assert_eq!(
+            frame_descriptor::frame_length_volatile(&appended, 0),
+            padding_length + data_frame_header::LENGTH
+        );
+
+        let resulting_offset = term_appender.append_unfragmented_message(
+            &header_writer,
+            &msg_body,
+            0,
+            msg_length,
+            reserved_value_supplier,
+            TERM_ID,
+            0,
+        );
+        assert_eq!(resulting_offset.unwrap(), aligned_padding_length + aligned_frame_length);
+
+        let mut offsets = vec![];
+        let mut header = Header::new(TERM_ID, TERM_BUFFER_CAPACITY);
+        let outcome = term_reader::read(
+            appended,
+            0,
+            &mut |_buffer: &AtomicBuffer, offset: Index, length: Index, _header: &Header| offsets.push((offset, length)),
+            10,
+            &mut header,
+            &VersionMismatchCounter::default(),
+            |_err| {},
+        );
+
+        assert_eq!(outcome.fragments_read, 1);
+        assert_eq!(outcome.offset, aligned_padding_length + aligned_frame_length);
+        assert_eq!(
+            offsets,
+            vec![(aligned_padding_length + data_frame_header::LENGTH, msg_length)]
+        );
+    }
+
     // Should fragment message over two frames
     #[test]
     #[allow(unused_variables)]
@@ -643,7 +898,9 @@ mod tests {
         let _prev_tail = hidden_metadata_buffer.get_and_add_i64(*TERM_TAIL_OFFSET, packed_tail);
 
         term_buffer.put_ordered::<i32>(frame_descriptor::length_offset(tail), -MAX_FRAME_LENGTH);
-        term_buffer.copy_from(tail + data_frame_header::LENGTH, &msg_body, 0, MAX_PAYLOAD_LENGTH);
+        term_buffer
+            .copy_from(tail + data_frame_header::LENGTH, &msg_body, 0, MAX_PAYLOAD_LENGTH)
+            .unwrap();
         term_buffer.put::<u8>(frame_descriptor::flags_offset(tail), frame_descriptor::BEGIN_FRAG);
         term_buffer.put::<i64>(tail + *data_frame_header::RESERVED_VALUE_FIELD_OFFSET, RESERVED_VALUE);
         term_buffer.put_ordered::<i32>(frame_descriptor::length_offset(tail), MAX_FRAME_LENGTH);
@@ -651,7 +908,9 @@ mod tests {
         tail = MAX_FRAME_LENGTH;
 
         term_buffer.put_ordered::<i32>(frame_descriptor::length_offset(tail), -frame_length);
-        term_buffer.copy_from(tail + data_frame_header::LENGTH, &msg_body, MAX_PAYLOAD_LENGTH, 1);
+        term_buffer
+            .copy_from(tail + data_frame_header::LENGTH, &msg_body, MAX_PAYLOAD_LENGTH, 1)
+            .unwrap();
         term_buffer.put::<u8>(frame_descriptor::flags_offset(tail), frame_descriptor::END_FRAG);
         term_buffer.put::<i64>(tail + *data_frame_header::RESERVED_VALUE_FIELD_OFFSET, RESERVED_VALUE);
         term_buffer.put_ordered::<i32>(frame_descriptor::length_offset(tail), frame_length);
@@ -664,6 +923,7 @@ mod tests {
             MAX_PAYLOAD_LENGTH,
             reserved_value_supplier,
             TERM_ID,
+            0,
         );
         assert!(resulting_offset.is_ok());
         assert_eq!(resulting_offset.unwrap(), required_capacity);
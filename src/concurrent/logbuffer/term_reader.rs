@@ -18,11 +18,12 @@ use crate::{
     concurrent::{
         atomic_buffer::AtomicBuffer,
         logbuffer::{
+            data_frame_header,
+            frame_descriptor::{self, VersionMismatchCounter},
             header::Header,
-            {data_frame_header, frame_descriptor},
         },
     },
-    utils::{bit_utils, errors::AeronError, types::Index},
+    utils::{errors::AeronError, misc::unix_time_ns, types::Index},
 };
 
 pub type ErrorHandler = fn(AeronError);
@@ -49,13 +50,48 @@ pub struct ReadOutcome {
     pub fragments_read: i32,
 }
 
+/**
+ * Wrap a fragment handler so that for each fragment the latency between the nano time stored in the reserved
+ * value by the publisher (see nano_time_reserved_value_supplier) and now is passed to the record callback.
+ *
+ * @param inner  fragment handler to which fragments are delegated.
+ * @param record callback receiving the latency in nanoseconds, e.g. to be stored in a histogram.
+ * @return fragment handler measuring latency.
+ */
+pub fn latency_measuring_handler(
+    inner: impl FnMut(&AtomicBuffer, Index, Index, &Header),
+    record: impl FnMut(i64),
+) -> impl FnMut(&AtomicBuffer, Index, Index, &Header) {
+    latency_measuring_handler_with_clock(inner, || unix_time_ns() as i64, record)
+}
+
+/**
+ * Same as latency_measuring_handler() but with the nano clock supplied by the caller.
+ *
+ * @param inner      fragment handler to which fragments are delegated.
+ * @param nano_clock returning current time in nanoseconds on the same time base as the publisher.
+ * @param record     callback receiving the latency in nanoseconds.
+ * @return fragment handler measuring latency.
+ */
+pub fn latency_measuring_handler_with_clock(
+    mut inner: impl FnMut(&AtomicBuffer, Index, Index, &Header),
+    mut nano_clock: impl FnMut() -> i64,
+    mut record: impl FnMut(i64),
+) -> impl FnMut(&AtomicBuffer, Index, Index, &Header) {
+    move |buffer: &AtomicBuffer, offset: Index, length: Index, header: &Header| {
+        record(nano_clock() - header.reserved_value());
+        inner(buffer, offset, length, header);
+    }
+}
+
 pub fn read(
     term_buffer: AtomicBuffer,
     mut term_offset: Index,
     data_handler: &mut impl FnMut(&AtomicBuffer, Index, Index, &Header),
     fragments_limit: i32,
     header: &mut Header,
-    _exception_handler: impl Fn(AeronError),
+    version_mismatch_counter: &VersionMismatchCounter,
+    exception_handler: impl Fn(AeronError),
 ) -> ReadOutcome {
     let mut outcome = ReadOutcome::default();
     outcome.fragments_read = 0;
@@ -70,9 +106,12 @@ pub fn read(
         }
 
         let fragment_offset = term_offset;
-        term_offset += bit_utils::align(frame_length as Index, frame_descriptor::FRAME_ALIGNMENT);
+        term_offset += frame_descriptor::aligned_frame_length(frame_length as Index);
 
         if !frame_descriptor::is_padding_frame(&term_buffer, fragment_offset) {
+            // Report version skew with the driver but still deliver the fragment
+            version_mismatch_counter.check(&term_buffer, fragment_offset, &exception_handler);
+
             header.set_buffer(term_buffer);
             header.set_offset(fragment_offset);
 
@@ -94,11 +133,17 @@ pub fn read(
 
 #[cfg(test)]
 mod tests {
+    use std::sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    };
+
     use super::*;
     use crate::concurrent::{
         atomic_buffer::AlignedBuffer,
         logbuffer::{log_buffer_descriptor, term_reader},
     };
+    use crate::utils::bit_utils;
 
     const LOG_BUFFER_CAPACITY: Index = log_buffer_descriptor::TERM_MIN_LENGTH;
     // const META_DATA_BUFFER_CAPACITY: Index = log_buffer_descriptor::LOG_META_DATA_LENGTH;
@@ -144,6 +189,7 @@ mod tests {
             &mut data_handler,
             INT_MAX,
             &mut fragment_header,
+            &VersionMismatchCounter::default(),
             error_handler,
         );
 
@@ -151,6 +197,38 @@ mod tests {
         assert_eq!(read_outcome.fragments_read, 1);
     }
 
+    #[test]
+    fn test_term_reader_count_version_mismatches_and_report_first_only() {
+        gen_test_data!(log, fragment_header);
+
+        let msg_length = 1;
+        let frame_length = data_frame_header::LENGTH + msg_length;
+        let aligned_frame_length = bit_utils::align(frame_length, frame_descriptor::FRAME_ALIGNMENT);
+
+        for i in 0..3 {
+            let frame_offset = i * aligned_frame_length;
+            log.put::<u16>(frame_descriptor::type_offset(frame_offset), data_frame_header::HDR_TYPE_DATA);
+            log.put_ordered::<i32>(frame_descriptor::length_offset(frame_offset), frame_length);
+        }
+        log.put::<u8>(frame_descriptor::VERSION_OFFSET, 0x7);
+        log.put::<u8>(2 * aligned_frame_length + frame_descriptor::VERSION_OFFSET, 0x7);
+
+        let count = Arc::new(AtomicU64::new(0));
+        let counter = VersionMismatchCounter::new(count.clone());
+        let errors = std::cell::Cell::new(0);
+
+        let read_outcome = term_reader::read(log, 0, &mut data_handler, INT_MAX, &mut fragment_header, &counter, |err| {
+            assert!(matches!(err, AeronError::IllegalStateException(_)));
+            errors.set(errors.get() + 1);
+        });
+
+        assert_eq!(read_outcome.fragments_read, 3);
+        assert_eq!(read_outcome.offset, aligned_frame_length * 3);
+        assert_eq!(errors.get(), 1);
+        assert_eq!(counter.count(), 2);
+        assert_eq!(count.load(Ordering::Relaxed), 2);
+    }
+
     #[test]
     fn test_term_reader_not_read_past_tail() {
         gen_test_data!(log, fragment_header);
@@ -165,6 +243,7 @@ mod tests {
             &mut data_handler,
             INT_MAX,
             &mut fragment_header,
+            &VersionMismatchCounter::default(),
             error_handler,
         );
 
@@ -185,7 +264,15 @@ mod tests {
 
         log.put::<u16>(frame_descriptor::type_offset(0), data_frame_header::HDR_TYPE_DATA);
 
-        let read_outcome = term_reader::read(log, term_offset, &mut data_handler, 1, &mut fragment_header, error_handler);
+        let read_outcome = term_reader::read(
+            log,
+            term_offset,
+            &mut data_handler,
+            1,
+            &mut fragment_header,
+            &VersionMismatchCounter::default(),
+            error_handler,
+        );
 
         assert_eq!(read_outcome.offset, aligned_frame_length);
         assert_eq!(read_outcome.fragments_read, 1);
@@ -217,6 +304,7 @@ mod tests {
             &mut data_handler,
             INT_MAX,
             &mut fragment_header,
+            &VersionMismatchCounter::default(),
             error_handler,
         );
 
@@ -245,6 +333,7 @@ mod tests {
             &mut data_handler,
             INT_MAX,
             &mut fragment_header,
+            &VersionMismatchCounter::default(),
             error_handler,
         );
 
@@ -273,10 +362,113 @@ mod tests {
             &mut data_handler,
             INT_MAX,
             &mut fragment_header,
+            &VersionMismatchCounter::default(),
             error_handler,
         );
 
         assert_eq!(read_outcome.offset, LOG_BUFFER_CAPACITY);
         assert_eq!(read_outcome.fragments_read, 0);
     }
+
+    #[test]
+    fn test_term_reader_read_zero_length_message() {
+        gen_test_data!(log, fragment_header);
+
+        let frame_length = data_frame_header::LENGTH;
+        let aligned_frame_length = bit_utils::align(frame_length, frame_descriptor::FRAME_ALIGNMENT);
+        let term_offset = 0;
+
+        log.put_ordered::<i32>(frame_descriptor::length_offset(0), frame_length);
+        log.put::<u16>(frame_descriptor::type_offset(0), data_frame_header::HDR_TYPE_DATA);
+
+        let mut received = vec![];
+        let mut handler = |_buf: &AtomicBuffer, offset: Index, length: Index, _header: &Header| {
+            received.push((offset, length));
+        };
+
+        let read_outcome = term_reader::read(
+            log,
+            term_offset,
+            &mut handler,
+            INT_MAX,
+            &mut fragment_header,
+            &VersionMismatchCounter::default(),
+            error_handler,
+        );
+
+        assert_eq!(read_outcome.offset, aligned_frame_length);
+        assert_eq!(read_outcome.fragments_read, 1);
+        assert_eq!(received, vec![(data_frame_header::LENGTH, 0)]);
+    }
+
+    #[test]
+    fn test_term_reader_measure_latency_from_reserved_value() {
+        gen_test_data!(log, fragment_header);
+
+        let msg_length = 1;
+        let frame_length = data_frame_header::LENGTH + msg_length;
+        let aligned_frame_length = bit_utils::align(frame_length, frame_descriptor::FRAME_ALIGNMENT);
+
+        for (i, timestamp) in [1_000_i64, 1_500].iter().enumerate() {
+            let frame_offset = aligned_frame_length * i as Index;
+            log.put::<u16>(frame_descriptor::type_offset(frame_offset), data_frame_header::HDR_TYPE_DATA);
+            log.put::<i64>(frame_offset + *data_frame_header::RESERVED_VALUE_FIELD_OFFSET, *timestamp);
+            log.put_ordered::<i32>(frame_descriptor::length_offset(frame_offset), frame_length);
+        }
+
+        let mut latencies = vec![];
+        let mut delivered = 0;
+        let mut handler = latency_measuring_handler_with_clock(
+            |_buf: &AtomicBuffer, _offset: Index, length: Index, _header: &Header| {
+                assert_eq!(length, msg_length);
+                delivered += 1;
+            },
+            || 2_000,
+            |latency| latencies.push(latency),
+        );
+
+        let read_outcome = term_reader::read(
+            log,
+            0,
+            &mut handler,
+            INT_MAX,
+            &mut fragment_header,
+            &VersionMismatchCounter::default(),
+            error_handler,
+        );
+        drop(handler);
+
+        assert_eq!(read_outcome.fragments_read, 2);
+        assert_eq!(delivered, 2);
+        assert_eq!(latencies, vec![1_000, 500]);
+        assert!(latencies.iter().all(|latency| *latency >= 0));
+    }
+
+    #[test]
+    fn test_term_reader_measure_latency_with_system_clock() {
+        gen_test_data!(log, fragment_header);
+
+        let frame_length = data_frame_header::LENGTH;
+        let stamp = crate::concurrent::logbuffer::term_appender::nano_time_reserved_value_supplier(log, 0, frame_length);
+        log.put::<u16>(frame_descriptor::type_offset(0), data_frame_header::HDR_TYPE_DATA);
+        log.put::<i64>(*data_frame_header::RESERVED_VALUE_FIELD_OFFSET, stamp);
+        log.put_ordered::<i32>(frame_descriptor::length_offset(0), frame_length);
+
+        let mut latencies = vec![];
+        let mut handler = latency_measuring_handler(data_handler, |latency| latencies.push(latency));
+
+        term_reader::read(
+            log,
+            0,
+            &mut handler,
+            INT_MAX,
+            &mut fragment_header,
+            &VersionMismatchCounter::default(),
+            error_handler,
+        );
+        drop(handler);
+
+        assert_eq!(latencies.len(), 1);
+        assert!(latencies[0] >= 0 && latencies[0] < 60 * 1_000_000_000);
+    }
 }
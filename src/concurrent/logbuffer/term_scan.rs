@@ -19,7 +19,7 @@ use crate::{
         atomic_buffer::AtomicBuffer,
         logbuffer::{data_frame_header, frame_descriptor},
     },
-    utils::{bit_utils, types::Index},
+    utils::types::Index,
 };
 
 /**
@@ -42,7 +42,7 @@ pub fn scan(term_buffer: &AtomicBuffer, term_offset: Index, limit_offset: Index)
             break;
         }
 
-        let aligned_frame_length = bit_utils::align(frame_length as Index, frame_descriptor::FRAME_ALIGNMENT);
+        let aligned_frame_length = frame_descriptor::aligned_frame_length(frame_length as Index);
 
         if frame_descriptor::is_padding_frame(term_buffer, offset) {
             if term_offset == offset {
@@ -83,7 +83,7 @@ pub fn scan_for_gap(
             break;
         }
 
-        rebuild_offset += bit_utils::align(frame_length as Index, frame_descriptor::FRAME_ALIGNMENT);
+        rebuild_offset += frame_descriptor::aligned_frame_length(frame_length as Index);
 
         if rebuild_offset >= hwm_offset {
             break;
@@ -135,7 +135,7 @@ pub fn scan_for_availability(term_buffer: &AtomicBuffer, offset: Index, max_leng
             break;
         }
 
-        let mut aligned_frame_length = bit_utils::align(frame_length as Index, frame_descriptor::FRAME_ALIGNMENT);
+        let mut aligned_frame_length = frame_descriptor::aligned_frame_length(frame_length as Index);
 
         if frame_descriptor::is_padding_frame(term_buffer, frame_offset) {
             padding = aligned_frame_length - data_frame_header::LENGTH;
@@ -165,6 +165,7 @@ mod tests {
         atomic_buffer::AlignedBuffer,
         logbuffer::{log_buffer_descriptor, term_scan},
     };
+    use crate::utils::bit_utils;
 
     const LOG_BUFFER_CAPACITY: Index = log_buffer_descriptor::TERM_MIN_LENGTH;
     const TERM_ID: i32 = 1;
@@ -106,6 +106,8 @@ pub mod record_descriptor {
         AeronCommand::from_command_id(type_id)
     }
 
+    /// Message type ids of 0 and below are reserved for the ring buffer's own records, e.g. padding, so
+    /// only ids of 1 and above may be written by applications.
     #[inline]
     pub fn check_msg_type_id(msg_type_id: i32) -> Result<(), RingBufferError> {
         if msg_type_id < 1 {
@@ -178,6 +180,8 @@ impl ManyToOneRingBuffer {
         })
     }
 
+    /// Write a message of type `cmd` copied from `src_buffer`. The type id must be 1 or above as 0 and
+    /// negative ids are reserved, otherwise `NonPositiveMessageTypeId` is returned and nothing is written.
     pub fn write(
         &self,
         cmd: AeronCommand,
@@ -196,12 +200,14 @@ impl ManyToOneRingBuffer {
         self.buffer
             .put_ordered::<i64>(record_index, record_descriptor::make_header(-record_len, cmd));
 
-        self.buffer.copy_from(
-            record_descriptor::encoded_msg_offset(record_index),
-            &src_buffer,
-            src_index,
-            length,
-        );
+        self.buffer
+            .copy_from(
+                record_descriptor::encoded_msg_offset(record_index),
+                &src_buffer,
+                src_index,
+                length,
+            )
+            .expect("copy_from failed");
 
         self.buffer
             .put_ordered::<i32>(record_descriptor::length_offset(record_index), record_len);
@@ -520,6 +526,36 @@ mod tests {
         assert_eq!(write_res.unwrap_err(), RingBufferError::MessageTooLong { msg: 129, max: 128 });
     }
 
+    #[test]
+    fn ring_buffer_reject_reserved_message_type_ids() {
+        let test = Test::new();
+
+        let write_res = test.ring_buffer.write(AeronCommand::Padding, test.src_ab, 0, 8);
+        assert_eq!(
+            write_res.unwrap_err(),
+            RingBufferError::NonPositiveMessageTypeId(AeronCommand::Padding as i32)
+        );
+        assert_eq!(test.ab.get::<i64>(TAIL_COUNTER_INDEX), 0);
+
+        assert_eq!(
+            record_descriptor::check_msg_type_id(0),
+            Err(RingBufferError::NonPositiveMessageTypeId(0))
+        );
+        assert_eq!(
+            record_descriptor::check_msg_type_id(i32::MIN),
+            Err(RingBufferError::NonPositiveMessageTypeId(i32::MIN))
+        );
+        assert_eq!(record_descriptor::check_msg_type_id(1), Ok(()));
+
+        test.ring_buffer
+            .write(AeronCommand::UnitTestMessageTypeID, test.src_ab, 0, 8)
+            .unwrap();
+        assert_eq!(
+            test.ab.get::<i64>(TAIL_COUNTER_INDEX),
+            align(8 + record_descriptor::HEADER_LENGTH, record_descriptor::ALIGNMENT) as i64
+        );
+    }
+
     #[test]
     fn ring_buffer_that_writes_to_empty() {
         let test = Test::new();
@@ -143,6 +143,10 @@ impl SleepingIdleStrategy {
     pub fn new(duration: Moment) -> Self {
         Self { duration }
     }
+
+    pub fn duration(&self) -> Moment {
+        self.duration
+    }
 }
 
 impl Strategy for SleepingIdleStrategy {
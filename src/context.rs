@@ -27,7 +27,7 @@ use crate::{
         errors::AeronError,
         memory_mapped_file::MemoryMappedFile,
         misc::{semantic_version_major, semantic_version_to_string},
-        types::{Index, Moment},
+        types::Moment,
     },
 };
 
@@ -120,8 +120,15 @@ pub type OnUnavailableCounter = fn(counters_reader: &CountersReader, registratio
  */
 pub type OnCloseClient = fn();
 
+/**
+ * Function called to supply the session id of an exclusive publication whose channel does not specify one,
+ * e.g. to make session ids deterministic in tests.
+ */
+pub type SessionIdSupplier = fn() -> i32;
+
 const DEFAULT_MEDIA_DRIVER_TIMEOUT_MS: Moment = 10000;
 const DEFAULT_RESOURCE_LINGER_MS: Moment = 5000;
+const DEFAULT_CONDUCTOR_MIN_IDLE_PERIOD_MS: Moment = 4;
 
 /**
  * The Default handler for Aeron runtime exceptions.
@@ -170,9 +177,14 @@ pub struct Context {
     on_close_client_handler: OnCloseClient,
     media_driver_timeout: Moment,
     resource_linger_timeout: Moment,
+    inter_service_timeout: Moment,
+    conductor_min_idle_period: Moment,
     use_conductor_agent_invoker: bool,
     is_on_new_exclusive_publication_handler_set: bool,
     pre_touch_mapped_memory: bool,
+    shared_position_group: Option<String>,
+    session_id_supplier: Option<SessionIdSupplier>,
+    sparse_term_buffers: Option<bool>,
     agent_name: String,
 }
 
@@ -197,9 +209,14 @@ impl Context {
             on_close_client_handler: default_on_close_client_handler,
             media_driver_timeout: DEFAULT_MEDIA_DRIVER_TIMEOUT_MS,
             resource_linger_timeout: DEFAULT_RESOURCE_LINGER_MS,
+            inter_service_timeout: 0,
+            conductor_min_idle_period: DEFAULT_CONDUCTOR_MIN_IDLE_PERIOD_MS,
             use_conductor_agent_invoker: false,
             is_on_new_exclusive_publication_handler_set: false,
             pre_touch_mapped_memory: false,
+            shared_position_group: None,
+            session_id_supplier: None,
+            sparse_term_buffers: None,
             agent_name: String::from(AGENT_NAME),
         }
     }
@@ -424,6 +441,39 @@ impl Context {
         self.resource_linger_timeout
     }
 
+    /**
+     * Set the amount of time, in milliseconds, the conductor may go without a duty cycle before the client
+     * considers itself timed out. A value of 0 uses the client liveness timeout advertised by the Media Driver.
+     *
+     * @param value Number of milliseconds.
+     * @return reference to this Context instance
+     */
+    pub fn set_inter_service_timeout(&mut self, value: Moment) -> &Self {
+        self.inter_service_timeout = value;
+        self
+    }
+
+    pub fn inter_service_timeout(&self) -> Moment {
+        self.inter_service_timeout
+    }
+
+    /**
+     * Set the minimum amount of time, in milliseconds, between conductor duty cycles when the previous cycle
+     * did no work. This is the sleep period of the conductor thread's idle strategy. Larger values reduce CPU
+     * usage of an idle client at the cost of reaction time.
+     *
+     * @param value Number of milliseconds.
+     * @return reference to this Context instance
+     */
+    pub fn set_conductor_min_idle_period(&mut self, value: Moment) -> &Self {
+        self.conductor_min_idle_period = value;
+        self
+    }
+
+    pub fn conductor_min_idle_period(&self) -> Moment {
+        self.conductor_min_idle_period
+    }
+
     /**
      * Set whether to use an invoker to control the conductor agent or spawn a thread.
      *
@@ -454,7 +504,57 @@ impl Context {
         self.pre_touch_mapped_memory
     }
 
-    pub fn request_driver_termination(directory: &str, token_buffer: *mut u8, token_length: Index) -> Result<(), AeronError> {
+    /**
+     * Set the tag of a shared position group. Subscriptions of the client join the group and share a single
+     * consumption position per Image of the same stream so each message is delivered to exactly one Subscription
+     * of the group. Subscriptions of different channels or streams never share a position.
+     *
+     * @param tag identifying the group.
+     * @return reference to this Context instance
+     */
+    pub fn set_shared_position_group(&mut self, tag: &str) -> &Self {
+        self.shared_position_group = Some(String::from(tag));
+        self
+    }
+
+    pub fn shared_position_group(&self) -> Option<String> {
+        self.shared_position_group.clone()
+    }
+
+    /**
+     * Set the supplier of session ids for exclusive publications added without a session-id in their channel.
+     * Without a supplier the media driver assigns a random session id.
+     *
+     * @param supplier called once for each such exclusive publication.
+     * @return reference to this Context instance
+     */
+    pub fn set_session_id_supplier(&mut self, supplier: SessionIdSupplier) -> &Self {
+        self.session_id_supplier = Some(supplier);
+        self
+    }
+
+    pub fn session_id_supplier(&self) -> Option<SessionIdSupplier> {
+        self.session_id_supplier
+    }
+
+    /**
+     * Set whether the media driver should create the term buffers of new publications and images as sparse files
+     * which are not pre-zeroed. Channels which specify the sparse parameter themselves are left unchanged and
+     * sparse log buffers are never pre-touched. Without this setting the media driver default applies.
+     *
+     * @param sparse_term_buffers true to request sparse term buffers otherwise false.
+     * @return reference to this Context instance
+     */
+    pub fn set_sparse_term_buffers(&mut self, sparse_term_buffers: bool) -> &Self {
+        self.sparse_term_buffers = Some(sparse_term_buffers);
+        self
+    }
+
+    pub fn sparse_term_buffers(&self) -> Option<bool> {
+        self.sparse_term_buffers
+    }
+
+    pub fn request_driver_termination(directory: &str, token: Option<&[u8]>) -> Result<(), AeronError> {
         let cnc_filename = String::from(directory) + "/" + cnc_file_descriptor::CNC_FILE;
 
         if MemoryMappedFile::get_file_size(cnc_filename.clone()).expect("Error getting CnC file size") > 0 {
@@ -474,7 +574,7 @@ impl Context {
             let ring_buffer = ManyToOneRingBuffer::new(to_driver_buffer).expect("ManyToOneRingBuffer creation failed");
             let driver_proxy = DriverProxy::new(Arc::new(ring_buffer));
 
-            driver_proxy.terminate_driver(token_buffer, token_length)?;
+            driver_proxy.terminate_driver(token)?;
         }
         Ok(())
     }
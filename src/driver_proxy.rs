@@ -29,7 +29,10 @@ use crate::{
         terminate_driver_flyweight::TerminateDriverFlyweight,
     },
     concurrent::{atomic_buffer::AtomicBuffer, ring_buffer::ManyToOneRingBuffer},
-    utils::{errors::AeronError, types::Index},
+    utils::{
+        errors::AeronError,
+        types::{to_i32, Index},
+    },
 };
 
 pub struct DriverProxy {
@@ -236,6 +239,7 @@ impl DriverProxy {
 
     pub fn add_counter(&self, type_id: i32, key: &[u8], label: CString) -> Result<i64, AeronError> {
         let correlation_id = self.to_driver_command_buffer.next_correlation_id();
+        let key_length = to_i32(key.len() as isize)?;
 
         self.write_command_to_driver(|buffer, length| {
             let mut command = CounterMessageFlyweight::new(buffer, 0);
@@ -244,7 +248,7 @@ impl DriverProxy {
             command.set_correlation_id(correlation_id);
             command.set_type_id(type_id);
             unsafe {
-                command.set_key_buffer(key.as_ptr(), key.len() as i32);
+                command.set_key_buffer(key.as_ptr(), key_length);
             }
             command.set_label(label.as_bytes());
 
@@ -291,14 +295,22 @@ impl DriverProxy {
         Ok(correlation_id)
     }
 
-    pub fn terminate_driver(&self, token_buffer: *const u8, token_length: Index) -> Result<(), AeronError> {
+    /**
+     * Ask the media driver to terminate. The optional token is passed to the driver's termination validator.
+     *
+     * @param token to be validated by the driver or None when no token is required.
+     */
+    pub fn terminate_driver(&self, token: Option<&[u8]>) -> Result<(), AeronError> {
+        let token = token.unwrap_or(&[]);
+        let token_length = to_i32(token.len() as isize)?;
+
         self.write_command_to_driver(|buffer, length| {
             let mut request = TerminateDriverFlyweight::new(buffer, 0);
 
             request.set_client_id(self.client_id);
             request.set_correlation_id(-1);
             unsafe {
-                request.set_token_buffer(token_buffer, token_length);
+                request.set_token_buffer(token.as_ptr(), token_length);
             }
 
             *length = request.length();
@@ -344,3 +356,67 @@ impl Default for DriverProxyCommandBuffer {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::command::terminate_driver_flyweight::TERMINATE_DRIVER_LENGTH;
+    use crate::concurrent::atomic_buffer::AlignedBuffer;
+    use crate::concurrent::ring_buffer;
+
+    use super::*;
+
+    const CAPACITY: Index = 1024;
+
+    fn make_ring_buffer(buffer: &AlignedBuffer) -> Arc<ManyToOneRingBuffer> {
+        Arc::new(ManyToOneRingBuffer::new(AtomicBuffer::from_aligned(buffer)).unwrap())
+    }
+
+    #[test]
+    fn should_write_terminate_driver_with_token() {
+        let buffer = AlignedBuffer::with_capacity(CAPACITY + ring_buffer::TRAILER_LENGTH);
+        let ring_buffer = make_ring_buffer(&buffer);
+        let driver_proxy = DriverProxy::new(ring_buffer.clone());
+        let token = b"secret";
+
+        driver_proxy.terminate_driver(Some(token)).unwrap();
+
+        let count = ring_buffer.read(
+            |msg_type_id, buffer| {
+                let message = TerminateDriverFlyweight::new(buffer, 0);
+
+                assert_eq!(msg_type_id, AeronCommand::TerminateDriver);
+                assert_eq!(message.client_id(), driver_proxy.client_id());
+                assert_eq!(message.correlation_id(), -1);
+                assert_eq!(message.token_length(), token.len() as i32);
+                assert_eq!(message.length(), TERMINATE_DRIVER_LENGTH + token.len() as Index);
+                let written = unsafe { std::slice::from_raw_parts(message.token_buffer(), token.len()) };
+                assert_eq!(written, token);
+            },
+            10,
+        );
+
+        assert_eq!(count, 1);
+    }
+
+    #[test]
+    fn should_write_terminate_driver_without_token() {
+        let buffer = AlignedBuffer::with_capacity(CAPACITY + ring_buffer::TRAILER_LENGTH);
+        let ring_buffer = make_ring_buffer(&buffer);
+        let driver_proxy = DriverProxy::new(ring_buffer.clone());
+
+        driver_proxy.terminate_driver(None).unwrap();
+
+        let count = ring_buffer.read(
+            |msg_type_id, buffer| {
+                let message = TerminateDriverFlyweight::new(buffer, 0);
+
+                assert_eq!(msg_type_id, AeronCommand::TerminateDriver);
+                assert_eq!(message.token_length(), 0);
+                assert_eq!(message.length(), TERMINATE_DRIVER_LENGTH);
+            },
+            10,
+        );
+
+        assert_eq!(count, 1);
+    }
+}
@@ -15,11 +15,14 @@
  */
 
 use std::{
+    borrow::Cow,
     ffi::CString,
+    panic::{self, AssertUnwindSafe},
     sync::{
         atomic::{AtomicBool, Ordering},
         Arc, Mutex,
     },
+    time::Duration,
 };
 
 use crate::{
@@ -38,8 +41,8 @@ use crate::{
         position::{ReadablePosition, UnsafeBufferPosition},
         status::status_indicator_reader,
     },
-    publication::{ADMIN_ACTION, BACK_PRESSURED, MAX_POSITION_EXCEEDED, NOT_CONNECTED, PUBLICATION_CLOSED},
-    utils::{bit_utils::number_of_trailing_zeroes, errors::AeronError, log_buffers::LogBuffers, types::Index},
+    publication::{OnTermRotation, ADMIN_ACTION, BACK_PRESSURED, MAX_POSITION_EXCEEDED, NOT_CONNECTED, PUBLICATION_CLOSED},
+    utils::{bit_utils::number_of_trailing_zeroes, errors::AeronError, log_buffers::LogBuffers, misc::spin_until, types::Index},
 };
 
 /**
@@ -89,6 +92,7 @@ pub struct ExclusivePublication {
     // it was unique_ptr on TermAppender's
     appenders: [ExclusiveTermAppender; log_buffer_descriptor::PARTITION_COUNT as usize],
     header_writer: HeaderWriter,
+    on_term_rotation: Option<OnTermRotation>,
 }
 
 impl ExclusivePublication {
@@ -145,6 +149,7 @@ impl ExclusivePublication {
             log_buffers,
             header_writer: HeaderWriter::new(log_buffer_descriptor::default_frame_header(&log_md_buffer)),
             appenders,
+            on_term_rotation: None,
         }
     }
 
@@ -158,6 +163,16 @@ impl ExclusivePublication {
         self.channel.clone()
     }
 
+    /**
+     * Media address for delivery to the channel as a string, e.g. for logging or re-adding destinations.
+     * Invalid UTF-8 sequences are replaced with U+FFFD rather than failing.
+     *
+     * @return Media address for delivery to the channel.
+     */
+    pub fn channel_str(&self) -> Cow<'_, str> {
+        self.channel.to_string_lossy()
+    }
+
     /**
      * Stream identity for scoping within the channel media address.
      *
@@ -290,6 +305,17 @@ impl ExclusivePublication {
         !self.is_closed() && log_buffer_descriptor::is_connected(&self.log_meta_data_buffer)
     }
 
+    /**
+     * Spin until this Publication sees an active subscriber, yielding the thread between checks of the
+     * is_connected flag in the log meta data.
+     *
+     * @param timeout to wait for a subscriber to connect.
+     * @return true if connected within the timeout, false if timed out or the Publication was closed.
+     */
+    pub fn await_connected(&self, timeout: Duration) -> bool {
+        spin_until(timeout, || self.is_connected(), || self.is_closed())
+    }
+
     /**
      * Has this object been closed and should no longer be used?
      *
@@ -355,6 +381,15 @@ impl ExclusivePublication {
         }
     }
 
+    /**
+     * Set the callback to be invoked each time this publication rotates to a new term.
+     *
+     * @param handler to be called with the old and new term ids.
+     */
+    pub fn set_on_term_rotation(&mut self, handler: OnTermRotation) {
+        self.on_term_rotation = Some(handler);
+    }
+
     /**
      * Get the counter id used to represent the channel status.
      *
@@ -381,6 +416,39 @@ impl ExclusivePublication {
         offset: Index,
         length: Index,
         reserved_value_supplier: OnReservedValueSupplier,
+    ) -> Result<i64, AeronError> {
+        self.append_message(buffer, offset, length, reserved_value_supplier, 0)
+    }
+
+    /**
+     * Non-blocking publish of a buffer containing a message with extra flag bits set on each of its frames, e.g.
+     * for protocol extensions. The extra flags are OR-ed with the BEGIN/END flags computed for each frame.
+     *
+     * @param buffer      containing message.
+     * @param offset      offset in the buffer at which the encoded message begins.
+     * @param length      in bytes of the encoded message.
+     * @param extra_flags to set on each frame, which must not contain the BEGIN_FRAG or END_FRAG bits.
+     * @return The new stream position, otherwise {@link #NOT_CONNECTED}, {@link #BACK_PRESSURED},
+     * {@link #ADMIN_ACTION} or {@link #CLOSED}, or IllegalArgumentException if the extra flags are reserved.
+     */
+    pub fn offer_with_flags(
+        &mut self,
+        buffer: AtomicBuffer,
+        offset: Index,
+        length: Index,
+        extra_flags: u8,
+    ) -> Result<i64, AeronError> {
+        frame_descriptor::check_extra_flags(extra_flags)?;
+        self.append_message(buffer, offset, length, default_reserved_value_supplier, extra_flags)
+    }
+
+    fn append_message(
+        &mut self,
+        buffer: AtomicBuffer,
+        offset: Index,
+        length: Index,
+        reserved_value_supplier: OnReservedValueSupplier,
+        extra_flags: u8,
     ) -> Result<i64, AeronError> {
         let mut new_position = PUBLICATION_CLOSED;
 
@@ -399,7 +467,8 @@ impl ExclusivePublication {
                         offset,
                         length,
                         reserved_value_supplier,
-                    )
+                        extra_flags,
+                    )?
                 } else {
                     if length > self.max_message_length {
                         return Err(AeronError::IllegalArgumentException(format!(
@@ -416,7 +485,8 @@ impl ExclusivePublication {
                         length,
                         self.max_payload_length,
                         reserved_value_supplier,
-                    )
+                        extra_flags,
+                    )?
                 };
 
                 new_position = self.new_position(resulting_offset);
@@ -513,7 +583,7 @@ impl ExclusivePublication {
                         buffers,
                         length,
                         reserved_value_supplier,
-                    )
+                    )?
                 } else {
                     if length > self.max_message_length {
                         return Err(AeronError::IllegalArgumentException(format!(
@@ -529,7 +599,7 @@ impl ExclusivePublication {
                         buffers,
                         length,
                         reserved_value_supplier,
-                    )
+                    )?
                 };
 
                 new_position = self.new_position(resulting_offset);
@@ -556,6 +626,40 @@ impl ExclusivePublication {
      * @see BufferClaim::abort
      */
     pub fn try_claim(&mut self, length: Index, mut buffer_claim: BufferClaim) -> Result<i64, AeronError> {
+        self.claim(length, &mut buffer_claim)
+    }
+
+    /**
+     * Claim a range in the publication log, let the writer construct the message in place and then commit it.
+     * The writer is only invoked when the claim succeeds. Should the writer panic the claim is aborted, so that
+     * subscribers skip it as padding, before the panic is resumed.
+     *
+     * @param length of the range to claim, in bytes.
+     * @param writer called with the claimed buffer and the offset at which the payload begins.
+     * @return The new stream position, otherwise {@link #NOT_CONNECTED}, {@link #BACK_PRESSURED},
+     * {@link #ADMIN_ACTION}, {@link #CLOSED} or {@link #MAX_POSITION_EXCEEDED}.
+     * @throws IllegalArgumentException if the length is greater than max payload length within an MTU.
+     */
+    pub fn claim_and_write(&mut self, length: Index, writer: impl FnOnce(&mut AtomicBuffer, Index)) -> Result<i64, AeronError> {
+        let mut buffer_claim = BufferClaim::default();
+        let new_position = self.claim(length, &mut buffer_claim)?;
+
+        if new_position > 0 {
+            let mut buffer = buffer_claim.buffer();
+            let offset = buffer_claim.offset();
+
+            if let Err(panic) = panic::catch_unwind(AssertUnwindSafe(|| writer(&mut buffer, offset))) {
+                buffer_claim.abort();
+                panic::resume_unwind(panic);
+            }
+
+            buffer_claim.commit();
+        }
+
+        Ok(new_position)
+    }
+
+    fn claim(&mut self, length: Index, buffer_claim: &mut BufferClaim) -> Result<i64, AeronError> {
         self.check_payload_length(length)?;
         let mut new_position = PUBLICATION_CLOSED;
 
@@ -566,7 +670,7 @@ impl ExclusivePublication {
 
             if position < limit {
                 let resulting_offset =
-                    term_appender.claim(self.term_id, self.term_offset, &self.header_writer, length, &mut buffer_claim);
+                    term_appender.claim(self.term_id, self.term_offset, &self.header_writer, length, buffer_claim);
                 new_position = self.new_position(resulting_offset);
             } else {
                 new_position = self.back_pressure_status(position, length);
@@ -653,6 +757,10 @@ impl ExclusivePublication {
         log_buffer_descriptor::initialize_tail_with_term_id(&self.log_meta_data_buffer, next_index, next_term_id);
         log_buffer_descriptor::set_active_term_count_ordered(&self.log_meta_data_buffer, term_count);
 
+        if let Some(on_term_rotation) = self.on_term_rotation {
+            on_term_rotation(next_term_id - 1, next_term_id);
+        }
+
         ADMIN_ACTION
     }
 
@@ -706,7 +814,12 @@ impl Drop for ExclusivePublication {
 #[cfg(test)]
 mod tests {
     use std::ffi::CString;
-    use std::sync::{Arc, Mutex};
+    use std::sync::{
+        atomic::{AtomicI32, Ordering},
+        Arc, Mutex,
+    };
+    use std::thread;
+    use std::time::Duration;
 
     use lazy_static::lazy_static;
 
@@ -722,8 +835,10 @@ mod tests {
             logbuffer::{
                 buffer_claim::BufferClaim,
                 data_frame_header::LENGTH,
-                frame_descriptor,
+                frame_descriptor::{self, VersionMismatchCounter},
+                header::Header,
                 log_buffer_descriptor::{self, AERON_PAGE_MIN_SIZE, TERM_MIN_LENGTH},
+                term_reader,
             },
             position::{ReadablePosition, UnsafeBufferPosition},
             ring_buffer::{self, ManyToOneRingBuffer},
@@ -733,6 +848,7 @@ mod tests {
         exclusive_publication::ExclusivePublication,
         publication::{ADMIN_ACTION, NOT_CONNECTED, PUBLICATION_CLOSED},
         utils::{
+            bit_utils::align,
             errors::AeronError,
             log_buffers::LogBuffers,
             misc::unix_time_ms,
@@ -927,6 +1043,17 @@ mod tests {
         }
     }
 
+    #[test]
+    fn should_report_channel_given_at_creation() {
+        let test = ExclusivePublicationTest::new();
+        assert_eq!(test.publication.channel(), *CHANNEL);
+        assert_eq!(test.publication.channel_str(), "aeron:udp?endpoint=localhost:40123");
+        assert_eq!(
+            test.publication.original_registration_id(),
+            test.publication.registration_id()
+        );
+    }
+
     #[test]
     fn should_report_initial_position() {
         let test = ExclusivePublicationTest::new();
@@ -962,6 +1089,23 @@ mod tests {
         assert!(test.publication.is_connected());
     }
 
+    #[test]
+    fn should_await_connected_flag_set_from_another_thread() {
+        let test = ExclusivePublicationTest::new();
+        log_buffer_descriptor::set_is_connected(&test.log_meta_data_buffer, false);
+        assert!(!test.publication.await_connected(Duration::from_millis(1)));
+
+        let address = test.log_meta_data_buffer.buffer() as usize;
+        let length = test.log_meta_data_buffer.capacity();
+        let driver = thread::spawn(move || {
+            let log_meta_data_buffer = AtomicBuffer::new(address as *mut u8, length);
+            log_buffer_descriptor::set_is_connected(&log_meta_data_buffer, true);
+        });
+
+        assert!(test.publication.await_connected(Duration::from_secs(5)));
+        driver.join().unwrap();
+    }
+
     #[test]
     fn should_ensure_the_publication_is_open_before_reading_position() {
         let test = ExclusivePublicationTest::new();
@@ -997,6 +1141,75 @@ mod tests {
         assert_eq!(test.publication.position(), expected_position as i64);
     }
 
+    #[test]
+    fn should_offer_and_read_zero_length_message() {
+        let mut test = ExclusivePublicationTest::new();
+        test.publication_limit.set(2 * test.src_buffer.capacity() as i64);
+
+        assert_eq!(test.publication.offer_part(test.src_buffer, 0, 0).unwrap(), LENGTH as i64);
+        assert_eq!(test.publication.position(), LENGTH as i64);
+
+        let index = log_buffer_descriptor::index_by_term(TERM_ID_1, TERM_ID_1);
+        let mut header = Header::new(TERM_ID_1, TERM_MIN_LENGTH);
+        let mut received = vec![];
+        let mut handler = |_buffer: &AtomicBuffer, offset: Index, length: Index, _header: &Header| {
+            received.push((offset, length));
+        };
+
+        let outcome = term_reader::read(
+            test.term_buffers[index as usize],
+            0,
+            &mut handler,
+            10,
+            &mut header,
+            &VersionMismatchCounter::default(),
+            error_handler,
+        );
+
+        assert_eq!(received, vec![(LENGTH, 0)]);
+        assert_eq!(outcome.fragments_read, 1);
+        assert_eq!(outcome.offset, LENGTH);
+    }
+
+    #[test]
+    fn should_offer_with_extra_flags_and_read_them_back() {
+        let mut test = ExclusivePublicationTest::new();
+        test.publication_limit.set(2 * test.src_buffer.capacity() as i64);
+        let extra_flags = 0x01;
+
+        assert!(matches!(
+            test.publication
+                .offer_with_flags(test.src_buffer, 0, 8, frame_descriptor::END_FRAG),
+            Err(AeronError::IllegalArgumentException(_))
+        ));
+        assert_eq!(test.publication.position(), 0);
+
+        let expected_position = align(LENGTH + 8, frame_descriptor::FRAME_ALIGNMENT) as i64;
+        assert_eq!(
+            test.publication.offer_with_flags(test.src_buffer, 0, 8, extra_flags).unwrap(),
+            expected_position
+        );
+
+        let index = log_buffer_descriptor::index_by_term(TERM_ID_1, TERM_ID_1);
+        let mut header = Header::new(TERM_ID_1, TERM_MIN_LENGTH);
+        let mut received_flags = vec![];
+        let mut handler = |_buffer: &AtomicBuffer, _offset: Index, _length: Index, header: &Header| {
+            received_flags.push(header.flags());
+        };
+
+        term_reader::read(
+            test.term_buffers[index as usize],
+            0,
+            &mut handler,
+            10,
+            &mut header,
+            &VersionMismatchCounter::default(),
+            error_handler,
+        );
+
+        assert_eq!(received_flags, vec![frame_descriptor::UNFRAGMENTED | extra_flags]);
+    }
+
     #[test]
     fn should_fail_to_offer_a_message_when_limited() {
         let mut test = ExclusivePublicationTest::new();
@@ -1057,6 +1270,33 @@ mod tests {
         assert!(test.publication.position() > (initial_position + LENGTH + test.src_buffer.capacity()) as i64);
     }
 
+    #[test]
+    fn should_notify_term_rotation_when_append_trips() {
+        static OLD_TERM_ID: AtomicI32 = AtomicI32::new(0);
+        static NEW_TERM_ID: AtomicI32 = AtomicI32::new(0);
+
+        fn on_term_rotation(old_term_id: i32, new_term_id: i32) {
+            OLD_TERM_ID.store(old_term_id, Ordering::SeqCst);
+            NEW_TERM_ID.store(new_term_id, Ordering::SeqCst);
+        }
+
+        let mut test = ExclusivePublicationTest::new();
+        let active_index = log_buffer_descriptor::index_by_term(TERM_ID_1, TERM_ID_1);
+        let initial_position = TERM_MIN_LENGTH - LENGTH;
+
+        test.log_meta_data_buffer.put(
+            term_tail_counter_offset(active_index),
+            raw_tail_value(TERM_ID_1, initial_position as i64),
+        );
+        test.publication_limit.set(i32::max_value() as i64);
+        test.create_pub();
+        test.publication.set_on_term_rotation(on_term_rotation);
+
+        assert_eq!(test.publication.offer(test.src_buffer).unwrap(), ADMIN_ACTION);
+        assert_eq!(OLD_TERM_ID.load(Ordering::SeqCst), TERM_ID_1);
+        assert_eq!(NEW_TERM_ID.load(Ordering::SeqCst), TERM_ID_1 + 1);
+    }
+
     #[test]
     fn should_rotate_when_claim_trips() {
         let mut test = ExclusivePublicationTest::new();
@@ -1092,4 +1332,87 @@ mod tests {
         );
         assert!(test.publication.position() > (initial_position + LENGTH + test.src_buffer.capacity()) as i64);
     }
+
+    #[test]
+    fn should_commit_payload_written_by_claim_and_write() {
+        let mut test = ExclusivePublicationTest::new();
+        let payload_length = 100;
+        test.publication_limit.set(2 * test.src_buffer.capacity() as i64);
+
+        let position = test
+            .publication
+            .claim_and_write(payload_length, |buffer, offset| {
+                for i in 0..payload_length {
+                    buffer.put::<u8>(offset + i, i as u8);
+                }
+            })
+            .unwrap();
+
+        let expected_offset = align(LENGTH + payload_length, frame_descriptor::FRAME_ALIGNMENT);
+        assert_eq!(position, expected_offset as i64);
+        assert_eq!(test.publication.position(), expected_offset as i64);
+
+        let index = log_buffer_descriptor::index_by_term(TERM_ID_1, TERM_ID_1);
+        let mut header = Header::new(TERM_ID_1, TERM_MIN_LENGTH);
+        let mut received = vec![];
+        let mut handler = |buffer: &AtomicBuffer, offset: Index, length: Index, _header: &Header| {
+            for i in 0..length {
+                received.push(buffer.get::<u8>(offset + i));
+            }
+        };
+
+        let outcome = term_reader::read(
+            test.term_buffers[index as usize],
+            0,
+            &mut handler,
+            10,
+            &mut header,
+            &VersionMismatchCounter::default(),
+            error_handler,
+        );
+
+        assert_eq!(outcome.fragments_read, 1);
+        assert_eq!(outcome.offset, expected_offset);
+        assert_eq!(received, (0..payload_length).map(|i| i as u8).collect::<Vec<u8>>());
+    }
+
+    #[test]
+    fn should_abort_claim_when_writer_panics() {
+        let mut test = ExclusivePublicationTest::new();
+        let payload_length = 100;
+        test.publication_limit.set(2 * test.src_buffer.capacity() as i64);
+
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            test.publication
+                .claim_and_write(payload_length, |_buffer, _offset| panic!("writer failed"))
+        }));
+        assert!(result.is_err());
+
+        let expected_offset = align(LENGTH + payload_length, frame_descriptor::FRAME_ALIGNMENT);
+        assert_eq!(test.publication.position(), expected_offset as i64);
+
+        let index = log_buffer_descriptor::index_by_term(TERM_ID_1, TERM_ID_1);
+        let term_buffer = test.term_buffers[index as usize];
+        assert!(frame_descriptor::is_padding_frame(&term_buffer, 0));
+
+        let mut header = Header::new(TERM_ID_1, TERM_MIN_LENGTH);
+        let mut fragments = 0;
+        let mut handler = |_buffer: &AtomicBuffer, _offset: Index, _length: Index, _header: &Header| {
+            fragments += 1;
+        };
+
+        let outcome = term_reader::read(
+            term_buffer,
+            0,
+            &mut handler,
+            10,
+            &mut header,
+            &VersionMismatchCounter::default(),
+            error_handler,
+        );
+
+        assert_eq!(fragments, 0);
+        assert_eq!(outcome.fragments_read, 0);
+        assert_eq!(outcome.offset, expected_offset);
+    }
 }
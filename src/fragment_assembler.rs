@@ -15,14 +15,21 @@
  */
 
 use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
 
 use crate::{
-    buffer_builder::BufferBuilder,
+    buffer_builder::{BufferAllocator, BufferBuilder, SystemAllocator},
     concurrent::{
         atomic_buffer::AtomicBuffer,
         logbuffer::{data_frame_header, frame_descriptor, header::Header},
     },
-    utils::types::Index,
+    subscription::Subscription,
+    utils::{
+        errors::AeronError,
+        misc::monotonic_time_ms,
+        types::{Index, Moment},
+    },
 };
 
 const DEFAULT_FRAGMENT_ASSEMBLY_BUFFER_LENGTH: isize = 4096;
@@ -31,6 +38,24 @@ pub trait Fragment: FnMut(&AtomicBuffer, Index, Index, &Header) {}
 
 impl<T: FnMut(&AtomicBuffer, Index, Index, &Header)> Fragment for T {}
 
+/**
+ * Callback invoked when a partially assembled message is dropped because a new BEGIN_FRAG arrived for the
+ * session before the END_FRAG of the message in progress, which indicates loss or reordering.
+ *
+ * @param session_id     of the session the message was being assembled for.
+ * @param dropped_length number of payload bytes assembled so far which have been dropped.
+ */
+pub type OnDroppedPartial = fn(session_id: i32, dropped_length: Index);
+
+/**
+ * Callback invoked with the total length of a reassembled message just before it is passed to the delegate,
+ * e.g. so that the consumer can allocate space for it ahead of delegation.
+ *
+ * @param session_id of the session the message was assembled for.
+ * @param length     of the assembled message payload.
+ */
+pub type OnMessageReady = fn(session_id: i32, length: Index);
+
 /**
  * A handler that sits in a chain-of-responsibility pattern that reassembles fragmented messages
  * so that the next handler in the chain only sees whole messages.
@@ -42,12 +67,26 @@ impl<T: FnMut(&AtomicBuffer, Index, Index, &Header)> Fragment for T {}
  * <p>
  * Session based buffers will be allocated and grown as necessary based on the length of messages to be assembled.
  * When sessions go inactive see {@link on_unavailable_image_t}, it is possible to free the buffer by calling
- * {@link #deleteSessionBuffer(std::int32_t)}.
+ * {@link #deleteSessionBuffer(std::int32_t)}. A session which rejoins as a new Image, e.g. after a reconnection,
+ * has any message partially assembled from the old Image discarded.
  */
 pub struct FragmentAssembler<'a> {
     delegate: &'a mut dyn Fragment,
     builder_by_session_id_map: HashMap<i32, BufferBuilder>,
+    image_correlation_id_by_session_id_map: HashMap<i32, i64>,
     initial_buffer_length: isize,
+    on_dropped_partial: Option<OnDroppedPartial>,
+    dropped_partial_count: u64,
+    partial_timeout_ms: Option<Moment>,
+    time_of_last_update_ms_by_session_id_map: HashMap<i32, Moment>,
+    on_partial_timeout: Option<OnDroppedPartial>,
+    clock: Box<dyn Fn() -> Moment>,
+    allocator: Arc<dyn BufferAllocator>,
+    validate_order: bool,
+    next_term_offset_by_session_id_map: HashMap<i32, Index>,
+    on_out_of_order: Option<OnDroppedPartial>,
+    out_of_order_count: u64,
+    on_message_ready: Option<OnMessageReady>,
 }
 
 impl<'a> FragmentAssembler<'a> {
@@ -61,10 +100,123 @@ impl<'a> FragmentAssembler<'a> {
         Self {
             delegate,
             builder_by_session_id_map: HashMap::new(),
+            image_correlation_id_by_session_id_map: HashMap::new(),
             initial_buffer_length: initial_buffer_length.unwrap_or(DEFAULT_FRAGMENT_ASSEMBLY_BUFFER_LENGTH),
+            on_dropped_partial: None,
+            dropped_partial_count: 0,
+            partial_timeout_ms: None,
+            time_of_last_update_ms_by_session_id_map: HashMap::new(),
+            on_partial_timeout: None,
+            clock: Box::new(monotonic_time_ms),
+            allocator: Arc::new(SystemAllocator),
+            validate_order: false,
+            next_term_offset_by_session_id_map: HashMap::new(),
+            on_out_of_order: None,
+            out_of_order_count: 0,
+            on_message_ready: None,
         }
     }
 
+    /**
+     * Validate that the fragments of each message arrive at contiguous, increasing term offsets. A message with a
+     * fragment at an unexpected offset is dropped and reported rather than delivered, which catches reordering.
+     *
+     * @return the FragmentAssembler with order validation enabled.
+     */
+    pub fn with_order_validation(mut self) -> Self {
+        self.validate_order = true;
+        self
+    }
+
+    /**
+     * Set the callback to be notified when a message is dropped because a fragment arrived at an unexpected
+     * term offset. Only used when order validation is enabled.
+     *
+     * @param on_out_of_order callback to be notified.
+     */
+    pub fn set_on_out_of_order(&mut self, on_out_of_order: OnDroppedPartial) {
+        self.on_out_of_order = Some(on_out_of_order);
+    }
+
+    /**
+     * Set the callback to be notified with the length of each reassembled message before it is delegated.
+     *
+     * @param on_message_ready callback to be notified.
+     */
+    pub fn set_on_message_ready(&mut self, on_message_ready: OnMessageReady) {
+        self.on_message_ready = Some(on_message_ready);
+    }
+
+    /**
+     * Number of messages dropped because a fragment arrived at an unexpected term offset.
+     *
+     * @return number of messages dropped by order validation.
+     */
+    pub fn out_of_order_count(&self) -> u64 {
+        self.out_of_order_count
+    }
+
+    /**
+     * Take the memory for session buffers from the given allocator rather than the global allocator, e.g. to
+     * use pre-faulted or NUMA-local memory.
+     *
+     * @param allocator from which session buffers are allocated.
+     * @return the FragmentAssembler with the allocator applied.
+     */
+    pub fn with_allocator(mut self, allocator: Arc<dyn BufferAllocator>) -> Self {
+        self.allocator = allocator;
+        self
+    }
+
+    /**
+     * Evict partially assembled messages which have not received a fragment within the timeout, e.g. because
+     * their END_FRAG was lost. Stale partials are swept, and their session buffers freed, by evict_stale_partials
+     * which should be called periodically, e.g. from the duty cycle that polls.
+     *
+     * @param partial_timeout after which a partial message with no new fragments is evicted.
+     * @return the FragmentAssembler with the timeout applied.
+     */
+    pub fn with_partial_timeout(mut self, partial_timeout: Duration) -> Self {
+        self.partial_timeout_ms = Some(partial_timeout.as_millis() as Moment);
+        self
+    }
+
+    /**
+     * Set the callback to be notified when a partially assembled message is evicted after the partial timeout.
+     *
+     * @param on_partial_timeout callback to be notified.
+     */
+    pub fn set_on_partial_timeout(&mut self, on_partial_timeout: OnDroppedPartial) {
+        self.on_partial_timeout = Some(on_partial_timeout);
+    }
+
+    /**
+     * Set the monotonic clock, in milliseconds, used to age partial messages. Defaults to monotonic_time_ms.
+     *
+     * @param new_provider of the current time in milliseconds.
+     */
+    pub fn set_clock_provider(&mut self, new_provider: Box<dyn Fn() -> Moment>) {
+        self.clock = new_provider;
+    }
+
+    /**
+     * Set the callback to be notified when a partially assembled message is dropped on a duplicate BEGIN_FRAG.
+     *
+     * @param on_dropped_partial callback to be notified.
+     */
+    pub fn set_on_dropped_partial(&mut self, on_dropped_partial: OnDroppedPartial) {
+        self.on_dropped_partial = Some(on_dropped_partial);
+    }
+
+    /**
+     * Number of partially assembled messages dropped because a new BEGIN_FRAG arrived before their END_FRAG.
+     *
+     * @return number of dropped partial messages.
+     */
+    pub fn dropped_partial_count(&self) -> u64 {
+        self.dropped_partial_count
+    }
+
     /**
      * Compose a fragment_handler_t that calls the this FragmentAssembler instance for reassembly. Suitable for
      * passing to Subscription::poll(fragment_handler_t, int).
@@ -75,6 +227,27 @@ impl<'a> FragmentAssembler<'a> {
         move |buffer: &AtomicBuffer, offset, length, header: &Header| self.on_fragment(buffer, offset, length, header)
     }
 
+    /**
+     * Poll the subscription for fragments and reassemble them, delegating on whole messages. Unlike handler()
+     * this borrows the FragmentAssembler only for the duration of the poll, so it can be kept and polled repeatedly.
+     *
+     * @param subscription   to poll for message fragments.
+     * @param fragment_limit number of message fragments to limit for the poll.
+     * @return the number of fragments received or IllegalStateException if the subscription is closed.
+     */
+    pub fn poll(&mut self, subscription: &mut Subscription, fragment_limit: i32) -> Result<i32, AeronError> {
+        if subscription.is_closed() {
+            return Err(AeronError::IllegalStateException(String::from("Subscription is closed")));
+        }
+
+        Ok(subscription.poll(
+            &mut |buffer: &AtomicBuffer, offset: Index, length: Index, header: &Header| {
+                self.on_fragment(buffer, offset, length, header)
+            },
+            fragment_limit,
+        ))
+    }
+
     /**
      * Free an existing session buffer to reduce memory pressure when an Image goes inactive or no more
      * large messages are expected.
@@ -83,46 +256,180 @@ impl<'a> FragmentAssembler<'a> {
      */
     pub fn delete_session_buffer(&mut self, session_id: i32) {
         self.builder_by_session_id_map.remove(&session_id);
+        self.image_correlation_id_by_session_id_map.remove(&session_id);
+        self.time_of_last_update_ms_by_session_id_map.remove(&session_id);
+        self.next_term_offset_by_session_id_map.remove(&session_id);
+    }
+
+    /**
+     * Free all session buffers dropping any partially assembled messages, e.g. when the stream is restarted
+     * and the consumer wants to begin from a clean state.
+     */
+    pub fn clear(&mut self) {
+        self.builder_by_session_id_map.clear();
+        self.image_correlation_id_by_session_id_map.clear();
+        self.time_of_last_update_ms_by_session_id_map.clear();
+        self.next_term_offset_by_session_id_map.clear();
+    }
+
+    /**
+     * Evict partially assembled messages which have not been updated within the partial timeout and free their
+     * session buffers. Does nothing if no partial timeout has been set.
+     *
+     * @return number of partial messages evicted.
+     */
+    pub fn evict_stale_partials(&mut self) -> i32 {
+        match self.partial_timeout_ms {
+            Some(partial_timeout_ms) => {
+                let now_ms = (self.clock)();
+                self.evict_partials_older_than(now_ms, partial_timeout_ms)
+            }
+            None => 0,
+        }
     }
 
     #[inline]
     fn on_fragment(&mut self, buffer: &AtomicBuffer, offset: Index, length: Index, header: &Header) {
+        let session_id = header.session_id();
+        // Only fragments of partial messages read the clock, unfragmented messages are never aged.
+        let has_partial_timeout = self.partial_timeout_ms.is_some();
+
         let flags = header.flags();
         if (flags & frame_descriptor::UNFRAGMENTED) == frame_descriptor::UNFRAGMENTED {
+            // BEGIN_FRAG | END_FRAG is a complete message on its own. Any message still being assembled for the
+            // session can never complete, so it is dropped to keep a later END_FRAG from delivering it.
+            self.drop_partial(session_id);
+
             (self.delegate)(buffer, offset, length, header);
         } else if (flags & frame_descriptor::BEGIN_FRAG) == frame_descriptor::BEGIN_FRAG {
+            self.drop_partial(session_id);
+
             // Here we need following logic: if BufferBuilder for given session_id do exist in the map - use it.
             // If there is no such BufferBuilder then create on, insert in to map and use it.
             let initial_buffer_length = self.initial_buffer_length;
+            let allocator = &self.allocator;
             let builder = self
                 .builder_by_session_id_map
-                .entry(header.session_id())
-                .or_insert_with(|| BufferBuilder::new(initial_buffer_length));
+                .entry(session_id)
+                .or_insert_with(|| BufferBuilder::with_allocator(initial_buffer_length, allocator.clone()));
 
             builder.reset().append(buffer, offset, length, header).expect("append failed");
-        } else if let Some(builder) = self.builder_by_session_id_map.get_mut(&header.session_id()) {
+            self.image_correlation_id_by_session_id_map
+                .insert(session_id, header.image_correlation_id());
+
+            if self.validate_order {
+                self.next_term_offset_by_session_id_map
+                    .insert(session_id, Self::next_term_offset(header));
+            }
+
+            if has_partial_timeout {
+                self.time_of_last_update_ms_by_session_id_map
+                    .insert(session_id, (self.clock)());
+            }
+        } else if let Some(builder) = self.builder_by_session_id_map.get_mut(&session_id) {
             if builder.limit() != data_frame_header::LENGTH {
+                if self.image_correlation_id_by_session_id_map.get(&session_id) != Some(&header.image_correlation_id()) {
+                    // The session rejoined as a new Image, e.g. after a reconnection, so partial data from the old
+                    // Image is stale.
+                    builder.reset();
+                    return;
+                }
+
+                if self.validate_order {
+                    let expected_term_offset = self.next_term_offset_by_session_id_map.get(&session_id).copied();
+                    if expected_term_offset != Some(header.term_offset()) {
+                        let dropped_length = builder.limit() - data_frame_header::LENGTH;
+                        builder.reset();
+                        self.next_term_offset_by_session_id_map.remove(&session_id);
+
+                        self.out_of_order_count += 1;
+                        if let Some(on_out_of_order) = self.on_out_of_order {
+                            on_out_of_order(session_id, dropped_length);
+                        }
+                        return;
+                    }
+
+                    self.next_term_offset_by_session_id_map
+                        .insert(session_id, Self::next_term_offset(header));
+                }
+
                 builder.append(buffer, offset, length, header).expect("append failed");
 
                 if flags & frame_descriptor::END_FRAG == frame_descriptor::END_FRAG {
                     let msg_length = builder.limit() - data_frame_header::LENGTH;
                     let msg_buffer = AtomicBuffer::new(builder.buffer(), builder.limit());
 
+                    if let Some(on_message_ready) = self.on_message_ready {
+                        on_message_ready(session_id, msg_length);
+                    }
+
                     (*self.delegate)(&msg_buffer, data_frame_header::LENGTH, msg_length, header);
 
                     builder.reset();
+                    if has_partial_timeout {
+                        self.time_of_last_update_ms_by_session_id_map.remove(&session_id);
+                    }
+                } else if has_partial_timeout {
+                    self.time_of_last_update_ms_by_session_id_map
+                        .insert(session_id, (self.clock)());
                 }
             }
         }
     }
-}
 
-#[cfg(test)]
-mod test {
-    use std::sync::atomic::{AtomicBool, Ordering};
+    fn next_term_offset(header: &Header) -> Index {
+        header.term_offset() + frame_descriptor::aligned_frame_length(header.frame_length())
+    }
 
-    use lazy_static::lazy_static;
+    fn evict_partials_older_than(&mut self, now_ms: Moment, partial_timeout_ms: Moment) -> i32 {
+        let stale_session_ids: Vec<i32> = self
+            .time_of_last_update_ms_by_session_id_map
+            .iter()
+            .filter(|(_, time_of_last_update_ms)| now_ms > *time_of_last_update_ms + partial_timeout_ms)
+            .map(|(session_id, _)| *session_id)
+            .collect();
+
+        let mut evicted = 0;
+        for session_id in stale_session_ids {
+            let evicted_length = match self.builder_by_session_id_map.get(&session_id) {
+                Some(builder) => builder.limit() - data_frame_header::LENGTH,
+                None => 0,
+            };
+
+            // The session buffer is freed rather than reset so a session which stopped sending does not keep it.
+            self.delete_session_buffer(session_id);
+
+            if evicted_length > 0 {
+                evicted += 1;
+
+                if let Some(on_partial_timeout) = self.on_partial_timeout {
+                    on_partial_timeout(session_id, evicted_length);
+                }
+            }
+        }
+
+        evicted
+    }
 
+    fn drop_partial(&mut self, session_id: i32) {
+        if let Some(builder) = self.builder_by_session_id_map.get_mut(&session_id) {
+            if builder.limit() != data_frame_header::LENGTH {
+                let dropped_length = builder.limit() - data_frame_header::LENGTH;
+                builder.reset();
+
+                self.dropped_partial_count += 1;
+                if let Some(on_dropped_partial) = self.on_dropped_partial {
+                    on_dropped_partial(session_id, dropped_length);
+                }
+            }
+        }
+    }
+}
+
+/// Term buffer and header shared by the fragment assembler tests, with helpers to write data frames into the term
+/// and feed them to an assembler.
+#[cfg(test)]
+pub(crate) mod fragment_assembler_test {
     use crate::concurrent::{
         atomic_buffer::{AlignedBuffer, AtomicBuffer},
         logbuffer::{
@@ -132,27 +439,21 @@ mod test {
             log_buffer_descriptor,
         },
     };
-    use crate::fragment_assembler::FragmentAssembler;
-    use crate::utils::{bit_utils, types::Index};
-
-    // const CHANNEL: &str = "aeron:udp?endpoint=localhost:40123";
-    const STREAM_ID: i32 = 10;
-    const SESSION_ID: i32 = 200;
-    const TERM_LENGTH: i32 = log_buffer_descriptor::TERM_MIN_LENGTH;
-    const INITIAL_TERM_ID: i32 = -1234;
-    const ACTIVE_TERM_ID: i32 = INITIAL_TERM_ID + 5;
-    const MTU_LENGTH: Index = 128;
+    use crate::utils::types::Index;
 
-    lazy_static! {
-        pub static ref POSITION_BITS_TO_SHIFT: i32 = bit_utils::number_of_trailing_zeroes(TERM_LENGTH);
-        pub static ref CALLED: AtomicBool = AtomicBool::new(false);
-    }
+    pub(crate) const STREAM_ID: i32 = 10;
+    pub(crate) const SESSION_ID: i32 = 200;
+    pub(crate) const TERM_LENGTH: i32 = log_buffer_descriptor::TERM_MIN_LENGTH;
+    pub(crate) const INITIAL_TERM_ID: i32 = -1234;
+    pub(crate) const ACTIVE_TERM_ID: i32 = INITIAL_TERM_ID + 5;
+    pub(crate) const MTU_LENGTH: Index = 128;
+    pub(crate) const MSG_LENGTH: Index = MTU_LENGTH - data_frame_header::LENGTH;
 
     #[allow(dead_code)]
-    struct FragmentAssemblerTest {
+    pub(crate) struct FragmentAssemblerTest {
         fragment: AlignedBuffer,
-        buffer: AtomicBuffer,
-        header: Header,
+        pub buffer: AtomicBuffer,
+        pub header: Header,
     }
 
     impl FragmentAssemblerTest {
@@ -168,7 +469,11 @@ mod test {
             }
         }
 
-        fn fill_frame(&self, flags: u8, offset: i32, length: i32, payload_value: u8) {
+        pub fn fill_frame(&self, flags: u8, offset: i32, length: i32, payload_value: u8) {
+            self.fill_frame_in_term(flags, offset, length, ACTIVE_TERM_ID, payload_value);
+        }
+
+        pub fn fill_frame_in_term(&self, flags: u8, offset: i32, length: i32, term_id: i32, payload_value: u8) {
             let frame = self.buffer.overlay_struct::<DataFrameHeaderDefn>(offset);
             unsafe {
                 (*frame).frame_length = data_frame_header::LENGTH + length;
@@ -178,7 +483,7 @@ mod test {
                 (*frame).term_offset = offset;
                 (*frame).session_id = SESSION_ID;
                 (*frame).stream_id = STREAM_ID;
-                (*frame).term_id = ACTIVE_TERM_ID;
+                (*frame).term_id = term_id;
             }
 
             for i in 0..length {
@@ -186,9 +491,32 @@ mod test {
             }
         }
 
+        /// Write an MTU sized frame at frame_index within the term, filled with frame_index + 1, and feed it to
+        /// on_fragment.
+        pub fn feed_fragment(
+            &mut self,
+            on_fragment: &mut impl FnMut(&AtomicBuffer, Index, Index, &Header),
+            flags: u8,
+            frame_index: Index,
+            term_id: i32,
+        ) {
+            let offset = frame_index * MTU_LENGTH;
+            self.fill_frame_in_term(flags, offset, MSG_LENGTH, term_id, frame_index as u8 + 1);
+            self.header.set_offset(offset);
+            on_fragment(&self.buffer, offset + data_frame_header::LENGTH, MSG_LENGTH, &self.header);
+        }
+
+        /// Feed a message of BEGIN_FRAG, middle and END_FRAG frames from the start of the active term.
+        pub fn feed_three_fragment_message(&mut self, on_fragment: &mut impl FnMut(&AtomicBuffer, Index, Index, &Header)) {
+            let flags = [frame_descriptor::BEGIN_FRAG, 0, frame_descriptor::END_FRAG];
+            for (i, flags) in flags.iter().enumerate() {
+                self.feed_fragment(on_fragment, *flags, i as Index, ACTIVE_TERM_ID);
+            }
+        }
+
         // Fragment_len must contain length on i-th fragment.
         // Each byte of each fragment was previously filled with the fragments seq number.
-        fn verify_payload(buffer: &AtomicBuffer, offset: Index, fragment_len: &[Index]) {
+        pub fn verify_payload(buffer: &AtomicBuffer, offset: Index, fragment_len: &[Index]) {
             unsafe {
                 let ptr = buffer.buffer().offset(offset as isize);
 
@@ -202,6 +530,33 @@ mod test {
             }
         }
     }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::atomic::{AtomicBool, AtomicI32, AtomicU64, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use lazy_static::lazy_static;
+
+    use crate::buffer_builder::counting_allocator::CountingAllocator;
+    use crate::concurrent::{
+        atomic_buffer::AtomicBuffer,
+        logbuffer::{data_frame_header, frame_descriptor, header::Header, log_buffer_descriptor},
+    };
+    use crate::fragment_assembler::{
+        fragment_assembler_test::{
+            FragmentAssemblerTest, ACTIVE_TERM_ID, INITIAL_TERM_ID, MTU_LENGTH, SESSION_ID, STREAM_ID, TERM_LENGTH,
+        },
+        FragmentAssembler,
+    };
+    use crate::utils::{bit_utils, types::Index};
+
+    lazy_static! {
+        pub static ref POSITION_BITS_TO_SHIFT: i32 = bit_utils::number_of_trailing_zeroes(TERM_LENGTH);
+        pub static ref CALLED: AtomicBool = AtomicBool::new(false);
+    }
 
     #[test]
     fn should_pass_through_unfragmented_message() {
@@ -242,6 +597,26 @@ mod test {
         assert!(CALLED.load(Ordering::Relaxed));
     }
 
+    #[test]
+    fn should_pass_through_zero_length_unfragmented_message() {
+        let test = FragmentAssemblerTest::new();
+        test.fill_frame(frame_descriptor::UNFRAGMENTED, 0, 0, 1);
+        static CALLED: AtomicBool = AtomicBool::new(false);
+
+        let mut fragment = move |_buffer: &AtomicBuffer, offset: Index, length: Index, header: &Header| {
+            CALLED.store(true, Ordering::Relaxed);
+            assert_eq!(offset, data_frame_header::LENGTH);
+            assert_eq!(length, 0);
+            assert_eq!(header.frame_length(), data_frame_header::LENGTH);
+            assert_eq!(header.flags(), frame_descriptor::UNFRAGMENTED);
+        };
+
+        let mut adapter = FragmentAssembler::new(&mut fragment, None);
+
+        adapter.handler()(&test.buffer, data_frame_header::LENGTH, 0, &test.header);
+        assert!(CALLED.load(Ordering::Relaxed));
+    }
+
     #[test]
     fn should_reassemble_from_two_fragments() {
         let mut test = FragmentAssemblerTest::new();
@@ -382,4 +757,389 @@ mod test {
         handler(&test.buffer, data_frame_header::LENGTH, msg_length, &test.header);
         assert!(!CALLED.load(Ordering::Relaxed));
     }
+
+    #[test]
+    fn should_drop_all_partial_sessions_on_clear() {
+        let mut test = FragmentAssemblerTest::new();
+        let msg_length = MTU_LENGTH - data_frame_header::LENGTH;
+        static CALLS: AtomicI32 = AtomicI32::new(0);
+        static LAST_LENGTH: AtomicI32 = AtomicI32::new(0);
+
+        let mut fragment = move |_buffer: &AtomicBuffer, _offset: Index, length: Index, _header: &Header| {
+            CALLS.fetch_add(1, Ordering::Relaxed);
+            LAST_LENGTH.store(length, Ordering::Relaxed);
+        };
+
+        let mut adapter = FragmentAssembler::new(&mut fragment, None);
+
+        for session_id in 1..=3 {
+            let offset = (session_id - 1) * MTU_LENGTH;
+            test.fill_frame(frame_descriptor::BEGIN_FRAG, offset, msg_length, 1);
+            test.buffer
+                .put::<i32>(offset + *data_frame_header::SESSION_ID_FIELD_OFFSET, session_id);
+            test.header.set_offset(offset);
+            adapter.on_fragment(&test.buffer, offset + data_frame_header::LENGTH, msg_length, &test.header);
+        }
+
+        assert_eq!(adapter.builder_by_session_id_map.len(), 3);
+
+        adapter.clear();
+        assert!(adapter.builder_by_session_id_map.is_empty());
+
+        // the tail of a message which began before clear() must not be delivered
+        let offset = 3 * MTU_LENGTH;
+        test.fill_frame(frame_descriptor::END_FRAG, offset, msg_length, 2);
+        test.buffer
+            .put::<i32>(offset + *data_frame_header::SESSION_ID_FIELD_OFFSET, 1);
+        test.header.set_offset(offset);
+        adapter.on_fragment(&test.buffer, offset + data_frame_header::LENGTH, msg_length, &test.header);
+        assert_eq!(CALLS.load(Ordering::Relaxed), 0);
+
+        // a new message for the same session is assembled from scratch
+        for (i, flags) in [frame_descriptor::BEGIN_FRAG, frame_descriptor::END_FRAG].iter().enumerate() {
+            let offset = (4 + i as Index) * MTU_LENGTH;
+            test.fill_frame(*flags, offset, msg_length, i as u8 + 1);
+            test.buffer
+                .put::<i32>(offset + *data_frame_header::SESSION_ID_FIELD_OFFSET, 1);
+            test.header.set_offset(offset);
+            adapter.on_fragment(&test.buffer, offset + data_frame_header::LENGTH, msg_length, &test.header);
+        }
+
+        assert_eq!(CALLS.load(Ordering::Relaxed), 1);
+        assert_eq!(LAST_LENGTH.load(Ordering::Relaxed), msg_length * 2);
+    }
+
+    #[test]
+    fn should_deliver_begin_and_end_fragment_once_without_builder() {
+        let test = FragmentAssemblerTest::new();
+        let msg_length = 64;
+        test.fill_frame(frame_descriptor::BEGIN_FRAG | frame_descriptor::END_FRAG, 0, msg_length, 1);
+        static CALLS: AtomicI32 = AtomicI32::new(0);
+
+        let mut fragment = move |buffer: &AtomicBuffer, offset: Index, length: Index, _header: &Header| {
+            CALLS.fetch_add(1, Ordering::Relaxed);
+            assert_eq!(offset, data_frame_header::LENGTH);
+            assert_eq!(length, msg_length);
+            FragmentAssemblerTest::verify_payload(buffer, offset, &[msg_length]);
+        };
+
+        let mut adapter = FragmentAssembler::new(&mut fragment, None);
+
+        adapter.on_fragment(&test.buffer, data_frame_header::LENGTH, msg_length, &test.header);
+
+        assert_eq!(CALLS.load(Ordering::Relaxed), 1);
+        assert!(adapter.builder_by_session_id_map.is_empty());
+    }
+
+    #[test]
+    fn should_discard_partial_message_when_begin_and_end_fragment_arrives() {
+        let mut test = FragmentAssemblerTest::new();
+        let msg_length = MTU_LENGTH - data_frame_header::LENGTH;
+        static CALLS: AtomicI32 = AtomicI32::new(0);
+
+        let mut fragment = move |_buffer: &AtomicBuffer, _offset: Index, length: Index, _header: &Header| {
+            CALLS.fetch_add(1, Ordering::Relaxed);
+            assert_eq!(length, msg_length);
+        };
+
+        let mut adapter = FragmentAssembler::new(&mut fragment, None);
+
+        let flags = [
+            frame_descriptor::BEGIN_FRAG,
+            frame_descriptor::BEGIN_FRAG | frame_descriptor::END_FRAG,
+            frame_descriptor::END_FRAG,
+        ];
+        for (i, flags) in flags.iter().enumerate() {
+            let offset = i as Index * MTU_LENGTH;
+            test.fill_frame(*flags, offset, msg_length, i as u8 + 1);
+            test.header.set_offset(offset);
+            adapter.on_fragment(&test.buffer, offset + data_frame_header::LENGTH, msg_length, &test.header);
+        }
+
+        assert_eq!(CALLS.load(Ordering::Relaxed), 1);
+        assert_eq!(
+            adapter.builder_by_session_id_map.get(&SESSION_ID).unwrap().limit(),
+            data_frame_header::LENGTH
+        );
+    }
+
+    #[test]
+    fn should_discard_partial_message_when_session_rejoins_as_new_image() {
+        let mut test = FragmentAssemblerTest::new();
+        let msg_length = MTU_LENGTH - data_frame_header::LENGTH;
+        static CALLS: AtomicI32 = AtomicI32::new(0);
+        static LAST_LENGTH: AtomicI32 = AtomicI32::new(0);
+
+        let mut fragment = move |_buffer: &AtomicBuffer, _offset: Index, length: Index, _header: &Header| {
+            CALLS.fetch_add(1, Ordering::Relaxed);
+            LAST_LENGTH.store(length, Ordering::Relaxed);
+        };
+
+        let mut adapter = FragmentAssembler::new(&mut fragment, None);
+
+        let fragments = [
+            (1, frame_descriptor::BEGIN_FRAG),
+            (2, frame_descriptor::END_FRAG),
+            (2, frame_descriptor::BEGIN_FRAG),
+            (2, frame_descriptor::END_FRAG),
+        ];
+        for (i, (image_correlation_id, flags)) in fragments.iter().enumerate() {
+            let offset = i as Index * MTU_LENGTH;
+            test.fill_frame(*flags, offset, msg_length, i as u8 + 1);
+            test.header.set_offset(offset);
+            test.header.set_image_correlation_id(*image_correlation_id);
+            adapter.on_fragment(&test.buffer, offset + data_frame_header::LENGTH, msg_length, &test.header);
+
+            // the END_FRAG of the rejoined image must not complete the message begun on the old image
+            if i == 1 {
+                assert_eq!(CALLS.load(Ordering::Relaxed), 0);
+            }
+        }
+
+        assert_eq!(CALLS.load(Ordering::Relaxed), 1);
+        assert_eq!(LAST_LENGTH.load(Ordering::Relaxed), msg_length * 2);
+    }
+
+    #[test]
+    fn should_report_dropped_partial_on_duplicate_begin() {
+        let mut test = FragmentAssemblerTest::new();
+        let msg_length = MTU_LENGTH - data_frame_header::LENGTH;
+        static DROPPED_CALLS: AtomicI32 = AtomicI32::new(0);
+        static DROPPED_LENGTH: AtomicI32 = AtomicI32::new(0);
+        static CALLS: AtomicI32 = AtomicI32::new(0);
+        static LAST_LENGTH: AtomicI32 = AtomicI32::new(0);
+
+        fn on_dropped_partial(session_id: i32, dropped_length: Index) {
+            assert_eq!(session_id, SESSION_ID);
+            DROPPED_CALLS.fetch_add(1, Ordering::Relaxed);
+            DROPPED_LENGTH.store(dropped_length, Ordering::Relaxed);
+        }
+
+        let mut fragment = move |buffer: &AtomicBuffer, offset: Index, length: Index, _header: &Header| {
+            CALLS.fetch_add(1, Ordering::Relaxed);
+            LAST_LENGTH.store(length, Ordering::Relaxed);
+            // payload bytes of the first two fragments carry 1 and 2, the restarted message carries 3 and 4
+            assert_eq!(buffer.get::<u8>(offset), 3);
+            assert_eq!(buffer.get::<u8>(offset + length - 1), 4);
+        };
+
+        let mut adapter = FragmentAssembler::new(&mut fragment, None);
+        adapter.set_on_dropped_partial(on_dropped_partial);
+
+        let flags = [
+            frame_descriptor::BEGIN_FRAG,
+            0,
+            frame_descriptor::BEGIN_FRAG,
+            frame_descriptor::END_FRAG,
+        ];
+        for (i, flags) in flags.iter().enumerate() {
+            let offset = i as Index * MTU_LENGTH;
+            test.fill_frame(*flags, offset, msg_length, i as u8 + 1);
+            test.header.set_offset(offset);
+            adapter.on_fragment(&test.buffer, offset + data_frame_header::LENGTH, msg_length, &test.header);
+        }
+
+        assert_eq!(DROPPED_CALLS.load(Ordering::Relaxed), 1);
+        assert_eq!(DROPPED_LENGTH.load(Ordering::Relaxed), msg_length * 2);
+        assert_eq!(adapter.dropped_partial_count(), 1);
+        assert_eq!(CALLS.load(Ordering::Relaxed), 1);
+        assert_eq!(LAST_LENGTH.load(Ordering::Relaxed), msg_length * 2);
+    }
+
+    #[test]
+    fn should_evict_partial_after_timeout() {
+        let mut test = FragmentAssemblerTest::new();
+        let msg_length = MTU_LENGTH - data_frame_header::LENGTH;
+        let clock = Arc::new(AtomicU64::new(1000));
+        static EVICTED_CALLS: AtomicI32 = AtomicI32::new(0);
+        static EVICTED_LENGTH: AtomicI32 = AtomicI32::new(0);
+        static CALLS: AtomicI32 = AtomicI32::new(0);
+
+        fn on_partial_timeout(session_id: i32, evicted_length: Index) {
+            assert_eq!(session_id, SESSION_ID);
+            EVICTED_CALLS.fetch_add(1, Ordering::Relaxed);
+            EVICTED_LENGTH.store(evicted_length, Ordering::Relaxed);
+        }
+
+        let mut fragment = move |_buffer: &AtomicBuffer, _offset: Index, _length: Index, _header: &Header| {
+            CALLS.fetch_add(1, Ordering::Relaxed);
+        };
+
+        let mut adapter = FragmentAssembler::new(&mut fragment, None).with_partial_timeout(Duration::from_millis(100));
+        adapter.set_on_partial_timeout(on_partial_timeout);
+        let clock_provider = clock.clone();
+        adapter.set_clock_provider(Box::new(move || clock_provider.load(Ordering::SeqCst)));
+
+        test.fill_frame(frame_descriptor::BEGIN_FRAG, 0, msg_length, 1);
+        test.header.set_offset(0);
+        adapter.on_fragment(&test.buffer, data_frame_header::LENGTH, msg_length, &test.header);
+
+        clock.store(1100, Ordering::SeqCst);
+        assert_eq!(adapter.evict_stale_partials(), 0);
+        assert_eq!(EVICTED_CALLS.load(Ordering::Relaxed), 0);
+
+        clock.store(1101, Ordering::SeqCst);
+        assert_eq!(adapter.evict_stale_partials(), 1);
+        assert_eq!(EVICTED_CALLS.load(Ordering::Relaxed), 1);
+        assert_eq!(EVICTED_LENGTH.load(Ordering::Relaxed), msg_length);
+        assert!(!adapter.builder_by_session_id_map.contains_key(&SESSION_ID));
+
+        // END_FRAG of the evicted message must not be delivered
+        test.fill_frame(frame_descriptor::END_FRAG, MTU_LENGTH, msg_length, 2);
+        test.header.set_offset(MTU_LENGTH);
+        adapter.on_fragment(&test.buffer, MTU_LENGTH + data_frame_header::LENGTH, msg_length, &test.header);
+
+        assert_eq!(CALLS.load(Ordering::Relaxed), 0);
+        assert_eq!(adapter.evict_stale_partials(), 0);
+    }
+
+    #[test]
+    fn should_keep_partial_updated_within_timeout() {
+        let mut test = FragmentAssemblerTest::new();
+        let msg_length = MTU_LENGTH - data_frame_header::LENGTH;
+        let clock = Arc::new(AtomicU64::new(1000));
+        static CALLS: AtomicI32 = AtomicI32::new(0);
+        static LAST_LENGTH: AtomicI32 = AtomicI32::new(0);
+
+        let mut fragment = move |_buffer: &AtomicBuffer, _offset: Index, length: Index, _header: &Header| {
+            CALLS.fetch_add(1, Ordering::Relaxed);
+            LAST_LENGTH.store(length, Ordering::Relaxed);
+        };
+
+        let mut adapter = FragmentAssembler::new(&mut fragment, None).with_partial_timeout(Duration::from_millis(100));
+        let clock_provider = clock.clone();
+        adapter.set_clock_provider(Box::new(move || clock_provider.load(Ordering::SeqCst)));
+
+        let flags = [frame_descriptor::BEGIN_FRAG, 0, frame_descriptor::END_FRAG];
+        for (i, flags) in flags.iter().enumerate() {
+            clock.store(1000 + 80 * i as u64, Ordering::SeqCst);
+            test.feed_fragment(
+                &mut |buffer, offset, length, header| adapter.on_fragment(buffer, offset, length, header),
+                *flags,
+                i as Index,
+                ACTIVE_TERM_ID,
+            );
+        }
+
+        assert_eq!(CALLS.load(Ordering::Relaxed), 1);
+        assert_eq!(LAST_LENGTH.load(Ordering::Relaxed), msg_length * 3);
+    }
+
+    #[test]
+    fn should_deliver_contiguous_fragments_with_order_validation() {
+        let mut test = FragmentAssemblerTest::new();
+        let msg_length = MTU_LENGTH - data_frame_header::LENGTH;
+        static CALLS: AtomicI32 = AtomicI32::new(0);
+        static LAST_LENGTH: AtomicI32 = AtomicI32::new(0);
+
+        let mut fragment = move |_buffer: &AtomicBuffer, _offset: Index, length: Index, _header: &Header| {
+            CALLS.fetch_add(1, Ordering::Relaxed);
+            LAST_LENGTH.store(length, Ordering::Relaxed);
+        };
+
+        let mut adapter = FragmentAssembler::new(&mut fragment, None).with_order_validation();
+
+        test.feed_three_fragment_message(&mut |buffer, offset, length, header| {
+            adapter.on_fragment(buffer, offset, length, header)
+        });
+
+        assert_eq!(adapter.out_of_order_count(), 0);
+        assert_eq!(CALLS.load(Ordering::Relaxed), 1);
+        assert_eq!(LAST_LENGTH.load(Ordering::Relaxed), msg_length * 3);
+    }
+
+    #[test]
+    fn should_drop_and_report_message_with_gap_in_term_offsets() {
+        let mut test = FragmentAssemblerTest::new();
+        let msg_length = MTU_LENGTH - data_frame_header::LENGTH;
+        static CALLS: AtomicI32 = AtomicI32::new(0);
+        static DROPPED_LENGTH: AtomicI32 = AtomicI32::new(0);
+
+        let mut fragment = move |_buffer: &AtomicBuffer, _offset: Index, _length: Index, _header: &Header| {
+            CALLS.fetch_add(1, Ordering::Relaxed);
+        };
+
+        let mut adapter = FragmentAssembler::new(&mut fragment, None).with_order_validation();
+        adapter.set_on_out_of_order(|session_id, dropped_length| {
+            assert_eq!(session_id, SESSION_ID);
+            DROPPED_LENGTH.store(dropped_length, Ordering::Relaxed);
+        });
+
+        // The middle fragment is missing so the END_FRAG arrives one frame further on than expected.
+        let frames = [(frame_descriptor::BEGIN_FRAG, 0), (frame_descriptor::END_FRAG, 2)];
+        for (flags, frame_index) in frames.iter() {
+            test.feed_fragment(
+                &mut |buffer, offset, length, header| adapter.on_fragment(buffer, offset, length, header),
+                *flags,
+                *frame_index,
+                ACTIVE_TERM_ID,
+            );
+        }
+
+        assert_eq!(adapter.out_of_order_count(), 1);
+        assert_eq!(DROPPED_LENGTH.load(Ordering::Relaxed), msg_length);
+        assert_eq!(CALLS.load(Ordering::Relaxed), 0);
+        assert_eq!(
+            adapter.builder_by_session_id_map.get(&SESSION_ID).unwrap().limit(),
+            data_frame_header::LENGTH
+        );
+    }
+
+    #[test]
+    fn should_report_assembled_length_before_delegating() {
+        let mut test = FragmentAssemblerTest::new();
+        let msg_length = MTU_LENGTH - data_frame_header::LENGTH;
+        static CALLS: AtomicI32 = AtomicI32::new(0);
+        static READY_LENGTH: AtomicI32 = AtomicI32::new(0);
+        static CALLS_WHEN_READY: AtomicI32 = AtomicI32::new(-1);
+
+        let mut fragment = move |_buffer: &AtomicBuffer, _offset: Index, length: Index, _header: &Header| {
+            assert_eq!(READY_LENGTH.load(Ordering::Relaxed), length);
+            CALLS.fetch_add(1, Ordering::Relaxed);
+        };
+
+        let mut adapter = FragmentAssembler::new(&mut fragment, None);
+        adapter.set_on_message_ready(|session_id, length| {
+            assert_eq!(session_id, SESSION_ID);
+            READY_LENGTH.store(length, Ordering::Relaxed);
+            CALLS_WHEN_READY.store(CALLS.load(Ordering::Relaxed), Ordering::Relaxed);
+        });
+
+        test.feed_three_fragment_message(&mut |buffer, offset, length, header| {
+            adapter.on_fragment(buffer, offset, length, header)
+        });
+
+        assert_eq!(READY_LENGTH.load(Ordering::Relaxed), msg_length * 3);
+        assert_eq!(CALLS_WHEN_READY.load(Ordering::Relaxed), 0);
+        assert_eq!(CALLS.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn should_allocate_session_buffers_from_configured_allocator() {
+        let mut test = FragmentAssemblerTest::new();
+        let allocator = Arc::new(CountingAllocator::default());
+
+        let mut fragment = |_buffer: &AtomicBuffer, _offset: Index, _length: Index, _header: &Header| {};
+
+        {
+            let mut adapter =
+                FragmentAssembler::new(&mut fragment, Some(2 * MTU_LENGTH as isize)).with_allocator(allocator.clone());
+
+            // Two messages of three fragments each: the session buffer is allocated once and grown once,
+            // then reused after being reset for the second message.
+            for _message in 0..2 {
+                test.feed_three_fragment_message(&mut |buffer, offset, length, header| {
+                    adapter.on_fragment(buffer, offset, length, header)
+                });
+            }
+
+            assert_eq!(allocator.allocations.load(Ordering::SeqCst), 2);
+            assert_eq!(allocator.deallocations.load(Ordering::SeqCst), 1);
+
+            adapter.delete_session_buffer(SESSION_ID);
+            assert_eq!(allocator.deallocations.load(Ordering::SeqCst), 2);
+        }
+
+        assert_eq!(allocator.allocations.load(Ordering::SeqCst), 2);
+    }
 }
@@ -18,30 +18,27 @@ use std::{
     cmp::min,
     ffi::CString,
     sync::{
-        atomic::{AtomicBool, Ordering},
-        Arc,
+        atomic::{AtomicBool, AtomicI64, Ordering},
+        Arc, Mutex, Weak,
     },
 };
 
 use crate::concurrent::{
     atomic_buffer::AtomicBuffer,
+    counters::CountersReader,
     logbuffer::{
-        data_frame_header, frame_descriptor,
-        header::Header,
+        data_frame_header,
+        frame_descriptor::{self, VersionMismatchCounter},
+        header::{Header, HeaderSnapshot},
         log_buffer_descriptor,
         term_reader::{self, ErrorHandler, ReadOutcome},
         term_scan::{scan, BlockHandler},
     },
     position::{ReadablePosition, UnsafeBufferPosition},
 };
-use crate::utils::{
-    bit_utils::{align, number_of_trailing_zeroes},
-    errors::AeronError,
-    log_buffers::LogBuffers,
-    types::Index,
-};
+use crate::utils::{bit_utils::number_of_trailing_zeroes, errors::AeronError, log_buffers::LogBuffers, types::Index};
 
-#[derive(Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum ControlledPollAction {
     /**
      * Abort the current polling operation and do not advance the position for this fragment.
@@ -75,14 +72,88 @@ pub enum ControlledPollAction {
  * @param header representing the meta data for the data.
  * @return The action to be taken with regard to the stream position after the callback.
  */
+pub trait ControlledFragment: FnMut(&AtomicBuffer, Index, Index, &Header) -> Result<ControlledPollAction, AeronError> {}
+
+impl<T: FnMut(&AtomicBuffer, Index, Index, &Header) -> Result<ControlledPollAction, AeronError>> ControlledFragment for T {}
+
+/**
+ * Adapt a plain predicate to a controlled fragment handler. The predicate returning true maps to
+ * ControlledPollAction::CONTINUE and false maps to ControlledPollAction::BREAK.
+ *
+ * @param predicate called for each fragment, returns true to keep on polling.
+ * @return controlled fragment handler wrapping the predicate.
+ */
+pub fn continue_if(mut predicate: impl FnMut(&AtomicBuffer, Index, Index, &Header) -> bool) -> impl ControlledFragment {
+    move |buffer: &AtomicBuffer, offset: Index, length: Index, header: &Header| {
+        if predicate(buffer, offset, length, header) {
+            Ok(ControlledPollAction::CONTINUE)
+        } else {
+            Ok(ControlledPollAction::BREAK)
+        }
+    }
+}
+
+// Value of a member's claim in flight when it is not reading a claimed message.
+const NO_CLAIM_IN_FLIGHT: i64 = i64::MAX;
+
+/**
+ * Consumption position of a shared position group. Members claim whole messages by advancing the claimed position,
+ * recording where their claim in flight begins before doing so. The completed position, below which every claimed
+ * message has been read, is what members publish to their subscriber position counters so the media driver never
+ * treats a message as consumed while a member is still reading it.
+ */
+pub(crate) struct SharedPosition {
+    claimed: AtomicI64,
+    claims_in_flight: Mutex<Vec<Weak<AtomicI64>>>,
+}
+
+impl SharedPosition {
+    pub(crate) fn new(position: i64) -> Self {
+        Self {
+            claimed: AtomicI64::new(position),
+            claims_in_flight: Mutex::new(Vec::new()),
+        }
+    }
+
+    // Register a member, returning the slot in which it records the start of its claim in flight.
+    fn join(&self) -> Arc<AtomicI64> {
+        let claim_in_flight = Arc::new(AtomicI64::new(NO_CLAIM_IN_FLIGHT));
+        let mut claims_in_flight = self.claims_in_flight.lock().expect("Mutex poisoned");
+        claims_in_flight.retain(|claim| claim.strong_count() > 0);
+        claims_in_flight.push(Arc::downgrade(&claim_in_flight));
+
+        claim_in_flight
+    }
+
+    // The claimed position is read before the claims in flight. A member records its claim before advancing the
+    // claimed position, so a claim missed here can only begin at or beyond the claimed position already read.
+    fn completed(&self) -> i64 {
+        let claimed = self.claimed.load(Ordering::SeqCst);
+
+        self.claims_in_flight
+            .lock()
+            .expect("Mutex poisoned")
+            .iter()
+            .filter_map(Weak::upgrade)
+            .fold(claimed, |position, claim| min(position, claim.load(Ordering::SeqCst)))
+    }
+}
+
+#[derive(Clone)]
+struct SharedPositionMember {
+    group: Arc<SharedPosition>,
+    claim_in_flight: Arc<AtomicI64>,
+}
 
 #[derive(Clone)]
 pub struct Image {
     source_identity: CString,
     log_buffers: Arc<LogBuffers>,
     exception_handler: ErrorHandler,
+    version_mismatch_counter: VersionMismatchCounter,
     term_buffers: Vec<AtomicBuffer>,
     subscriber_position: UnsafeBufferPosition,
+    shared_position: Option<SharedPositionMember>,
     header: Header,
     is_closed: Arc<AtomicBool>, // to make Image clonable
     is_eos: bool,
@@ -111,12 +182,13 @@ impl Image {
         log_buffers: Arc<LogBuffers>,
         exception_handler: ErrorHandler,
     ) -> Image {
-        let header = Header::new(
+        let mut header = Header::new(
             log_buffer_descriptor::initial_term_id(
                 &log_buffers.atomic_buffer(log_buffer_descriptor::LOG_META_DATA_SECTION_INDEX),
             ),
             log_buffers.atomic_buffer(0).capacity(),
         );
+        header.set_image_correlation_id(correlation_id);
 
         let mut term_buffers: Vec<AtomicBuffer> = Vec::new();
 
@@ -133,10 +205,12 @@ impl Image {
             term_buffers,
             header,
             subscriber_position: (*subscriber_position).clone(),
+            shared_position: None,
             log_buffers,
             source_identity,
             is_closed: Arc::new(AtomicBool::new(false)),
             exception_handler,
+            version_mismatch_counter: VersionMismatchCounter::default(),
             correlation_id,
             subscription_registration_id,
             session_id,
@@ -179,6 +253,19 @@ impl Image {
         self.term_buffers[0].capacity() as i32
     }
 
+    /**
+     * Get the MTU length used by the source of the stream, i.e. the maximum length of a fragment including its
+     * header. Useful to size reassembly buffers for messages spanning a few fragments.
+     *
+     * @return the MTU length in bytes from the log buffer meta data.
+     */
+    pub fn mtu_length(&self) -> i32 {
+        let log_meta_data_buffer = self
+            .log_buffers
+            .atomic_buffer(log_buffer_descriptor::LOG_META_DATA_SECTION_INDEX);
+        log_buffer_descriptor::mtu_length(&log_meta_data_buffer)
+    }
+
     /**
      * Number of bits to right shift a position to get a term count for how far the stream has progressed.
      *
@@ -305,6 +392,16 @@ impl Image {
             )
     }
 
+    /**
+     * Has a poll consumed the stream up to its end? The flag is set by poll once all messages up to the
+     * end of stream position have been delivered.
+     *
+     * @return true if a poll has reached the end of the stream.
+     */
+    pub fn reached_end_of_stream(&self) -> bool {
+        self.is_eos
+    }
+
     /**
      * Poll for new messages in a stream. If new messages are found beyond the last consumed position then they
      * will be delivered via the fragment_handler_t up to a limited number of fragments as specified.
@@ -316,33 +413,244 @@ impl Image {
      * @see fragment_handler_t
      */
     pub fn poll(&mut self, fragment_handler: &mut impl FnMut(&AtomicBuffer, Index, Index, &Header), fragment_limit: i32) -> i32 {
+        self.poll_with_image(
+            &mut |_image: &Image, buffer: &AtomicBuffer, offset: Index, length: Index, header: &Header| {
+                fragment_handler(buffer, offset, length, header)
+            },
+            fragment_limit,
+        )
+    }
+
+    /**
+     * Poll for new fragments in a stream and copy each of them out so they can be consumed with an Iterator.
+     * The iterator yields the fragments delivered by a single poll and then ends. Fragments are not reassembled,
+     * to consume messages spanning multiple fragments use a FragmentAssembler with poll instead.
+     *
+     * @param fragment_limit for the number of fragments to be consumed during one polling operation.
+     * @return iterator over the copied payload and header of each fragment consumed.
+     */
+    pub fn fragments(&mut self, fragment_limit: i32) -> impl Iterator<Item = (Vec<u8>, HeaderSnapshot)> {
+        let mut fragments = Vec::new();
+
+        self.poll(
+            &mut |buffer: &AtomicBuffer, offset: Index, length: Index, header: &Header| {
+                fragments.push((buffer.as_sub_slice(offset, length).to_vec(), header.snapshot()));
+            },
+            fragment_limit,
+        );
+
+        fragments.into_iter()
+    }
+
+    /**
+     * Poll for new messages in a stream passing this Image along with each fragment so a handler shared by
+     * several Images can tell which source each message came from.
+     *
+     * @param fragment_handler to which messages are delivered together with this Image.
+     * @param fragment_limit   for the number of fragments to be consumed during one polling operation.
+     * @return the number of fragments that have been consumed.
+     */
+    pub fn poll_with_image(
+        &mut self,
+        fragment_handler: &mut impl FnMut(&Image, &AtomicBuffer, Index, Index, &Header),
+        fragment_limit: i32,
+    ) -> i32 {
         if !self.is_closed() {
+            if self.shared_position.is_some() {
+                return self.poll_shared(fragment_handler, fragment_limit);
+            }
+
             let position = self.subscriber_position.get();
             let term_offset: Index = (position as Index) & self.term_length_mask;
             let index = log_buffer_descriptor::index_by_position(position, self.position_bits_to_shift);
             assert!(index >= 0 && index < log_buffer_descriptor::PARTITION_COUNT);
             let term_buffer = self.term_buffers[index as usize];
 
+            let mut header = self.header.clone();
+            let image: &Image = self;
             let read_outcome: ReadOutcome = term_reader::read(
                 term_buffer,
                 term_offset,
-                fragment_handler,
+                &mut |buffer: &AtomicBuffer, offset: Index, length: Index, header: &Header| {
+                    fragment_handler(image, buffer, offset, length, header)
+                },
                 fragment_limit,
-                &mut self.header,
+                &mut header,
+                &self.version_mismatch_counter,
                 self.exception_handler,
             );
+            self.header = header;
 
             let new_position = position + (read_outcome.offset - term_offset) as i64;
             if new_position > position {
                 self.subscriber_position.set_ordered(new_position);
             }
 
+            self.update_end_of_stream(new_position);
+
             read_outcome.fragments_read
         } else {
             0
         }
     }
 
+    /**
+     * Count frames read by this Image with an unsupported version on the given counter, e.g. one shared by all the
+     * Images of a client. The first mismatch is still reported to the exception handler.
+     *
+     * @param version_mismatch_counter to count frame version mismatches on.
+     */
+    pub(crate) fn set_version_mismatch_counter(&mut self, version_mismatch_counter: VersionMismatchCounter) {
+        self.version_mismatch_counter = version_mismatch_counter;
+    }
+
+    /**
+     * Share the consumption position of this Image with other Images of the same stream so each message is
+     * delivered to exactly one of them. Messages are claimed whole from the shared position and the subscriber
+     * position counter of this Image is advanced only as far as every member of the group has finished reading.
+     * Only poll and poll_with_image may then be used to consume, the other poll variants report an error and
+     * consume nothing.
+     *
+     * @param shared_position consumption position shared by the group.
+     */
+    pub(crate) fn set_shared_position(&mut self, shared_position: Arc<SharedPosition>) {
+        let claim_in_flight = shared_position.join();
+        self.shared_position = Some(SharedPositionMember {
+            group: shared_position,
+            claim_in_flight,
+        });
+    }
+
+    /**
+     * Is the consumption position of this Image shared with other Images in a shared position group.
+     *
+     * @return true if the consumption position is shared otherwise false.
+     */
+    pub fn is_position_shared(&self) -> bool {
+        self.shared_position.is_some()
+    }
+
+    // Only poll and poll_with_image consume through the shared position. Other variants read and advance the
+    // subscriber position of this Image alone, which would deliver messages already claimed by the group again.
+    fn reject_when_position_shared(&self, operation: &str) -> bool {
+        if self.shared_position.is_some() {
+            (self.exception_handler)(AeronError::IllegalStateException(format!(
+                "{} is not supported on an Image in a shared position group",
+                operation
+            )));
+            return true;
+        }
+
+        false
+    }
+
+    fn poll_shared(
+        &mut self,
+        fragment_handler: &mut impl FnMut(&Image, &AtomicBuffer, Index, Index, &Header),
+        fragment_limit: i32,
+    ) -> i32 {
+        let member = self.shared_position.clone().expect("shared position must be set");
+        let mut fragments_read = 0;
+
+        while fragments_read < fragment_limit {
+            let position = member.group.claimed.load(Ordering::SeqCst);
+            let term_offset: Index = (position as Index) & self.term_length_mask;
+            let index = log_buffer_descriptor::index_by_position(position, self.position_bits_to_shift);
+            assert!((0..log_buffer_descriptor::PARTITION_COUNT).contains(&index));
+            let term_buffer = self.term_buffers[index as usize];
+
+            // Claim a whole message so its fragments are never split across members of the group.
+            let term_length = term_buffer.capacity();
+            let mut message_length: Index = 0;
+            let mut message_fragments = 0;
+            let mut is_complete = false;
+            while term_offset + message_length < term_length {
+                let frame_offset = term_offset + message_length;
+                let frame_length = frame_descriptor::frame_length_volatile(&term_buffer, frame_offset);
+                if frame_length <= 0 {
+                    break;
+                }
+
+                message_length += frame_descriptor::aligned_frame_length(frame_length);
+                if frame_descriptor::is_padding_frame(&term_buffer, frame_offset) {
+                    is_complete = true;
+                    break;
+                }
+
+                message_fragments += 1;
+                let flags = term_buffer.get::<u8>(frame_descriptor::flags_offset(frame_offset));
+                if (flags & frame_descriptor::END_FRAG) == frame_descriptor::END_FRAG {
+                    is_complete = true;
+                    break;
+                }
+            }
+
+            if !is_complete || (fragments_read > 0 && fragments_read + message_fragments > fragment_limit) {
+                break;
+            }
+
+            // The claim is recorded before it is made so the completed position never passes it.
+            member.claim_in_flight.store(position, Ordering::SeqCst);
+            let new_position = position + message_length as i64;
+            if member
+                .group
+                .claimed
+                .compare_exchange(position, new_position, Ordering::SeqCst, Ordering::SeqCst)
+                .is_err()
+            {
+                continue;
+            }
+
+            if message_fragments == 0 {
+                // Padding is claimed alone and skipped without being delivered.
+                member.claim_in_flight.store(NO_CLAIM_IN_FLIGHT, Ordering::SeqCst);
+                continue;
+            }
+
+            let mut header = self.header.clone();
+            let image: &Image = self;
+            fragments_read += term_reader::read(
+                term_buffer,
+                term_offset,
+                &mut |buffer: &AtomicBuffer, offset: Index, length: Index, header: &Header| {
+                    fragment_handler(image, buffer, offset, length, header)
+                },
+                message_fragments,
+                &mut header,
+                &self.version_mismatch_counter,
+                self.exception_handler,
+            )
+            .fragments_read;
+            self.header = header;
+            member.claim_in_flight.store(NO_CLAIM_IN_FLIGHT, Ordering::SeqCst);
+        }
+
+        // A claim attempt which lost the race may still be recorded.
+        member.claim_in_flight.store(NO_CLAIM_IN_FLIGHT, Ordering::SeqCst);
+
+        let position = member.group.completed();
+        if position > self.subscriber_position.get() {
+            self.subscriber_position.set_ordered(position);
+        }
+
+        self.update_end_of_stream(position);
+
+        fragments_read
+    }
+
+    fn update_end_of_stream(&mut self, position: i64) {
+        if !self.is_eos
+            && position
+                >= log_buffer_descriptor::end_of_stream_position(
+                    &self
+                        .log_buffers
+                        .atomic_buffer(log_buffer_descriptor::LOG_META_DATA_SECTION_INDEX),
+                )
+        {
+            self.is_eos = true;
+        }
+    }
+
     /**
      * Poll for new messages in a stream. If new messages are found beyond the last consumed position then they
      * will be delivered via the fragment_handler_t up to a limited number of fragments as specified or the
@@ -361,7 +669,7 @@ impl Image {
         limit_position: i64,
         fragment_limit: i32,
     ) -> i32 {
-        if !self.is_closed() {
+        if !self.is_closed() && !self.reject_when_position_shared("bounded_poll") {
             let mut fragments_read = 0;
             let initial_position = self.subscriber_position.get();
             let initial_offset = (initial_position & self.term_length_mask as i64) as i32;
@@ -382,13 +690,17 @@ impl Image {
                 }
 
                 let frame_offset = offset;
-                let aligned_length = align(length, frame_descriptor::FRAME_ALIGNMENT);
+                let aligned_length = frame_descriptor::aligned_frame_length(length);
                 offset += aligned_length;
 
                 if frame_descriptor::is_padding_frame(&term_buffer, frame_offset) {
                     continue;
                 }
 
+                // Report version skew with the driver but still deliver the fragment
+                self.version_mismatch_counter
+                    .check(&term_buffer, frame_offset, self.exception_handler);
+
                 self.header.set_offset(frame_offset);
 
                 fragment_handler(
@@ -430,7 +742,7 @@ impl Image {
         mut fragment_handler: impl FnMut(&AtomicBuffer, Index, Index, &Header) -> Result<ControlledPollAction, AeronError>,
         fragment_limit: i32,
     ) -> i32 {
-        if !self.is_closed() {
+        if !self.is_closed() && !self.reject_when_position_shared("controlled_poll") {
             let mut fragments_read = 0;
             let mut initial_position = self.subscriber_position.get();
             let mut initial_offset: Index = (initial_position as i32) & self.term_length_mask;
@@ -451,13 +763,17 @@ impl Image {
                 }
 
                 let frame_offset = resulting_offset;
-                let aligned_length = align(length, frame_descriptor::FRAME_ALIGNMENT);
+                let aligned_length = frame_descriptor::aligned_frame_length(length);
                 resulting_offset += aligned_length;
 
                 if frame_descriptor::is_padding_frame(&term_buffer, frame_offset) {
                     continue;
                 }
 
+                // Report version skew with the driver but still deliver the fragment
+                self.version_mismatch_counter
+                    .check(&term_buffer, frame_offset, self.exception_handler);
+
                 self.header.set_offset(frame_offset);
 
                 let action = fragment_handler(
@@ -499,6 +815,32 @@ impl Image {
         }
     }
 
+    /**
+     * Poll for new messages in a stream, delivering fragments while the handler returns true. A lighter alternative
+     * to controlled_poll when only backpressure is needed: the fragment for which the handler returns false is not
+     * consumed and is delivered again on the next poll.
+     *
+     * @param predicate_handler to which message fragments are delivered, returning false to stop.
+     * @param fragment_limit    for the number of fragments to be consumed during one polling operation.
+     * @return the number of fragments that have been consumed.
+     */
+    pub fn poll_while(
+        &mut self,
+        mut predicate_handler: impl FnMut(&AtomicBuffer, Index, Index, &Header) -> bool,
+        fragment_limit: i32,
+    ) -> i32 {
+        self.controlled_poll(
+            |buffer: &AtomicBuffer, offset: Index, length: Index, header: &Header| {
+                if predicate_handler(buffer, offset, length, header) {
+                    Ok(ControlledPollAction::CONTINUE)
+                } else {
+                    Ok(ControlledPollAction::ABORT)
+                }
+            },
+            fragment_limit,
+        )
+    }
+
     /**
      * Poll for new messages in a stream. If new messages are found beyond the last consumed position then they
      * will be delivered to the controlled_poll_fragment_handler_t up to a limited number of fragments as specified or
@@ -519,7 +861,7 @@ impl Image {
         max_position: i64,
         fragment_limit: i32,
     ) -> i32 {
-        if !self.is_closed() {
+        if !self.is_closed() && !self.reject_when_position_shared("bounded_controlled_poll") {
             let mut fragments_read = 0;
             let mut initial_position = self.subscriber_position.get();
             let mut initial_offset: Index = initial_position as Index & self.term_length_mask;
@@ -539,13 +881,17 @@ impl Image {
                 }
 
                 let frame_offset = resulting_offset;
-                let aligned_length = align(length, frame_descriptor::FRAME_ALIGNMENT);
+                let aligned_length = frame_descriptor::aligned_frame_length(length);
                 resulting_offset += aligned_length;
 
                 if frame_descriptor::is_padding_frame(&term_buffer, frame_offset) {
                     continue;
                 }
 
+                // Report version skew with the driver but still deliver the fragment
+                self.version_mismatch_counter
+                    .check(&term_buffer, frame_offset, self.exception_handler);
+
                 self.header.set_offset(frame_offset);
 
                 let action = fragment_handler(
@@ -583,6 +929,28 @@ impl Image {
         }
     }
 
+    /**
+     * Poll for new messages in a stream up to a limit position read from a counter on each call, e.g. a replay
+     * window controlled by the application. Delivery otherwise behaves as bounded_controlled_poll.
+     *
+     * @param fragment_handler to which message fragments are delivered.
+     * @param counters_reader  from which the limit counter is read.
+     * @param limit_counter_id of the counter holding the position to consume messages up to.
+     * @param fragment_limit   for the number of fragments to be consumed during one polling operation.
+     * @return the number of fragments that have been consumed or an error if the counter id is invalid.
+     */
+    pub fn bounded_controlled_poll_by_counter(
+        &mut self,
+        fragment_handler: impl FnMut(&AtomicBuffer, Index, Index, &Header) -> Result<ControlledPollAction, AeronError>,
+        counters_reader: &CountersReader,
+        limit_counter_id: i32,
+        fragment_limit: i32,
+    ) -> Result<i32, AeronError> {
+        let limit_position = counters_reader.counter_value(limit_counter_id)? as i64;
+
+        Ok(self.bounded_controlled_poll(fragment_handler, limit_position, fragment_limit))
+    }
+
     /**
      * Peek for new messages in a stream by scanning forward from an initial position. If new messages are found then
      * they will be delivered to the controlled_poll_fragment_handler_t up to a limited position.
@@ -627,7 +995,7 @@ impl Image {
                 }
 
                 let frame_offset = offset;
-                let aligned_length = align(length as Index, frame_descriptor::FRAME_ALIGNMENT);
+                let aligned_length = frame_descriptor::aligned_frame_length(length as Index);
                 offset += aligned_length;
 
                 if frame_descriptor::is_padding_frame(&termb_buffer, frame_offset) {
@@ -637,6 +1005,10 @@ impl Image {
                     continue;
                 }
 
+                // Report version skew with the driver but still deliver the fragment
+                self.version_mismatch_counter
+                    .check(&termb_buffer, frame_offset, self.exception_handler);
+
                 self.header.set_offset(frame_offset);
 
                 let action = fragment_handler(
@@ -692,7 +1064,7 @@ impl Image {
      */
 
     pub fn block_poll(&self, block_handler: BlockHandler, block_length_limit: Index) -> i32 {
-        if !self.is_closed() {
+        if !self.is_closed() && !self.reject_when_position_shared("block_poll") {
             let position = self.subscriber_position.get();
             let term_offset = position as Index & self.term_length_mask;
             let index = log_buffer_descriptor::index_by_position(position, self.position_bits_to_shift);
@@ -999,6 +1371,78 @@ mod tests {
         assert_eq!(image.term_buffer_length(), TERM_LENGTH);
     }
 
+    #[test]
+    fn should_iterate_fragments_available_in_one_poll() {
+        let log_buf = AlignedBuffer::with_capacity(LOG_BUFFER_LENGTH);
+        let src_buf = AlignedBuffer::with_capacity(SRC_BUFFER_LENGTH);
+        let cnt_buf = AlignedBuffer::with_capacity(COUNTER_VALUES_BUFFER_LENGTH);
+        let image_test = ImageTest::new(&log_buf, &src_buf, &cnt_buf);
+        let initial_position =
+            log_buffer_descriptor::compute_position(INITIAL_TERM_ID, 0, *POSITION_BITS_TO_SHIFT, INITIAL_TERM_ID);
+
+        image_test.subscriber_position.set(initial_position);
+        let mut image = Image::create(
+            SESSION_ID,
+            CORRELATION_ID,
+            SUBSCRIPTION_REGISTRATION_ID,
+            CString::new(SOURCE_IDENTITY).unwrap(),
+            &image_test.subscriber_position,
+            image_test.log_buffers.clone(),
+            error_handler,
+        );
+
+        for message_index in 0..3 {
+            image_test.insert_data_frame(INITIAL_TERM_ID, ImageTest::offset_of_frame(message_index));
+        }
+
+        let fragments: Vec<_> = image.fragments(2).collect();
+        assert_eq!(fragments.len(), 2);
+        for (i, (payload, header)) in fragments.iter().enumerate() {
+            assert_eq!(payload.as_slice(), DATA.as_ref());
+            assert_eq!(header.session_id, SESSION_ID);
+            assert_eq!(header.term_offset, ImageTest::offset_of_frame(i as Index));
+            assert_eq!(
+                header.position,
+                initial_position + ImageTest::offset_of_frame(i as Index + 1) as i64
+            );
+        }
+
+        let mut remaining = image.fragments(10);
+        let (payload, header) = remaining.next().unwrap();
+        assert_eq!(payload.as_slice(), DATA.as_ref());
+        assert_eq!(header.term_offset, ImageTest::offset_of_frame(2));
+        assert!(remaining.next().is_none());
+
+        assert_eq!(image.fragments(10).count(), 0);
+    }
+
+    #[test]
+    fn should_report_log_meta_data_for_buffer_sizing() {
+        let log_buf = AlignedBuffer::with_capacity(LOG_BUFFER_LENGTH);
+        let src_buf = AlignedBuffer::with_capacity(SRC_BUFFER_LENGTH);
+        let cnt_buf = AlignedBuffer::with_capacity(COUNTER_VALUES_BUFFER_LENGTH);
+        let image_test = ImageTest::new(&log_buf, &src_buf, &cnt_buf);
+        let mtu_length = 1408;
+
+        image_test
+            .log_meta_data_buffer
+            .put::<i32>(*log_buffer_descriptor::LOG_MTU_LENGTH_OFFSET, mtu_length);
+
+        let image = Image::create(
+            SESSION_ID,
+            CORRELATION_ID,
+            SUBSCRIPTION_REGISTRATION_ID,
+            CString::new(SOURCE_IDENTITY).unwrap(),
+            &image_test.subscriber_position,
+            image_test.log_buffers.clone(),
+            error_handler,
+        );
+
+        assert_eq!(image.mtu_length(), mtu_length);
+        assert_eq!(image.term_buffer_length(), TERM_LENGTH);
+        assert_eq!(image.initial_term_id(), INITIAL_TERM_ID);
+    }
+
     fn fragment_handler_check_len(_buf: &AtomicBuffer, _offset: Index, length: Index, _header: &Header) {
         assert_eq!(length as usize, DATA.len());
     }
@@ -1492,6 +1936,196 @@ mod tests {
         assert_eq!(image.position(), initial_position);
     }
 
+    #[test]
+    fn should_reject_controlled_and_bounded_polls_when_position_shared() {
+        use std::sync::atomic::AtomicI32;
+
+        static ERRORS: AtomicI32 = AtomicI32::new(0);
+
+        fn counting_error_handler(_err: AeronError) {
+            ERRORS.fetch_add(1, Ordering::SeqCst);
+        }
+
+        let log_buf = AlignedBuffer::with_capacity(LOG_BUFFER_LENGTH);
+        let src_buf = AlignedBuffer::with_capacity(SRC_BUFFER_LENGTH);
+        let cnt_buf = AlignedBuffer::with_capacity(COUNTER_VALUES_BUFFER_LENGTH);
+        let image_test = ImageTest::new(&log_buf, &src_buf, &cnt_buf);
+
+        let initial_position = log_buffer_descriptor::compute_position(
+            INITIAL_TERM_ID,
+            ImageTest::offset_of_frame(0),
+            *POSITION_BITS_TO_SHIFT,
+            INITIAL_TERM_ID,
+        );
+
+        image_test.subscriber_position.set(initial_position);
+        let mut image = Image::create(
+            SESSION_ID,
+            CORRELATION_ID,
+            SUBSCRIPTION_REGISTRATION_ID,
+            CString::new(SOURCE_IDENTITY).unwrap(),
+            &image_test.subscriber_position,
+            image_test.log_buffers.clone(),
+            counting_error_handler,
+        );
+        image.set_shared_position(Arc::new(SharedPosition::new(initial_position)));
+
+        image_test.insert_data_frame(INITIAL_TERM_ID, ImageTest::offset_of_frame(0));
+
+        let mut delivered = 0;
+        let mut controlled_handler = |_buffer: &AtomicBuffer, _offset: Index, _length: Index, _header: &Header| {
+            delivered += 1;
+            Ok(ControlledPollAction::CONTINUE)
+        };
+        assert_eq!(image.controlled_poll(&mut controlled_handler, i32::MAX), 0);
+        assert_eq!(image.bounded_controlled_poll(&mut controlled_handler, i64::MAX, i32::MAX), 0);
+        assert_eq!(
+            image.bounded_poll(
+                |_buffer: &AtomicBuffer, _offset: Index, _length: Index, _header: &Header| {},
+                i64::MAX,
+                i32::MAX
+            ),
+            0
+        );
+
+        assert_eq!(delivered, 0);
+        assert_eq!(ERRORS.load(Ordering::SeqCst), 3);
+        assert_eq!(image_test.subscriber_position.get(), initial_position);
+
+        assert_eq!(
+            image.poll(
+                &mut |_buffer: &AtomicBuffer, _offset: Index, _length: Index, _header: &Header| {},
+                i32::MAX
+            ),
+            1
+        );
+    }
+
+    #[test]
+    fn should_count_version_mismatches_and_deliver_from_controlled_and_bounded_polls() {
+        use std::sync::atomic::{AtomicI32, AtomicU64};
+
+        static ERRORS: AtomicI32 = AtomicI32::new(0);
+
+        fn counting_error_handler(err: AeronError) {
+            assert!(matches!(err, AeronError::IllegalStateException(_)));
+            ERRORS.fetch_add(1, Ordering::SeqCst);
+        }
+
+        let log_buf = AlignedBuffer::with_capacity(LOG_BUFFER_LENGTH);
+        let src_buf = AlignedBuffer::with_capacity(SRC_BUFFER_LENGTH);
+        let cnt_buf = AlignedBuffer::with_capacity(COUNTER_VALUES_BUFFER_LENGTH);
+        let image_test = ImageTest::new(&log_buf, &src_buf, &cnt_buf);
+
+        let initial_position = log_buffer_descriptor::compute_position(
+            INITIAL_TERM_ID,
+            ImageTest::offset_of_frame(0),
+            *POSITION_BITS_TO_SHIFT,
+            INITIAL_TERM_ID,
+        );
+
+        image_test.subscriber_position.set(initial_position);
+        let mut image = Image::create(
+            SESSION_ID,
+            CORRELATION_ID,
+            SUBSCRIPTION_REGISTRATION_ID,
+            CString::new(SOURCE_IDENTITY).unwrap(),
+            &image_test.subscriber_position,
+            image_test.log_buffers.clone(),
+            counting_error_handler,
+        );
+        let version_mismatch_count = Arc::new(AtomicU64::new(0));
+        image.set_version_mismatch_counter(VersionMismatchCounter::new(version_mismatch_count.clone()));
+
+        for message_index in 0..3 {
+            let offset = ImageTest::offset_of_frame(message_index);
+            image_test.insert_data_frame(INITIAL_TERM_ID, offset);
+            image_test.term_buffers[0].put::<u8>(offset + frame_descriptor::VERSION_OFFSET, 0x7);
+        }
+
+        let mut delivered = 0;
+        let mut controlled_handler = |_buffer: &AtomicBuffer, _offset: Index, _length: Index, _header: &Header| {
+            delivered += 1;
+            Ok(ControlledPollAction::CONTINUE)
+        };
+        assert_eq!(image.controlled_poll(&mut controlled_handler, 1), 1);
+        assert_eq!(image.bounded_controlled_poll(&mut controlled_handler, i64::MAX, 1), 1);
+        assert_eq!(
+            image.bounded_poll(
+                |_buffer: &AtomicBuffer, _offset: Index, _length: Index, _header: &Header| {},
+                i64::MAX,
+                1
+            ),
+            1
+        );
+
+        assert_eq!(delivered, 2);
+        assert_eq!(version_mismatch_count.load(Ordering::SeqCst), 3);
+        // Only the first mismatch of the image is reported
+        assert_eq!(ERRORS.load(Ordering::SeqCst), 1);
+        assert_eq!(
+            image_test.subscriber_position.get(),
+            initial_position + 3 * *ALIGNED_FRAME_LENGTH as i64
+        );
+    }
+
+    #[test]
+    fn should_stop_poll_while_when_handler_returns_false() {
+        let log_buf = AlignedBuffer::with_capacity(LOG_BUFFER_LENGTH);
+        let src_buf = AlignedBuffer::with_capacity(SRC_BUFFER_LENGTH);
+        let cnt_buf = AlignedBuffer::with_capacity(COUNTER_VALUES_BUFFER_LENGTH);
+        let image_test = ImageTest::new(&log_buf, &src_buf, &cnt_buf);
+
+        let initial_position = log_buffer_descriptor::compute_position(
+            INITIAL_TERM_ID,
+            ImageTest::offset_of_frame(0),
+            *POSITION_BITS_TO_SHIFT,
+            INITIAL_TERM_ID,
+        );
+
+        image_test.subscriber_position.set(initial_position);
+        let mut image = Image::create(
+            SESSION_ID,
+            CORRELATION_ID,
+            SUBSCRIPTION_REGISTRATION_ID,
+            CString::new(SOURCE_IDENTITY).unwrap(),
+            &image_test.subscriber_position,
+            image_test.log_buffers.clone(),
+            error_handler,
+        );
+
+        for message_index in 0..4 {
+            image_test.insert_data_frame(INITIAL_TERM_ID, ImageTest::offset_of_frame(message_index));
+        }
+
+        let mut delivered = 0;
+        let fragments = image.poll_while(
+            |_buffer: &AtomicBuffer, _offset: Index, _length: Index, _header: &Header| {
+                delivered += 1;
+                delivered <= 2
+            },
+            std::i32::MAX,
+        );
+
+        assert_eq!(fragments, 2);
+        assert_eq!(delivered, 3);
+        assert_eq!(
+            image_test.subscriber_position.get(),
+            initial_position + *ALIGNED_FRAME_LENGTH as i64 * 2
+        );
+
+        let fragments = image.poll_while(
+            |_buffer: &AtomicBuffer, _offset: Index, _length: Index, _header: &Header| true,
+            std::i32::MAX,
+        );
+
+        assert_eq!(fragments, 2);
+        assert_eq!(
+            image_test.subscriber_position.get(),
+            initial_position + *ALIGNED_FRAME_LENGTH as i64 * 4
+        );
+    }
+
     #[test]
     fn should_poll_one_fragment_to_controlled_fragment_handler_on_break() {
         let log_buf = AlignedBuffer::with_capacity(LOG_BUFFER_LENGTH);
@@ -1738,6 +2372,62 @@ mod tests {
         assert_eq!(image.position(), max_position);
     }
 
+    #[test]
+    fn should_track_limit_counter_between_bounded_controlled_polls() {
+        let log_buf = AlignedBuffer::with_capacity(LOG_BUFFER_LENGTH);
+        let src_buf = AlignedBuffer::with_capacity(SRC_BUFFER_LENGTH);
+        let cnt_buf = AlignedBuffer::with_capacity(COUNTER_VALUES_BUFFER_LENGTH);
+        let image_test = ImageTest::new(&log_buf, &src_buf, &cnt_buf);
+
+        let limit_counter_id = SUBSCRIBER_POSITION_ID + 1;
+        let counters_metadata = AlignedBuffer::with_capacity(COUNTER_VALUES_BUFFER_LENGTH);
+        let counters_values = AtomicBuffer::from_aligned(&cnt_buf);
+        let counters_reader = CountersReader::new(AtomicBuffer::from_aligned(&counters_metadata), counters_values);
+        let limit_position = UnsafeBufferPosition::new(counters_values, limit_counter_id);
+
+        let initial_position =
+            log_buffer_descriptor::compute_position(INITIAL_TERM_ID, 0, *POSITION_BITS_TO_SHIFT, INITIAL_TERM_ID);
+        image_test.subscriber_position.set(initial_position);
+        let mut image = Image::create(
+            SESSION_ID,
+            CORRELATION_ID,
+            SUBSCRIPTION_REGISTRATION_ID,
+            CString::new(SOURCE_IDENTITY).unwrap(),
+            &image_test.subscriber_position,
+            image_test.log_buffers.clone(),
+            error_handler,
+        );
+
+        for message_index in 0..4 {
+            image_test.insert_data_frame(INITIAL_TERM_ID, ImageTest::offset_of_frame(message_index));
+        }
+
+        limit_position.set(initial_position);
+        let fragments = image
+            .bounded_controlled_poll_by_counter(controlled_poll_handler_continue, &counters_reader, limit_counter_id, 10)
+            .unwrap();
+        assert_eq!(fragments, 0);
+        assert_eq!(image.position(), initial_position);
+
+        limit_position.set(initial_position + *ALIGNED_FRAME_LENGTH as i64);
+        let fragments = image
+            .bounded_controlled_poll_by_counter(controlled_poll_handler_continue, &counters_reader, limit_counter_id, 10)
+            .unwrap();
+        assert_eq!(fragments, 1);
+        assert_eq!(image.position(), initial_position + *ALIGNED_FRAME_LENGTH as i64);
+
+        limit_position.set(initial_position + 3 * *ALIGNED_FRAME_LENGTH as i64);
+        let fragments = image
+            .bounded_controlled_poll_by_counter(controlled_poll_handler_continue, &counters_reader, limit_counter_id, 10)
+            .unwrap();
+        assert_eq!(fragments, 2);
+        assert_eq!(image.position(), initial_position + 3 * *ALIGNED_FRAME_LENGTH as i64);
+
+        assert!(image
+            .bounded_controlled_poll_by_counter(controlled_poll_handler_continue, &counters_reader, -1, 10)
+            .is_err());
+    }
+
     #[test]
     fn should_poll_fragments_to_bounded_controlled_fragment_handler_with_max_position_after_end_of_term() {
         let log_buf = AlignedBuffer::with_capacity(LOG_BUFFER_LENGTH);
@@ -1807,4 +2497,136 @@ mod tests {
         assert_eq!(image_test.subscriber_position.get(), TERM_LENGTH as i64);
         assert_eq!(image.position(), TERM_LENGTH as i64);
     }
+
+    #[test]
+    fn should_map_predicate_result_to_controlled_poll_action() {
+        let buf = AlignedBuffer::with_capacity(LOG_BUFFER_LENGTH);
+        let buffer = AtomicBuffer::from_aligned(&buf);
+        let header = Header::new(INITIAL_TERM_ID, LOG_BUFFER_LENGTH);
+
+        let mut handler = continue_if(|_buf: &AtomicBuffer, offset: Index, _length: Index, _header: &Header| offset == 0);
+
+        assert!(handler(&buffer, 0, 0, &header).unwrap() == ControlledPollAction::CONTINUE);
+        assert!(handler(&buffer, 1, 0, &header).unwrap() == ControlledPollAction::BREAK);
+    }
+
+    #[test]
+    fn should_stop_controlled_poll_when_predicate_returns_false() {
+        let log_buf = AlignedBuffer::with_capacity(LOG_BUFFER_LENGTH);
+        let src_buf = AlignedBuffer::with_capacity(SRC_BUFFER_LENGTH);
+        let cnt_buf = AlignedBuffer::with_capacity(COUNTER_VALUES_BUFFER_LENGTH);
+        let image_test = ImageTest::new(&log_buf, &src_buf, &cnt_buf);
+
+        let initial_position = log_buffer_descriptor::compute_position(
+            INITIAL_TERM_ID,
+            ImageTest::offset_of_frame(0),
+            *POSITION_BITS_TO_SHIFT,
+            INITIAL_TERM_ID,
+        );
+
+        image_test.subscriber_position.set(initial_position);
+        let mut image = Image::create(
+            SESSION_ID,
+            CORRELATION_ID,
+            SUBSCRIPTION_REGISTRATION_ID,
+            CString::new(SOURCE_IDENTITY).unwrap(),
+            &image_test.subscriber_position,
+            image_test.log_buffers.clone(),
+            error_handler,
+        );
+
+        for i in 0..3 {
+            image_test.insert_data_frame(INITIAL_TERM_ID, ImageTest::offset_of_frame(i));
+        }
+
+        let mut calls = 0;
+        let fragments = image.controlled_poll(
+            continue_if(|_buf: &AtomicBuffer, _offset: Index, _length: Index, _header: &Header| {
+                calls += 1;
+                calls < 2
+            }),
+            std::i32::MAX,
+        );
+
+        assert_eq!(fragments, 2);
+        assert_eq!(calls, 2);
+        assert_eq!(image.position(), initial_position + *ALIGNED_FRAME_LENGTH as i64 * 2);
+    }
+
+    #[test]
+    fn should_deliver_final_messages_then_report_end_of_stream() {
+        let log_buf = AlignedBuffer::with_capacity(LOG_BUFFER_LENGTH);
+        let src_buf = AlignedBuffer::with_capacity(SRC_BUFFER_LENGTH);
+        let cnt_buf = AlignedBuffer::with_capacity(COUNTER_VALUES_BUFFER_LENGTH);
+        let image_test = ImageTest::new(&log_buf, &src_buf, &cnt_buf);
+
+        let initial_position =
+            log_buffer_descriptor::compute_position(INITIAL_TERM_ID, 0, *POSITION_BITS_TO_SHIFT, INITIAL_TERM_ID);
+        let eos_position = initial_position + *ALIGNED_FRAME_LENGTH as i64 * 2;
+        log_buffer_descriptor::set_end_of_stream_position(&image_test.log_meta_data_buffer, eos_position);
+
+        image_test.subscriber_position.set(initial_position);
+        let mut image = Image::create(
+            SESSION_ID,
+            CORRELATION_ID,
+            SUBSCRIPTION_REGISTRATION_ID,
+            CString::new(SOURCE_IDENTITY).unwrap(),
+            &image_test.subscriber_position,
+            image_test.log_buffers.clone(),
+            error_handler,
+        );
+
+        image_test.insert_data_frame(INITIAL_TERM_ID, ImageTest::offset_of_frame(0));
+        image_test.insert_data_frame(INITIAL_TERM_ID, ImageTest::offset_of_frame(1));
+
+        assert!(!image.is_end_of_stream());
+        assert!(!image.reached_end_of_stream());
+
+        let mut lengths = vec![];
+        let fragments = image.poll(
+            &mut |_buf: &AtomicBuffer, _offset: Index, length: Index, _header: &Header| lengths.push(length),
+            std::i32::MAX,
+        );
+
+        assert_eq!(fragments, 2);
+        assert_eq!(lengths, vec![DATA.len() as Index; 2]);
+        assert_eq!(image.position(), eos_position);
+        assert!(image.reached_end_of_stream());
+        assert!(image.is_end_of_stream());
+    }
+
+    #[test]
+    fn should_not_report_end_of_stream_before_final_message_is_consumed() {
+        let log_buf = AlignedBuffer::with_capacity(LOG_BUFFER_LENGTH);
+        let src_buf = AlignedBuffer::with_capacity(SRC_BUFFER_LENGTH);
+        let cnt_buf = AlignedBuffer::with_capacity(COUNTER_VALUES_BUFFER_LENGTH);
+        let image_test = ImageTest::new(&log_buf, &src_buf, &cnt_buf);
+
+        let initial_position =
+            log_buffer_descriptor::compute_position(INITIAL_TERM_ID, 0, *POSITION_BITS_TO_SHIFT, INITIAL_TERM_ID);
+        let eos_position = initial_position + *ALIGNED_FRAME_LENGTH as i64 * 2;
+        log_buffer_descriptor::set_end_of_stream_position(&image_test.log_meta_data_buffer, eos_position);
+
+        image_test.subscriber_position.set(initial_position);
+        let mut image = Image::create(
+            SESSION_ID,
+            CORRELATION_ID,
+            SUBSCRIPTION_REGISTRATION_ID,
+            CString::new(SOURCE_IDENTITY).unwrap(),
+            &image_test.subscriber_position,
+            image_test.log_buffers.clone(),
+            error_handler,
+        );
+
+        image_test.insert_data_frame(INITIAL_TERM_ID, ImageTest::offset_of_frame(0));
+        image_test.insert_data_frame(INITIAL_TERM_ID, ImageTest::offset_of_frame(1));
+
+        assert_eq!(image.poll(&mut fragment_handler, 1), 1);
+        assert!(!image.reached_end_of_stream());
+        assert!(!image.is_end_of_stream());
+
+        assert_eq!(image.poll(&mut fragment_handler, 1), 1);
+        assert!(image.reached_end_of_stream());
+        assert!(image.is_end_of_stream());
+    }
 }
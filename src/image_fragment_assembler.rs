@@ -0,0 +1,199 @@
+/*
+ * Copyright 2020 UT OVERSEAS INC
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ * https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use crate::{
+    buffer_builder::BufferBuilder,
+    concurrent::{
+        atomic_buffer::AtomicBuffer,
+        logbuffer::{data_frame_header, frame_descriptor, header::Header},
+    },
+    fragment_assembler::{Fragment, OnDroppedPartial},
+    utils::types::Index,
+};
+
+const DEFAULT_IMAGE_FRAGMENT_ASSEMBLY_BUFFER_LENGTH: isize = 4096;
+
+/**
+ * A handler that sits in a chain-of-responsibility pattern that reassembles fragmented messages
+ * so that the next handler in the chain only sees whole messages. This is for a single session on an Image
+ * and not for multiple session Images in a Subscription.
+ * <p>
+ * Unfragmented messages are delegated without copy. Fragmented messages are copied to a temporary
+ * buffer for reassembly before delegation.
+ * <p>
+ * The Header passed to the delegate on assembling a message will be that of the last fragment.
+ * <p>
+ * The fragments of a message never span terms, so a fragment carrying a term id other than that of the
+ * message in progress means the Image rolled over or reconnected mid-message. The partial message is then
+ * discarded and reported rather than concatenated with data from the new term.
+ */
+pub struct ImageFragmentAssembler<'a> {
+    delegate: &'a mut dyn Fragment,
+    builder: BufferBuilder,
+    partial_term_id: i32,
+    on_term_rollover: Option<OnDroppedPartial>,
+    term_rollover_count: u64,
+}
+
+impl<'a> ImageFragmentAssembler<'a> {
+    /**
+     * Construct an adapter to reassemble message fragments and delegate on whole messages.
+     *
+     * @param delegate              onto which whole messages are forwarded.
+     * @param initial_buffer_length to be used for the reassembly buffer.
+     */
+    pub fn new(delegate: &'a mut impl Fragment, initial_buffer_length: Option<isize>) -> Self {
+        Self {
+            delegate,
+            builder: BufferBuilder::new(initial_buffer_length.unwrap_or(DEFAULT_IMAGE_FRAGMENT_ASSEMBLY_BUFFER_LENGTH)),
+            partial_term_id: 0,
+            on_term_rollover: None,
+            term_rollover_count: 0,
+        }
+    }
+
+    /**
+     * Set the callback to be notified when a partially assembled message is discarded because a fragment
+     * arrived with a different term id.
+     *
+     * @param on_term_rollover callback to be notified.
+     */
+    pub fn set_on_term_rollover(&mut self, on_term_rollover: OnDroppedPartial) {
+        self.on_term_rollover = Some(on_term_rollover);
+    }
+
+    /**
+     * Number of partially assembled messages discarded because a fragment arrived with a different term id.
+     *
+     * @return number of partial messages discarded on term rollover.
+     */
+    pub fn term_rollover_count(&self) -> u64 {
+        self.term_rollover_count
+    }
+
+    /**
+     * Compose a fragment handler that calls this ImageFragmentAssembler instance for reassembly. Suitable for
+     * passing to Image::poll.
+     *
+     * @return fragment handler composed with the ImageFragmentAssembler instance
+     */
+    pub fn handler(&'a mut self) -> impl Fragment + 'a {
+        move |buffer: &AtomicBuffer, offset, length, header: &Header| self.on_fragment(buffer, offset, length, header)
+    }
+
+    #[inline]
+    fn on_fragment(&mut self, buffer: &AtomicBuffer, offset: Index, length: Index, header: &Header) {
+        let flags = header.flags();
+
+        if (flags & frame_descriptor::UNFRAGMENTED) == frame_descriptor::UNFRAGMENTED {
+            (self.delegate)(buffer, offset, length, header);
+        } else if (flags & frame_descriptor::BEGIN_FRAG) == frame_descriptor::BEGIN_FRAG {
+            self.builder
+                .reset()
+                .append(buffer, offset, length, header)
+                .expect("append failed");
+            self.partial_term_id = header.term_id();
+        } else if self.builder.limit() != data_frame_header::LENGTH {
+            if header.term_id() != self.partial_term_id {
+                let dropped_length = self.builder.limit() - data_frame_header::LENGTH;
+                self.builder.reset();
+
+                self.term_rollover_count += 1;
+                if let Some(on_term_rollover) = self.on_term_rollover {
+                    on_term_rollover(header.session_id(), dropped_length);
+                }
+                return;
+            }
+
+            self.builder.append(buffer, offset, length, header).expect("append failed");
+
+            if flags & frame_descriptor::END_FRAG == frame_descriptor::END_FRAG {
+                let msg_length = self.builder.limit() - data_frame_header::LENGTH;
+                let msg_buffer = AtomicBuffer::new(self.builder.buffer(), self.builder.limit());
+
+                (*self.delegate)(&msg_buffer, data_frame_header::LENGTH, msg_length, header);
+
+                self.builder.reset();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicI32, Ordering};
+
+    use super::*;
+    use crate::fragment_assembler::fragment_assembler_test::{FragmentAssemblerTest, ACTIVE_TERM_ID, MSG_LENGTH, SESSION_ID};
+
+    #[test]
+    fn should_reassemble_fragments_within_one_term() {
+        let mut test = FragmentAssemblerTest::new();
+        static LAST_LENGTH: AtomicI32 = AtomicI32::new(0);
+
+        let mut fragment = |_buffer: &AtomicBuffer, _offset: Index, length: Index, _header: &Header| {
+            LAST_LENGTH.store(length, Ordering::Relaxed);
+        };
+        let mut adapter = ImageFragmentAssembler::new(&mut fragment, None);
+
+        test.feed_three_fragment_message(&mut |buffer, offset, length, header| {
+            adapter.on_fragment(buffer, offset, length, header)
+        });
+
+        assert_eq!(LAST_LENGTH.load(Ordering::Relaxed), MSG_LENGTH * 3);
+        assert_eq!(adapter.term_rollover_count(), 0);
+    }
+
+    #[test]
+    fn should_discard_and_report_partial_when_term_id_changes_mid_message() {
+        let mut test = FragmentAssemblerTest::new();
+        static ROLLOVER_CALLS: AtomicI32 = AtomicI32::new(0);
+        static DROPPED_LENGTH: AtomicI32 = AtomicI32::new(0);
+        static CALLS: AtomicI32 = AtomicI32::new(0);
+
+        fn on_term_rollover(session_id: i32, dropped_length: Index) {
+            assert_eq!(session_id, SESSION_ID);
+            ROLLOVER_CALLS.fetch_add(1, Ordering::Relaxed);
+            DROPPED_LENGTH.store(dropped_length, Ordering::Relaxed);
+        }
+
+        let mut fragment = |_buffer: &AtomicBuffer, _offset: Index, _length: Index, _header: &Header| {
+            CALLS.fetch_add(1, Ordering::Relaxed);
+        };
+        let mut adapter = ImageFragmentAssembler::new(&mut fragment, None);
+        adapter.set_on_term_rollover(on_term_rollover);
+
+        // BEGIN in the active term followed by a MID and an END claiming the next term.
+        let frames = [
+            (frame_descriptor::BEGIN_FRAG, ACTIVE_TERM_ID),
+            (0, ACTIVE_TERM_ID + 1),
+            (frame_descriptor::END_FRAG, ACTIVE_TERM_ID + 1),
+        ];
+        for (i, (flags, term_id)) in frames.iter().enumerate() {
+            test.feed_fragment(
+                &mut |buffer, offset, length, header| adapter.on_fragment(buffer, offset, length, header),
+                *flags,
+                i as Index,
+                *term_id,
+            );
+        }
+
+        assert_eq!(ROLLOVER_CALLS.load(Ordering::Relaxed), 1);
+        assert_eq!(DROPPED_LENGTH.load(Ordering::Relaxed), MSG_LENGTH);
+        assert_eq!(adapter.term_rollover_count(), 1);
+        assert_eq!(CALLS.load(Ordering::Relaxed), 0);
+    }
+}
@@ -34,7 +34,11 @@ pub mod exclusive_publication;
 pub mod fragment_assembler;
 pub mod heartbeat_timestamp;
 pub mod image;
+pub mod image_fragment_assembler;
 pub mod protocol;
 pub mod publication;
+pub mod rate_limited_publication;
+pub mod recording_controlled_handler;
+pub mod replay_merge;
 pub mod subscription;
 pub mod utils;
@@ -15,11 +15,13 @@
  */
 
 use std::{
+    borrow::Cow,
     ffi::CString,
     sync::{
         atomic::{AtomicBool, Ordering},
         Arc, Mutex,
     },
+    time::Duration,
 };
 
 use crate::{
@@ -36,15 +38,27 @@ use crate::{
         position::{ReadablePosition, UnsafeBufferPosition},
         status::status_indicator_reader,
     },
-    utils::{bit_utils::number_of_trailing_zeroes, errors::AeronError, log_buffers::LogBuffers, types::Index},
+    utils::{bit_utils::number_of_trailing_zeroes, errors::AeronError, log_buffers::LogBuffers, misc::spin_until, types::Index},
 };
 
 pub const NOT_CONNECTED: i64 = -1;
 pub const BACK_PRESSURED: i64 = -2;
 pub const ADMIN_ACTION: i64 = -3;
 pub const PUBLICATION_CLOSED: i64 = -4;
+/**
+ * The offer failed because the maximum possible position for the term length has been reached. This is terminal,
+ * retrying will never succeed, and the publication should be closed and a new one added.
+ */
 pub const MAX_POSITION_EXCEEDED: i64 = -5;
 
+/**
+ * Callback invoked when a publication rotates its log to a new term.
+ *
+ * @param old_term_id term id of the term which has been filled.
+ * @param new_term_id term id of the term which is now active.
+ */
+pub type OnTermRotation = fn(i32, i32);
+
 pub trait BulkPubSize {
     const SIZE: usize;
 }
@@ -88,6 +102,7 @@ pub struct Publication {
     // it was unique_ptr on TermAppender's
     appenders: [TermAppender; log_buffer_descriptor::PARTITION_COUNT as usize],
     header_writer: HeaderWriter,
+    on_term_rotation: Option<OnTermRotation>,
 }
 
 impl Publication {
@@ -123,6 +138,7 @@ impl Publication {
             channel_status_id,
             is_closed: AtomicBool::from(false),
             header_writer: HeaderWriter::new(log_buffer_descriptor::default_frame_header(&log_md_buffer)),
+            on_term_rotation: None,
             appenders: [
                 TermAppender::new(
                     log_buffers.atomic_buffer(0),
@@ -146,12 +162,22 @@ impl Publication {
     /**
      * Media address for delivery to the channel.
      *
-     * @    Media address for delivery to the channel.
+     * @return Media address for delivery to the channel.
      */
     pub fn channel(&self) -> CString {
         self.channel.clone()
     }
 
+    /**
+     * Media address for delivery to the channel as a string, e.g. for logging or re-adding destinations.
+     * Invalid UTF-8 sequences are replaced with U+FFFD rather than failing.
+     *
+     * @return Media address for delivery to the channel.
+     */
+    pub fn channel_str(&self) -> Cow<'_, str> {
+        self.channel.to_string_lossy()
+    }
+
     /**
      * Stream identity for scoping within the channel media address.
      *
@@ -255,6 +281,17 @@ impl Publication {
         !self.is_closed() && log_buffer_descriptor::is_connected(&self.log_meta_data_buffer)
     }
 
+    /**
+     * Spin until this Publication sees an active subscriber, yielding the thread between checks of the
+     * is_connected flag in the log meta data.
+     *
+     * @param timeout to wait for a subscriber to connect.
+     * @return true if connected within the timeout, false if timed out or the Publication was closed.
+     */
+    pub fn await_connected(&self, timeout: Duration) -> bool {
+        spin_until(timeout, || self.is_connected(), || self.is_closed())
+    }
+
     /**
      * Has this object been closed and should no longer be used?
      *
@@ -323,6 +360,15 @@ impl Publication {
         }
     }
 
+    /**
+     * Set the callback to be invoked each time this publication rotates to a new term.
+     *
+     * @param handler to be called with the old and new term ids.
+     */
+    pub fn set_on_term_rotation(&mut self, handler: OnTermRotation) {
+        self.on_term_rotation = Some(handler);
+    }
+
     /**
      * Get the counter id used to represent the channel status.
      *
@@ -340,7 +386,7 @@ impl Publication {
      * @param length in bytes of the encoded message.
      * @param reserved_value_supplier for the frame.
      * @    The new stream position, otherwise {@link #NOT_CONNECTED}, {@link #BACK_PRESSURED},
-     * {@link #ADMIN_ACTION} or {@link #CLOSED}.
+     * {@link #ADMIN_ACTION}, {@link #CLOSED} or {@link #MAX_POSITION_EXCEEDED}.
      */
     pub fn offer_opt(
         &self,
@@ -375,6 +421,7 @@ impl Publication {
                         length,
                         reserved_value_supplier,
                         term_id,
+                        0,
                     )
                 } else {
                     self.check_max_message_length(length)?;
@@ -386,6 +433,7 @@ impl Publication {
                         self.max_payload_length,
                         reserved_value_supplier,
                         term_id,
+                        0,
                     )
                 };
 
@@ -393,7 +441,7 @@ impl Publication {
                     term_count,
                     term_offset as i32,
                     term_id,
-                    position as Index,
+                    position,
                     resulting_offset.expect("Something wrong with resulting offset"),
                 );
             } else {
@@ -411,7 +459,7 @@ impl Publication {
      * @param offset offset in the buffer at which the encoded message begins.
      * @param length in bytes of the encoded message.
      * @    The new stream position, otherwise {@link #NOT_CONNECTED}, {@link #BACK_PRESSURED},
-     * {@link #ADMIN_ACTION} or {@link #CLOSED}.
+     * {@link #ADMIN_ACTION}, {@link #CLOSED} or {@link #MAX_POSITION_EXCEEDED}.
      */
     pub fn offer_part(&self, buffer: AtomicBuffer, offset: Index, length: Index) -> Result<i64, AeronError> {
         self.offer_opt(buffer, offset, length, default_reserved_value_supplier)
@@ -434,7 +482,7 @@ impl Publication {
      * @param lastBuffer after the message.
      * @param reserved_value_supplier for the frame.
      * @    The new stream position, otherwise {@link #NOT_CONNECTED}, {@link #BACK_PRESSURED},
-     * {@link #ADMIN_ACTION} or {@link #CLOSED}.
+     * {@link #ADMIN_ACTION}, {@link #CLOSED} or {@link #MAX_POSITION_EXCEEDED}.
      */
     // NOT implemented. Translate it from C++ if you need one.
     //pub fn offer_buf_iter<T>(&self, startBuffer: T, lastBuffer: T, reserved_value_supplier: OnReservedValueSupplier) -> Result<i64, AeronError> { }
@@ -446,7 +494,7 @@ impl Publication {
      * @param length of the array of buffers.
      * @param reserved_value_supplier for the frame.
      * @    The new stream position, otherwise {@link #NOT_CONNECTED}, {@link #BACK_PRESSURED},
-     * {@link #ADMIN_ACTION} or {@link #CLOSED}.
+     * {@link #ADMIN_ACTION}, {@link #CLOSED} or {@link #MAX_POSITION_EXCEEDED}.
      */
     // NOT implemented. Translate it from C++ if you need one.
     //pub fn offer_arr(&self, buffers[]: AtomicBuffer, length: Index, reserved_value_supplier: OnReservedValueSupplier) -> Result<i64, AeronError> {
@@ -459,7 +507,7 @@ impl Publication {
      * @param buffers containing parts of the message.
      * @param reserved_value_supplier for the frame.
      * @    The new stream position, otherwise {@link #NOT_CONNECTED}, {@link #BACK_PRESSURED},
-     * {@link #ADMIN_ACTION} or {@link #CLOSED}.
+     * {@link #ADMIN_ACTION}, {@link #CLOSED} or {@link #MAX_POSITION_EXCEEDED}.
      */
     pub fn offer_bulk(
         &mut self,
@@ -520,7 +568,7 @@ impl Publication {
                     term_count,
                     term_offset as i32,
                     term_id,
-                    position as Index,
+                    position,
                     resulting_offset.expect("Error getting resulting_offset"),
                 );
             } else {
@@ -540,7 +588,7 @@ impl Publication {
      * @param length      of the range to claim, in bytes..
      * @param buffer_claim to be populate if the claim succeeds.
      * @    The new stream position, otherwise {@link #NOT_CONNECTED}, {@link #BACK_PRESSURED},
-     * {@link #ADMIN_ACTION} or {@link #CLOSED}.
+     * {@link #ADMIN_ACTION}, {@link #CLOSED} or {@link #MAX_POSITION_EXCEEDED}.
      * @throws IllegalArgumentException if the length is greater than max payload length within an MTU.
      * @see BufferClaim::commit
      */
@@ -569,7 +617,7 @@ impl Publication {
                     term_count,
                     term_offset as i32,
                     term_id,
-                    position as Index,
+                    position,
                     resulting_offset.expect("Error getting resulting_offset"),
                 );
             } else {
@@ -660,16 +708,24 @@ impl Publication {
         self.is_closed.store(true, Ordering::Release);
     }
 
-    fn new_position(&self, term_count: Index, term_offset: Index, term_id: i32, position: Index, resulting_offset: Index) -> i64 {
+    fn new_position(&self, term_count: Index, term_offset: Index, term_id: i32, position: i64, resulting_offset: Index) -> i64 {
+        let term_begin_position = position - term_offset as i64;
+
         if resulting_offset > 0 {
-            return (position - term_offset) as i64 + resulting_offset as i64;
+            return term_begin_position + resulting_offset as i64;
         }
 
-        if position as i64 + term_offset as i64 > self.max_possible_position {
+        // Rotating past the last term would wrap the term id, so the publication can make no further progress.
+        if term_begin_position + self.term_buffer_length() as i64 >= self.max_possible_position {
             return MAX_POSITION_EXCEEDED;
         }
 
-        log_buffer_descriptor::rotate_log(&self.log_meta_data_buffer, term_count, term_id);
+        // Only the publisher whose CAS moves the active term count on reports the rotation.
+        if log_buffer_descriptor::rotate_log(&self.log_meta_data_buffer, term_count, term_id) {
+            if let Some(on_term_rotation) = self.on_term_rotation {
+                on_term_rotation(term_id, term_id + 1);
+            }
+        }
 
         ADMIN_ACTION
     }
@@ -720,8 +776,10 @@ impl Drop for Publication {
     }
 }
 
+/// Publication over an in-memory log with a conductor that is not connected to a media driver, shared by the
+/// publication tests.
 #[cfg(test)]
-mod tests {
+pub(crate) mod publication_test {
     use std::ffi::CString;
     use std::sync::{Arc, Mutex};
 
@@ -736,18 +794,13 @@ mod tests {
                 copy_broadcast_receiver::CopyBroadcastReceiver,
             },
             counters::CountersReader,
-            logbuffer::{
-                buffer_claim::BufferClaim,
-                data_frame_header::LENGTH,
-                frame_descriptor,
-                log_buffer_descriptor::{self, AERON_PAGE_MIN_SIZE, TERM_MIN_LENGTH},
-            },
-            position::{ReadablePosition, UnsafeBufferPosition},
+            logbuffer::log_buffer_descriptor::{self, AERON_PAGE_MIN_SIZE, TERM_MIN_LENGTH},
+            position::UnsafeBufferPosition,
             ring_buffer::{self, ManyToOneRingBuffer},
             status::status_indicator_reader::{StatusIndicatorReader, NO_ID_ALLOCATED},
         },
         driver_proxy::DriverProxy,
-        publication::{Publication, ADMIN_ACTION, NOT_CONNECTED, PUBLICATION_CLOSED},
+        publication::Publication,
         utils::{
             errors::AeronError,
             log_buffers::LogBuffers,
@@ -757,37 +810,37 @@ mod tests {
     };
 
     lazy_static! {
-        pub static ref CHANNEL: CString = CString::new("aeron:udp?endpoint=localhost:40123").unwrap();
+        pub(crate) static ref CHANNEL: CString = CString::new("aeron:udp?endpoint=localhost:40123").unwrap();
     }
-    const STREAM_ID: i32 = 10;
-    const SESSION_ID: i32 = 200;
-    const PUBLICATION_LIMIT_COUNTER_ID: i32 = 0;
+    pub(crate) const STREAM_ID: i32 = 10;
+    pub(crate) const SESSION_ID: i32 = 200;
+    pub(crate) const PUBLICATION_LIMIT_COUNTER_ID: i32 = 0;
 
-    const CORRELATION_ID: i64 = 100;
-    const ORIGINAL_REGISTRATION_ID: i64 = 100;
-    const TERM_ID_1: i32 = 1;
+    pub(crate) const CORRELATION_ID: i64 = 100;
+    pub(crate) const ORIGINAL_REGISTRATION_ID: i64 = 100;
+    pub(crate) const TERM_ID_1: i32 = 1;
 
-    const DRIVER_TIMEOUT_MS: Moment = 10 * 1000;
-    const RESOURCE_LINGER_TIMEOUT_MS: Moment = 5 * 1000;
-    const INTER_SERVICE_TIMEOUT_NS: Moment = 5 * 1000 * 1000 * 1000;
-    const INTER_SERVICE_TIMEOUT_MS: Moment = INTER_SERVICE_TIMEOUT_NS / 1_000_000;
-    const PRE_TOUCH_MAPPED_MEMORY: bool = false;
+    pub(crate) const DRIVER_TIMEOUT_MS: Moment = 10 * 1000;
+    pub(crate) const RESOURCE_LINGER_TIMEOUT_MS: Moment = 5 * 1000;
+    pub(crate) const INTER_SERVICE_TIMEOUT_NS: Moment = 5 * 1000 * 1000 * 1000;
+    pub(crate) const INTER_SERVICE_TIMEOUT_MS: Moment = INTER_SERVICE_TIMEOUT_NS / 1_000_000;
+    pub(crate) const PRE_TOUCH_MAPPED_MEMORY: bool = false;
 
     // const LOG_FILE_LENGTH: i32 = ((TERM_MIN_LENGTH * 3) + log_buffer_descriptor::LOG_META_DATA_LENGTH);
 
-    const CAPACITY: i32 = 1024;
-    const MANY_TO_ONE_RING_BUFFER_LENGTH: i32 = CAPACITY + ring_buffer::TRAILER_LENGTH;
-    const BROADCAST_BUFFER_LENGTH: i32 = CAPACITY + broadcast_buffer_descriptor::TRAILER_LENGTH;
+    pub(crate) const CAPACITY: i32 = 1024;
+    pub(crate) const MANY_TO_ONE_RING_BUFFER_LENGTH: i32 = CAPACITY + ring_buffer::TRAILER_LENGTH;
+    pub(crate) const BROADCAST_BUFFER_LENGTH: i32 = CAPACITY + broadcast_buffer_descriptor::TRAILER_LENGTH;
     // const COUNTER_VALUES_BUFFER_LENGTH: i32 = 1024 * 1024;
-    const COUNTER_METADATA_BUFFER_LENGTH: i32 = 4 * 1024 * 1024;
+    pub(crate) const COUNTER_METADATA_BUFFER_LENGTH: i32 = 4 * 1024 * 1024;
 
     #[inline]
-    fn raw_tail_value(term_id: i32, position: i64) -> i64 {
+    pub(crate) fn raw_tail_value(term_id: i32, position: i64) -> i64 {
         (term_id as i64 * (1_i64 << 32)) as i64 | position
     }
 
     #[inline]
-    fn term_tail_counter_offset(index: i32) -> Index {
+    pub(crate) fn term_tail_counter_offset(index: i32) -> Index {
         *log_buffer_descriptor::TERM_TAIL_COUNTER_OFFSET + (index * I64_SIZE)
     }
 
@@ -808,29 +861,29 @@ mod tests {
     fn on_close_client_handler() {}
 
     #[allow(dead_code)]
-    struct PublicationTest {
-        src: AlignedBuffer,
-        log: AlignedBuffer,
+    pub(crate) struct PublicationTest {
+        pub src: AlignedBuffer,
+        pub log: AlignedBuffer,
 
-        conductor: Arc<Mutex<ClientConductor>>,
-        to_driver: AlignedBuffer,
-        to_clients: AlignedBuffer,
-        counter_metadata: AlignedBuffer,
-        counter_values: AlignedBuffer,
+        pub conductor: Arc<Mutex<ClientConductor>>,
+        pub to_driver: AlignedBuffer,
+        pub to_clients: AlignedBuffer,
+        pub counter_metadata: AlignedBuffer,
+        pub counter_values: AlignedBuffer,
 
-        to_driver_buffer: AtomicBuffer,
-        to_clients_buffer: AtomicBuffer,
+        pub to_driver_buffer: AtomicBuffer,
+        pub to_clients_buffer: AtomicBuffer,
 
-        many_to_one_ring_buffer: Arc<ManyToOneRingBuffer>,
+        pub many_to_one_ring_buffer: Arc<ManyToOneRingBuffer>,
 
-        term_buffers: [AtomicBuffer; 3],
-        log_meta_data_buffer: AtomicBuffer,
-        src_buffer: AtomicBuffer,
+        pub term_buffers: [AtomicBuffer; 3],
+        pub log_meta_data_buffer: AtomicBuffer,
+        pub src_buffer: AtomicBuffer,
 
-        log_buffers: Arc<LogBuffers>,
-        publication_limit: UnsafeBufferPosition,
-        channel_status_indicator: StatusIndicatorReader,
-        publication: Publication,
+        pub log_buffers: Arc<LogBuffers>,
+        pub publication_limit: UnsafeBufferPosition,
+        pub channel_status_indicator: StatusIndicatorReader,
+        pub publication: Publication,
     }
 
     impl PublicationTest {
@@ -934,6 +987,55 @@ mod tests {
                 ),
             }
         }
+
+        /// Create another Publication over the log of this fixture, e.g. for a wrapper to take ownership of.
+        pub fn new_publication(&self) -> Publication {
+            Publication::new(
+                self.conductor.clone(),
+                (*CHANNEL).clone(),
+                CORRELATION_ID,
+                ORIGINAL_REGISTRATION_ID,
+                STREAM_ID,
+                SESSION_ID,
+                self.publication_limit.clone(),
+                NO_ID_ALLOCATED,
+                self.log_buffers.clone(),
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicI32, Ordering};
+    use std::thread;
+    use std::time::Duration;
+
+    use crate::{
+        concurrent::{
+            atomic_buffer::AtomicBuffer,
+            logbuffer::{
+                buffer_claim::BufferClaim,
+                data_frame_header::LENGTH,
+                frame_descriptor,
+                log_buffer_descriptor::{self, TERM_MIN_LENGTH},
+            },
+            position::ReadablePosition,
+            status::status_indicator_reader::NO_ID_ALLOCATED,
+        },
+        publication::{Publication, ADMIN_ACTION, MAX_POSITION_EXCEEDED, NOT_CONNECTED, PUBLICATION_CLOSED},
+    };
+
+    use super::publication_test::*;
+
+    #[test]
+    fn should_report_channel_and_registration_ids_given_at_creation() {
+        let test = PublicationTest::new();
+        assert_eq!(test.publication.channel(), *CHANNEL);
+        assert_eq!(test.publication.channel_str(), "aeron:udp?endpoint=localhost:40123");
+        assert_eq!(test.publication.registration_id(), CORRELATION_ID);
+        assert_eq!(test.publication.original_registration_id(), ORIGINAL_REGISTRATION_ID);
+        assert!(test.publication.is_original());
     }
 
     #[test]
@@ -971,6 +1073,23 @@ mod tests {
         assert!(test.publication.is_connected());
     }
 
+    #[test]
+    fn should_await_connected_flag_set_from_another_thread() {
+        let test = PublicationTest::new();
+        log_buffer_descriptor::set_is_connected(&test.log_meta_data_buffer, false);
+        assert!(!test.publication.await_connected(Duration::from_millis(1)));
+
+        let address = test.log_meta_data_buffer.buffer() as usize;
+        let length = test.log_meta_data_buffer.capacity();
+        let driver = thread::spawn(move || {
+            let log_meta_data_buffer = AtomicBuffer::new(address as *mut u8, length);
+            log_buffer_descriptor::set_is_connected(&log_meta_data_buffer, true);
+        });
+
+        assert!(test.publication.await_connected(Duration::from_secs(5)));
+        driver.join().unwrap();
+    }
+
     #[test]
     fn should_ensure_the_publication_is_open_before_reading_position() {
         let test = PublicationTest::new();
@@ -1066,6 +1185,32 @@ mod tests {
         assert!(test.publication.position() > (initial_position + LENGTH + test.src_buffer.capacity()) as i64);
     }
 
+    #[test]
+    fn should_notify_term_rotation_when_append_trips() {
+        static OLD_TERM_ID: AtomicI32 = AtomicI32::new(0);
+        static NEW_TERM_ID: AtomicI32 = AtomicI32::new(0);
+
+        fn on_term_rotation(old_term_id: i32, new_term_id: i32) {
+            OLD_TERM_ID.store(old_term_id, Ordering::SeqCst);
+            NEW_TERM_ID.store(new_term_id, Ordering::SeqCst);
+        }
+
+        let mut test = PublicationTest::new();
+        let active_index = log_buffer_descriptor::index_by_term(TERM_ID_1, TERM_ID_1);
+        let initial_position = TERM_MIN_LENGTH - LENGTH;
+
+        test.log_meta_data_buffer.put(
+            term_tail_counter_offset(active_index),
+            raw_tail_value(TERM_ID_1, initial_position as i64),
+        );
+        test.publication_limit.set(i32::max_value() as i64);
+        test.publication.set_on_term_rotation(on_term_rotation);
+
+        assert_eq!(test.publication.offer(test.src_buffer).unwrap(), ADMIN_ACTION);
+        assert_eq!(OLD_TERM_ID.load(Ordering::SeqCst), TERM_ID_1);
+        assert_eq!(NEW_TERM_ID.load(Ordering::SeqCst), TERM_ID_1 + 1);
+    }
+
     #[test]
     fn should_rotate_when_claim_trips() {
         let mut test = PublicationTest::new();
@@ -1100,4 +1245,52 @@ mod tests {
         );
         assert!(test.publication.position() > (initial_position + LENGTH + test.src_buffer.capacity()) as i64);
     }
+
+    #[test]
+    fn should_return_max_position_exceeded_when_last_term_trips() {
+        let test = PublicationTest::new();
+        let initial_term_id = 0;
+        let last_term_count = i32::max_value();
+        let last_term_id = initial_term_id + last_term_count;
+        let active_index = log_buffer_descriptor::index_by_term_count(last_term_count as i64);
+        let initial_position = TERM_MIN_LENGTH - LENGTH;
+
+        test.log_meta_data_buffer
+            .put(*log_buffer_descriptor::LOG_INITIAL_TERM_ID_OFFSET, initial_term_id);
+        test.log_meta_data_buffer
+            .put(*log_buffer_descriptor::LOG_ACTIVE_TERM_COUNT_OFFSET, last_term_count);
+        test.log_meta_data_buffer.put(
+            term_tail_counter_offset(active_index),
+            raw_tail_value(last_term_id, initial_position as i64),
+        );
+        test.publication_limit.set(i64::max_value());
+
+        let mut publication = Publication::new(
+            test.conductor.clone(),
+            (*CHANNEL).clone(),
+            CORRELATION_ID,
+            ORIGINAL_REGISTRATION_ID,
+            STREAM_ID,
+            SESSION_ID,
+            test.publication_limit.clone(),
+            NO_ID_ALLOCATED,
+            test.log_buffers.clone(),
+        );
+
+        let max_possible_position = (TERM_MIN_LENGTH as i64) << 31;
+        assert_eq!(publication.position(), max_possible_position - LENGTH as i64);
+
+        // The result is terminal so repeated offers keep failing without rotating the log
+        for _ in 0..3 {
+            assert_eq!(publication.offer(test.src_buffer).unwrap(), MAX_POSITION_EXCEEDED);
+            assert_eq!(
+                test.log_meta_data_buffer
+                    .get::<i32>(*log_buffer_descriptor::LOG_ACTIVE_TERM_COUNT_OFFSET),
+                last_term_count
+            );
+        }
+
+        let mut buffer_claim = BufferClaim::default();
+        assert_eq!(publication.try_claim(1024, &mut buffer_claim).unwrap(), MAX_POSITION_EXCEEDED);
+    }
 }
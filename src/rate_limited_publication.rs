@@ -0,0 +1,260 @@
+/*
+ * Copyright 2020 UT OVERSEAS INC
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ * https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::sync::{Arc, Mutex};
+
+use crate::{
+    concurrent::{
+        atomic_buffer::AtomicBuffer,
+        logbuffer::term_appender::{default_reserved_value_supplier, OnReservedValueSupplier},
+    },
+    publication::{Publication, BACK_PRESSURED},
+    utils::{
+        errors::AeronError,
+        misc::monotonic_time_ms,
+        types::{Index, Moment},
+    },
+};
+
+// Tokens are kept in thousandths so that a millisecond clock refills rate / 1000 tokens per millisecond exactly.
+const MILLI_TOKENS_PER_TOKEN: u64 = 1000;
+
+/**
+ * Publication wrapper which throttles offers with a token bucket. The bucket holds up to burst messages and is
+ * refilled at messages_per_second based on the monotonic clock. When the bucket is empty offers are rejected with
+ * BACK_PRESSURED without reaching the log so that the driver is not flooded.
+ */
+pub struct RateLimitedPublication {
+    publication: Arc<Mutex<Publication>>,
+    messages_per_second: u64,
+    burst: u64,
+    available_milli_tokens: u64,
+    time_of_last_refill_ms: Moment,
+    clock: Box<dyn Fn() -> Moment>,
+}
+
+impl RateLimitedPublication {
+    /**
+     * Wrap a publication limiting the rate at which messages are offered. The bucket starts full.
+     *
+     * @param publication         to offer messages to.
+     * @param messages_per_second sustained rate at which messages are allowed.
+     * @param burst               number of messages which may be offered back to back.
+     */
+    pub fn new(publication: Arc<Mutex<Publication>>, messages_per_second: u64, burst: u64) -> Self {
+        let burst = std::cmp::max(burst, 1);
+
+        Self {
+            publication,
+            messages_per_second,
+            burst,
+            available_milli_tokens: burst * MILLI_TOKENS_PER_TOKEN,
+            time_of_last_refill_ms: monotonic_time_ms(),
+            clock: Box::new(monotonic_time_ms),
+        }
+    }
+
+    pub fn set_clock_provider(&mut self, new_provider: Box<dyn Fn() -> Moment>) {
+        self.time_of_last_refill_ms = new_provider();
+        self.clock = new_provider;
+    }
+
+    pub fn publication(&self) -> Arc<Mutex<Publication>> {
+        self.publication.clone()
+    }
+
+    pub fn messages_per_second(&self) -> u64 {
+        self.messages_per_second
+    }
+
+    pub fn burst(&self) -> u64 {
+        self.burst
+    }
+
+    /**
+     * Number of messages which can be offered now without being throttled.
+     *
+     * @return number of whole tokens in the bucket.
+     */
+    pub fn available_tokens(&mut self) -> u64 {
+        self.refill();
+        self.available_milli_tokens / MILLI_TOKENS_PER_TOKEN
+    }
+
+    /**
+     * Non-blocking publish of a buffer containing a message if the rate limit allows it.
+     *
+     * @param buffer containing message.
+     * @param offset offset in the buffer at which the encoded message begins.
+     * @param length in bytes of the encoded message.
+     * @param reserved_value_supplier for the frame.
+     * @return The new stream position, otherwise {@link #NOT_CONNECTED}, {@link #BACK_PRESSURED},
+     * {@link #ADMIN_ACTION} or {@link #CLOSED}.
+     */
+    pub fn offer_opt(
+        &mut self,
+        buffer: AtomicBuffer,
+        offset: Index,
+        length: Index,
+        reserved_value_supplier: OnReservedValueSupplier,
+    ) -> Result<i64, AeronError> {
+        self.refill();
+
+        if self.available_milli_tokens < MILLI_TOKENS_PER_TOKEN {
+            return Ok(BACK_PRESSURED);
+        }
+
+        let new_position =
+            self.publication
+                .lock()
+                .expect("Mutex poisoned")
+                .offer_opt(buffer, offset, length, reserved_value_supplier)?;
+
+        if new_position > 0 {
+            self.available_milli_tokens -= MILLI_TOKENS_PER_TOKEN;
+        }
+
+        Ok(new_position)
+    }
+
+    /**
+     * Non-blocking publish of a buffer containing a message if the rate limit allows it.
+     *
+     * @param buffer containing message.
+     * @param offset offset in the buffer at which the encoded message begins.
+     * @param length in bytes of the encoded message.
+     * @return The new stream position, otherwise {@link #NOT_CONNECTED}, {@link #BACK_PRESSURED},
+     * {@link #ADMIN_ACTION} or {@link #CLOSED}.
+     */
+    pub fn offer_part(&mut self, buffer: AtomicBuffer, offset: Index, length: Index) -> Result<i64, AeronError> {
+        self.offer_opt(buffer, offset, length, default_reserved_value_supplier)
+    }
+
+    /**
+     * Non-blocking publish of a buffer containing a message if the rate limit allows it.
+     *
+     * @param buffer containing message.
+     * @return The new stream position on success, otherwise {@link BACK_PRESSURED} or {@link NOT_CONNECTED}.
+     */
+    pub fn offer(&mut self, buffer: AtomicBuffer) -> Result<i64, AeronError> {
+        self.offer_part(buffer, 0, buffer.capacity())
+    }
+
+    fn refill(&mut self) {
+        let now_ms = (self.clock)();
+
+        if now_ms > self.time_of_last_refill_ms {
+            let elapsed_ms = now_ms - self.time_of_last_refill_ms;
+            let max_milli_tokens = self.burst * MILLI_TOKENS_PER_TOKEN;
+
+            self.available_milli_tokens = std::cmp::min(
+                max_milli_tokens,
+                self.available_milli_tokens
+                    .saturating_add(elapsed_ms.saturating_mul(self.messages_per_second)),
+            );
+            self.time_of_last_refill_ms = now_ms;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    use crate::{
+        concurrent::{logbuffer::log_buffer_descriptor::TERM_MIN_LENGTH, position::ReadablePosition},
+        publication::publication_test::PublicationTest,
+    };
+
+    use super::*;
+
+    const MSG_LENGTH: Index = 32;
+
+    struct RateLimitedPublicationTest {
+        test: PublicationTest,
+        current_time: Arc<AtomicU64>,
+        publication: RateLimitedPublication,
+    }
+
+    impl RateLimitedPublicationTest {
+        pub fn new(messages_per_second: u64, burst: u64) -> Self {
+            let test = PublicationTest::new();
+            test.publication_limit.set(i64::from(TERM_MIN_LENGTH));
+
+            let publication = Arc::new(Mutex::new(test.new_publication()));
+
+            let current_time = Arc::new(AtomicU64::new(0));
+            let clock = current_time.clone();
+            let mut publication = RateLimitedPublication::new(publication, messages_per_second, burst);
+            publication.set_clock_provider(Box::new(move || clock.load(Ordering::SeqCst)));
+
+            Self {
+                test,
+                current_time,
+                publication,
+            }
+        }
+
+        fn advance_time(&self, delta_ms: Moment) {
+            self.current_time.fetch_add(delta_ms, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn should_back_pressure_offers_above_the_rate_limit() {
+        let mut test = RateLimitedPublicationTest::new(10, 5);
+
+        let results: Vec<i64> = (0..10)
+            .map(|_| test.publication.offer_part(test.test.src_buffer, 0, MSG_LENGTH).unwrap())
+            .collect();
+
+        assert!(results[..5].iter().all(|position| *position > 0));
+        assert!(results[5..].iter().all(|position| *position == BACK_PRESSURED));
+        assert_eq!(test.publication.available_tokens(), 0);
+
+        // 10 messages per second refill one token every 100ms
+        test.advance_time(99);
+        assert_eq!(
+            test.publication.offer_part(test.test.src_buffer, 0, MSG_LENGTH).unwrap(),
+            BACK_PRESSURED
+        );
+        test.advance_time(1);
+        assert!(test.publication.offer_part(test.test.src_buffer, 0, MSG_LENGTH).unwrap() > 0);
+        assert_eq!(
+            test.publication.offer_part(test.test.src_buffer, 0, MSG_LENGTH).unwrap(),
+            BACK_PRESSURED
+        );
+    }
+
+    #[test]
+    fn should_accept_all_offers_below_the_rate_limit() {
+        let mut test = RateLimitedPublicationTest::new(1000, 1);
+
+        for _ in 0..100 {
+            assert!(test.publication.offer_part(test.test.src_buffer, 0, MSG_LENGTH).unwrap() > 0);
+            test.advance_time(1);
+        }
+    }
+
+    #[test]
+    fn should_not_refill_above_burst() {
+        let mut test = RateLimitedPublicationTest::new(1000, 3);
+
+        test.advance_time(10_000);
+
+        assert_eq!(test.publication.available_tokens(), 3);
+    }
+}
@@ -0,0 +1,202 @@
+/*
+ * Copyright 2020 UT OVERSEAS INC
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ * https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use crate::{
+    concurrent::{atomic_buffer::AtomicBuffer, logbuffer::header::Header},
+    image::{ControlledFragment, ControlledPollAction},
+    utils::{errors::AeronError, types::Index},
+};
+
+/**
+ * Testing aid which sits in front of a controlled fragment handler and records the (offset, length, action)
+ * of every fragment the delegate handled, so that tests can assert exactly how a controlled poll progressed.
+ * Fragments for which the delegate returned an error are not recorded.
+ */
+pub struct RecordingControlledHandler<T: ControlledFragment> {
+    delegate: T,
+    recorded: Vec<(Index, Index, ControlledPollAction)>,
+}
+
+impl<T: ControlledFragment> RecordingControlledHandler<T> {
+    /**
+     * Construct a recording handler delegating to the supplied handler.
+     *
+     * @param delegate handler whose decisions are recorded.
+     */
+    pub fn new(delegate: T) -> Self {
+        Self {
+            delegate,
+            recorded: Vec::new(),
+        }
+    }
+
+    /**
+     * Compose a controlled fragment handler that calls this RecordingControlledHandler. Suitable for passing to
+     * Image::controlled_poll or Subscription::controlled_poll.
+     *
+     * @return controlled fragment handler composed with the RecordingControlledHandler instance.
+     */
+    pub fn handler(&mut self) -> impl ControlledFragment + '_ {
+        move |buffer: &AtomicBuffer, offset, length, header: &Header| self.on_fragment(buffer, offset, length, header)
+    }
+
+    /**
+     * The (offset, length, action) of each fragment handled so far, in the order they were handled.
+     *
+     * @return the recorded decisions.
+     */
+    pub fn recorded(&self) -> &[(Index, Index, ControlledPollAction)] {
+        &self.recorded
+    }
+
+    /**
+     * Forget all the decisions recorded so far.
+     */
+    pub fn clear(&mut self) {
+        self.recorded.clear();
+    }
+
+    fn on_fragment(
+        &mut self,
+        buffer: &AtomicBuffer,
+        offset: Index,
+        length: Index,
+        header: &Header,
+    ) -> Result<ControlledPollAction, AeronError> {
+        let result = (self.delegate)(buffer, offset, length, header);
+
+        if let Ok(action) = result {
+            self.recorded.push((offset, length, action));
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::CString;
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::{
+        concurrent::{
+            atomic_buffer::AlignedBuffer,
+            logbuffer::{
+                data_frame_header::{self, DataFrameHeaderDefn},
+                frame_descriptor, log_buffer_descriptor,
+            },
+            position::{ReadablePosition, UnsafeBufferPosition},
+        },
+        image::Image,
+        utils::{bit_utils::align, log_buffers::LogBuffers},
+    };
+
+    const TERM_LENGTH: Index = log_buffer_descriptor::TERM_MIN_LENGTH;
+    const LOG_BUFFER_LENGTH: Index = TERM_LENGTH * 3 + log_buffer_descriptor::LOG_META_DATA_LENGTH;
+    const COUNTER_VALUES_BUFFER_LENGTH: Index = 1024;
+    const INITIAL_TERM_ID: i32 = 7;
+    const STREAM_ID: i32 = 10;
+    const SESSION_ID: i32 = 200;
+    const PAYLOAD_LENGTH: Index = 32;
+
+    fn error_handler(err: AeronError) {
+        println!("error_handler: {:?}", err)
+    }
+
+    fn insert_fragment(term_buffer: &AtomicBuffer, offset: Index, flags: u8) {
+        let frame = term_buffer.overlay_struct::<DataFrameHeaderDefn>(offset);
+
+        unsafe {
+            (*frame).frame_length = data_frame_header::LENGTH + PAYLOAD_LENGTH;
+            (*frame).version = data_frame_header::CURRENT_VERSION;
+            (*frame).flags = flags;
+            (*frame).frame_type = data_frame_header::HDR_TYPE_DATA;
+            (*frame).term_offset = offset;
+            (*frame).session_id = SESSION_ID;
+            (*frame).stream_id = STREAM_ID;
+            (*frame).term_id = INITIAL_TERM_ID;
+        }
+    }
+
+    #[test]
+    fn should_record_decisions_across_multi_fragment_message() {
+        let log_buf = AlignedBuffer::with_capacity(LOG_BUFFER_LENGTH);
+        let cnt_buf = AlignedBuffer::with_capacity(COUNTER_VALUES_BUFFER_LENGTH);
+        let log_buffers = Arc::new(unsafe { LogBuffers::new(log_buf.ptr, log_buf.len as isize, TERM_LENGTH) });
+        let log_meta_data_buffer = log_buffers.atomic_buffer(log_buffer_descriptor::LOG_META_DATA_SECTION_INDEX);
+        log_meta_data_buffer.put::<i32>(*log_buffer_descriptor::LOG_TERM_LENGTH_OFFSET, TERM_LENGTH);
+        log_meta_data_buffer.put::<i32>(*log_buffer_descriptor::LOG_INITIAL_TERM_ID_OFFSET, INITIAL_TERM_ID);
+
+        let subscriber_position = UnsafeBufferPosition::new(AtomicBuffer::from_aligned(&cnt_buf), 0);
+        subscriber_position.set(0);
+
+        let mut image = Image::create(
+            SESSION_ID,
+            100,
+            99,
+            CString::new("test").unwrap(),
+            &subscriber_position,
+            log_buffers.clone(),
+            error_handler,
+        );
+
+        let term_buffer = log_buffers.atomic_buffer(0);
+        let aligned_length = align(data_frame_header::LENGTH + PAYLOAD_LENGTH, frame_descriptor::FRAME_ALIGNMENT);
+        insert_fragment(&term_buffer, 0, frame_descriptor::BEGIN_FRAG);
+        insert_fragment(&term_buffer, aligned_length, 0);
+        insert_fragment(&term_buffer, aligned_length * 2, frame_descriptor::END_FRAG);
+
+        // Abort the END fragment the first time it is seen and commit it when it is redelivered.
+        let mut end_seen = false;
+        let delegate = |_buffer: &AtomicBuffer, _offset: Index, _length: Index, header: &Header| {
+            if header.flags() & frame_descriptor::END_FRAG == frame_descriptor::END_FRAG {
+                if end_seen {
+                    return Ok(ControlledPollAction::COMMIT);
+                }
+                end_seen = true;
+                return Ok(ControlledPollAction::ABORT);
+            }
+
+            Ok(ControlledPollAction::CONTINUE)
+        };
+
+        let mut recorder = RecordingControlledHandler::new(delegate);
+
+        assert_eq!(image.controlled_poll(recorder.handler(), 10), 2);
+        assert_eq!(subscriber_position.get(), (aligned_length * 2) as i64);
+        assert_eq!(image.controlled_poll(recorder.handler(), 10), 1);
+        assert_eq!(subscriber_position.get(), (aligned_length * 3) as i64);
+
+        let first_offset = data_frame_header::LENGTH;
+        assert_eq!(
+            recorder.recorded(),
+            &[
+                (first_offset, PAYLOAD_LENGTH, ControlledPollAction::CONTINUE),
+                (first_offset + aligned_length, PAYLOAD_LENGTH, ControlledPollAction::CONTINUE),
+                (first_offset + aligned_length * 2, PAYLOAD_LENGTH, ControlledPollAction::ABORT),
+                (
+                    first_offset + aligned_length * 2,
+                    PAYLOAD_LENGTH,
+                    ControlledPollAction::COMMIT
+                ),
+            ]
+        );
+
+        recorder.clear();
+        assert!(recorder.recorded().is_empty());
+    }
+}
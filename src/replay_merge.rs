@@ -0,0 +1,263 @@
+/*
+ * Copyright 2020 UT OVERSEAS INC
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ * https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use crate::{
+    concurrent::{atomic_buffer::AtomicBuffer, logbuffer::header::Header},
+    image::Image,
+    utils::{errors::AeronError, types::Index},
+};
+
+/**
+ * Presents a replay Image followed by a live Image of the same stream as a single ordered stream of messages.
+ * <p>
+ * The replay is consumed up to the position at which the live Image was joined and consumption then continues
+ * from the live Image, so no message is lost or delivered twice at the join. Both Images must share the stream
+ * positions of the original recording, as a replay from an archive does. The live Image must not be polled
+ * elsewhere while the merge is in progress.
+ */
+pub struct ReplayMerge {
+    replay_image: Image,
+    live_image: Image,
+    is_merged: bool,
+}
+
+impl ReplayMerge {
+    /**
+     * Construct a merge of a replay and the live stream it catches up with.
+     *
+     * @param replay_image from which messages before the join position are consumed.
+     * @param live_image   joined at the position from which messages are consumed once the replay catches up.
+     */
+    pub fn new(replay_image: Image, live_image: Image) -> Self {
+        Self {
+            replay_image,
+            live_image,
+            is_merged: false,
+        }
+    }
+
+    /**
+     * Poll the replay Image up to the join position, or the live Image once the replay has reached it, delivering
+     * fragments to the handler in stream order.
+     *
+     * @param fragment_handler to which message fragments are delivered.
+     * @param fragment_limit   for the number of fragments to be consumed during one polling operation.
+     * @return the number of fragments consumed or an error if the live Image could not be moved to the join.
+     */
+    pub fn poll(
+        &mut self,
+        fragment_handler: &mut impl FnMut(&AtomicBuffer, Index, Index, &Header),
+        fragment_limit: i32,
+    ) -> Result<i32, AeronError> {
+        if self.is_merged {
+            return Ok(self.live_image.poll(fragment_handler, fragment_limit));
+        }
+
+        let join_position = self.live_image.position();
+        let mut fragments_read = self.replay_image.bounded_poll(
+            |buffer: &AtomicBuffer, offset: Index, length: Index, header: &Header| {
+                fragment_handler(buffer, offset, length, header)
+            },
+            join_position,
+            fragment_limit,
+        );
+
+        let replay_position = self.replay_image.position();
+        if replay_position >= join_position {
+            if replay_position > join_position {
+                // The last replayed fragment ended past the join, so it must not be delivered again from live.
+                self.live_image.set_position(replay_position)?;
+            }
+            self.is_merged = true;
+
+            if fragments_read < fragment_limit {
+                fragments_read += self.live_image.poll(fragment_handler, fragment_limit - fragments_read);
+            }
+        }
+
+        Ok(fragments_read)
+    }
+
+    /**
+     * Has the replay caught up with the live stream so that messages are now consumed from the live Image?
+     *
+     * @return true once consumption has switched to the live Image.
+     */
+    pub fn is_merged(&self) -> bool {
+        self.is_merged
+    }
+
+    /**
+     * The position of the merged stream, i.e. of the replay Image before the merge and of the live Image after.
+     *
+     * @return the position consumed to.
+     */
+    pub fn position(&self) -> i64 {
+        if self.is_merged {
+            self.live_image.position()
+        } else {
+            self.replay_image.position()
+        }
+    }
+
+    pub fn replay_image(&self) -> &Image {
+        &self.replay_image
+    }
+
+    pub fn live_image(&self) -> &Image {
+        &self.live_image
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::CString;
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::{
+        concurrent::{
+            atomic_buffer::AlignedBuffer,
+            logbuffer::{
+                data_frame_header::{self, DataFrameHeaderDefn},
+                frame_descriptor, log_buffer_descriptor,
+            },
+            position::{ReadablePosition, UnsafeBufferPosition},
+        },
+        utils::{bit_utils::align, log_buffers::LogBuffers},
+    };
+
+    const TERM_LENGTH: Index = log_buffer_descriptor::TERM_MIN_LENGTH;
+    const LOG_BUFFER_LENGTH: Index = TERM_LENGTH * 3 + log_buffer_descriptor::LOG_META_DATA_LENGTH;
+    const COUNTER_VALUES_BUFFER_LENGTH: Index = 1024;
+    const INITIAL_TERM_ID: i32 = 7;
+    const STREAM_ID: i32 = 10;
+    const SESSION_ID: i32 = 200;
+    const PAYLOAD_LENGTH: Index = 32;
+    const REPLAY_POSITION_ID: i32 = 0;
+    const LIVE_POSITION_ID: i32 = 1;
+
+    fn error_handler(err: AeronError) {
+        println!("error_handler: {:?}", err)
+    }
+
+    fn aligned_frame_length() -> Index {
+        align(data_frame_header::LENGTH + PAYLOAD_LENGTH, frame_descriptor::FRAME_ALIGNMENT)
+    }
+
+    // A stream whose message n is at position n * aligned_frame_length() and carries n as its first payload byte.
+    fn create_image(
+        log_buf: &AlignedBuffer,
+        subscriber_position: &UnsafeBufferPosition,
+        join_position: i64,
+        messages: std::ops::Range<u8>,
+    ) -> Image {
+        let log_buffers = Arc::new(unsafe { LogBuffers::new(log_buf.ptr, log_buf.len as isize, TERM_LENGTH) });
+        let log_meta_data_buffer = log_buffers.atomic_buffer(log_buffer_descriptor::LOG_META_DATA_SECTION_INDEX);
+        log_meta_data_buffer.put::<i32>(*log_buffer_descriptor::LOG_TERM_LENGTH_OFFSET, TERM_LENGTH);
+        log_meta_data_buffer.put::<i32>(*log_buffer_descriptor::LOG_INITIAL_TERM_ID_OFFSET, INITIAL_TERM_ID);
+
+        let term_buffer = log_buffers.atomic_buffer(0);
+        for message in messages {
+            let offset = message as Index * aligned_frame_length();
+            let frame = term_buffer.overlay_struct::<DataFrameHeaderDefn>(offset);
+
+            unsafe {
+                (*frame).frame_length = data_frame_header::LENGTH + PAYLOAD_LENGTH;
+                (*frame).version = data_frame_header::CURRENT_VERSION;
+                (*frame).flags = frame_descriptor::UNFRAGMENTED;
+                (*frame).frame_type = data_frame_header::HDR_TYPE_DATA;
+                (*frame).term_offset = offset;
+                (*frame).session_id = SESSION_ID;
+                (*frame).stream_id = STREAM_ID;
+                (*frame).term_id = INITIAL_TERM_ID;
+            }
+            term_buffer.put::<u8>(offset + data_frame_header::LENGTH, message);
+        }
+
+        subscriber_position.set(join_position);
+
+        Image::create(
+            SESSION_ID,
+            100,
+            99,
+            CString::new("test").unwrap(),
+            subscriber_position,
+            log_buffers,
+            error_handler,
+        )
+    }
+
+    #[test]
+    fn should_deliver_replay_then_live_without_gap_or_duplicate() {
+        let replay_log_buf = AlignedBuffer::with_capacity(LOG_BUFFER_LENGTH);
+        let live_log_buf = AlignedBuffer::with_capacity(LOG_BUFFER_LENGTH);
+        let cnt_buf = AlignedBuffer::with_capacity(COUNTER_VALUES_BUFFER_LENGTH);
+        let replay_position = UnsafeBufferPosition::new(AtomicBuffer::from_aligned(&cnt_buf), REPLAY_POSITION_ID);
+        let live_position = UnsafeBufferPosition::new(AtomicBuffer::from_aligned(&cnt_buf), LIVE_POSITION_ID);
+
+        // The recording holds messages 0..5 and the live stream was joined at message 3 and has received up to 6.
+        let join_position = 3 * aligned_frame_length() as i64;
+        let replay_image = create_image(&replay_log_buf, &replay_position, 0, 0..5);
+        let live_image = create_image(&live_log_buf, &live_position, join_position, 3..7);
+
+        let mut merge = ReplayMerge::new(replay_image, live_image);
+        let mut received = vec![];
+        let mut handler = |buffer: &AtomicBuffer, offset: Index, _length: Index, _header: &Header| {
+            received.push(buffer.get::<u8>(offset));
+        };
+
+        assert_eq!(merge.poll(&mut handler, 2).unwrap(), 2);
+        assert!(!merge.is_merged());
+        assert_eq!(merge.position(), 2 * aligned_frame_length() as i64);
+
+        assert_eq!(merge.poll(&mut handler, 2).unwrap(), 2);
+        assert!(merge.is_merged());
+        assert_eq!(live_position.get(), 4 * aligned_frame_length() as i64);
+
+        assert_eq!(merge.poll(&mut handler, 10).unwrap(), 3);
+        assert_eq!(merge.position(), 7 * aligned_frame_length() as i64);
+        assert_eq!(replay_position.get(), join_position);
+
+        drop(handler);
+        assert_eq!(received, vec![0, 1, 2, 3, 4, 5, 6]);
+    }
+
+    #[test]
+    fn should_switch_to_live_within_one_poll_when_replay_reaches_join() {
+        let replay_log_buf = AlignedBuffer::with_capacity(LOG_BUFFER_LENGTH);
+        let live_log_buf = AlignedBuffer::with_capacity(LOG_BUFFER_LENGTH);
+        let cnt_buf = AlignedBuffer::with_capacity(COUNTER_VALUES_BUFFER_LENGTH);
+        let replay_position = UnsafeBufferPosition::new(AtomicBuffer::from_aligned(&cnt_buf), REPLAY_POSITION_ID);
+        let live_position = UnsafeBufferPosition::new(AtomicBuffer::from_aligned(&cnt_buf), LIVE_POSITION_ID);
+
+        let join_position = 2 * aligned_frame_length() as i64;
+        let replay_image = create_image(&replay_log_buf, &replay_position, 0, 0..2);
+        let live_image = create_image(&live_log_buf, &live_position, join_position, 2..4);
+
+        let mut merge = ReplayMerge::new(replay_image, live_image);
+        let mut received = vec![];
+        let mut handler = |buffer: &AtomicBuffer, offset: Index, _length: Index, _header: &Header| {
+            received.push(buffer.get::<u8>(offset));
+        };
+
+        assert_eq!(merge.poll(&mut handler, 10).unwrap(), 4);
+        assert!(merge.is_merged());
+
+        drop(handler);
+        assert_eq!(received, vec![0, 1, 2, 3]);
+    }
+}
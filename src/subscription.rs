@@ -15,6 +15,7 @@
  */
 
 use std::{
+    borrow::Cow,
     ffi::CString,
     sync::{
         atomic::{AtomicBool, Ordering},
@@ -27,7 +28,10 @@ use crate::{
     concurrent::{
         atomic_buffer::AtomicBuffer,
         atomic_vec::AtomicVec,
-        logbuffer::{header::Header, term_scan::BlockHandler},
+        logbuffer::{
+            header::{Header, HeaderSnapshot},
+            term_scan::BlockHandler,
+        },
         status::status_indicator_reader,
     },
     image::{ControlledPollAction, Image},
@@ -76,6 +80,16 @@ impl Subscription {
         self.channel.clone()
     }
 
+    /**
+     * Media address for delivery to the channel as a string, e.g. for logging or re-adding destinations.
+     * Invalid UTF-8 sequences are replaced with U+FFFD rather than failing.
+     *
+     * @return Media address for delivery to the channel.
+     */
+    pub fn channel_str(&self) -> Cow<'_, str> {
+        self.channel.to_string_lossy()
+    }
+
     /**
      * Stream identity for scoping within the channel media address.
      *
@@ -192,6 +206,48 @@ impl Subscription {
      */
 
     pub fn poll(&mut self, fragment_handler: &mut impl FnMut(&AtomicBuffer, Index, Index, &Header), fragment_limit: i32) -> i32 {
+        self.poll_with_image(
+            &mut |_image: &Image, buffer: &AtomicBuffer, offset: Index, length: Index, header: &Header| {
+                fragment_handler(buffer, offset, length, header)
+            },
+            fragment_limit,
+        )
+    }
+
+    /**
+     * Poll the Image s under the subscription and copy each message fragment out so they can be consumed with
+     * an Iterator. The iterator yields the fragments delivered by a single poll and then ends. Fragments are not
+     * reassembled, to consume messages spanning multiple fragments use a FragmentAssembler with poll instead.
+     *
+     * @param fragment_limit number of message fragments to limit for the poll across multiple Image s.
+     * @return iterator over the copied payload and header of each fragment received.
+     */
+    pub fn fragments(&mut self, fragment_limit: i32) -> impl Iterator<Item = (Vec<u8>, HeaderSnapshot)> {
+        let mut fragments = Vec::new();
+
+        self.poll(
+            &mut |buffer: &AtomicBuffer, offset: Index, length: Index, header: &Header| {
+                fragments.push((buffer.as_sub_slice(offset, length).to_vec(), header.snapshot()));
+            },
+            fragment_limit,
+        );
+
+        fragments.into_iter()
+    }
+
+    /**
+     * Poll the Image s under the subscription for available message fragments passing the Image each fragment
+     * was read from to the handler. This allows messages from multiple publishers to be routed per source.
+     *
+     * @param fragment_handler callback for handling each message fragment together with its source Image.
+     * @param fragment_limit   number of message fragments to limit for the poll across multiple Image s.
+     * @return the number of fragments received
+     */
+    pub fn poll_with_image(
+        &mut self,
+        fragment_handler: &mut impl FnMut(&Image, &AtomicBuffer, Index, Index, &Header),
+        fragment_limit: i32,
+    ) -> i32 {
         let image_list = self.image_list.load_mut();
 
         let mut fragments_read = 0;
@@ -209,7 +265,7 @@ impl Subscription {
                 fragments_read += image_list
                     .get_mut(i)
                     .expect("Error getting element from Image vec")
-                    .poll(fragment_handler, fragment_limit - fragments_read);
+                    .poll_with_image(fragment_handler, fragment_limit - fragments_read);
             }
         }
 
@@ -218,13 +274,70 @@ impl Subscription {
                 fragments_read += image_list
                     .get_mut(i)
                     .expect("Error getting element from Image vec")
-                    .poll(fragment_handler, fragment_limit - fragments_read);
+                    .poll_with_image(fragment_handler, fragment_limit - fragments_read);
             }
         }
 
         fragments_read
     }
 
+    /**
+     * Poll the Image s under the subscription for available message fragments using a fragment limit
+     * which adapts to the recent yield of polls.
+     *
+     * @param fragment_handler callback for handling each message fragment as it is read.
+     * @param limit            adaptive fragment limit which is used for this poll and then updated with its result.
+     * @return the number of fragments received
+     */
+    pub fn poll_adaptive(
+        &mut self,
+        fragment_handler: &mut impl FnMut(&AtomicBuffer, Index, Index, &Header),
+        limit: &mut AdaptivePollLimit,
+    ) -> i32 {
+        let fragments_read = self.poll(fragment_handler, limit.limit());
+        limit.on_poll(fragments_read);
+        fragments_read
+    }
+
+    /**
+     * Keep polling the Image s under the subscription until every one of them has delivered its final message
+     * and reached End of Stream, or until the stop condition becomes true.
+     *
+     * @param fragment_handler callback for handling each message fragment as it is read.
+     * @param fragment_limit   number of message fragments to limit for each poll across multiple Image s.
+     * @param should_stop      called after each poll with the number of fragments it read, returns true to stop.
+     * @return the total number of fragments received
+     */
+    pub fn poll_until(
+        &mut self,
+        fragment_handler: &mut impl FnMut(&AtomicBuffer, Index, Index, &Header),
+        fragment_limit: i32,
+        mut should_stop: impl FnMut(i32) -> bool,
+    ) -> i64 {
+        let mut total_fragments_read: i64 = 0;
+
+        loop {
+            let fragments_read = self.poll(fragment_handler, fragment_limit);
+            total_fragments_read += fragments_read as i64;
+
+            if self.all_images_reached_end_of_stream() || should_stop(fragments_read) {
+                break;
+            }
+        }
+
+        total_fragments_read
+    }
+
+    /**
+     * Have all Image s under the subscription been consumed up to End of Stream?
+     *
+     * @return true if there is at least one Image and all of them have reached End of Stream.
+     */
+    pub fn all_images_reached_end_of_stream(&self) -> bool {
+        let image_list = self.image_list.load();
+        !image_list.is_empty() && image_list.iter().all(|image| image.reached_end_of_stream())
+    }
+
     /**
      * Poll in a controlled manner the Image s under the subscription for available message fragments.
      * Control is applied to fragments in the stream. If more fragments can be read on another stream
@@ -417,3 +530,497 @@ impl Drop for Subscription {
 }
 
 type EndOfStreamHandler = fn(&Image);
+
+/**
+ * Fragment limit which tunes itself to the yield of recent polls. When a poll reads as many fragments as
+ * the limit allows the limit is doubled, when a poll reads nothing the limit is halved. The limit always
+ * stays within configured bounds.
+ */
+#[derive(Debug, Copy, Clone)]
+pub struct AdaptivePollLimit {
+    min_limit: i32,
+    max_limit: i32,
+    limit: i32,
+}
+
+impl AdaptivePollLimit {
+    /**
+     * Create a new adaptive limit starting at the lower bound.
+     *
+     * @param min_limit lower bound for the fragment limit, at least 1.
+     * @param max_limit upper bound for the fragment limit, at least min_limit.
+     */
+    pub fn new(min_limit: i32, max_limit: i32) -> Self {
+        let min_limit = min_limit.max(1);
+        let max_limit = max_limit.max(min_limit);
+
+        Self {
+            min_limit,
+            max_limit,
+            limit: min_limit,
+        }
+    }
+
+    /**
+     * Fragment limit to be used for the next poll.
+     *
+     * @return fragment limit to be used for the next poll.
+     */
+    pub fn limit(&self) -> i32 {
+        self.limit
+    }
+
+    pub fn min_limit(&self) -> i32 {
+        self.min_limit
+    }
+
+    pub fn max_limit(&self) -> i32 {
+        self.max_limit
+    }
+
+    /**
+     * Update the limit with the number of fragments read by the last poll.
+     *
+     * @param fragments_read number of fragments read by the last poll.
+     */
+    pub fn on_poll(&mut self, fragments_read: i32) {
+        if fragments_read >= self.limit {
+            self.limit = self.limit.saturating_mul(2).min(self.max_limit);
+        } else if fragments_read <= 0 {
+            self.limit = (self.limit / 2).max(self.min_limit);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        ffi::CString,
+        sync::{Arc, Mutex},
+        thread,
+    };
+
+    use crate::{
+        client_conductor::ClientConductor,
+        concurrent::{
+            atomic_buffer::{AlignedBuffer, AtomicBuffer},
+            broadcast::{
+                broadcast_buffer_descriptor, broadcast_receiver::BroadcastReceiver,
+                copy_broadcast_receiver::CopyBroadcastReceiver,
+            },
+            counters::CountersReader,
+            logbuffer::{
+                data_frame_header::{self, DataFrameHeaderDefn},
+                frame_descriptor,
+                header::Header,
+                log_buffer_descriptor::{self, AERON_PAGE_MIN_SIZE, TERM_MIN_LENGTH},
+            },
+            position::{ReadablePosition, UnsafeBufferPosition},
+            ring_buffer::{self, ManyToOneRingBuffer},
+        },
+        driver_proxy::DriverProxy,
+        fragment_assembler::FragmentAssembler,
+        image::Image,
+        subscription::{AdaptivePollLimit, Subscription},
+        utils::{
+            bit_utils::align,
+            errors::AeronError,
+            log_buffers::LogBuffers,
+            misc::unix_time_ms,
+            types::{Index, Moment},
+        },
+    };
+
+    const CHANNEL: &str = "aeron:udp?endpoint=localhost:40123";
+    const STREAM_ID: i32 = 10;
+    const REGISTRATION_ID: i64 = 100;
+    const INITIAL_TERM_ID: i32 = 7;
+    const MSG_LENGTH: Index = 17;
+
+    const DRIVER_TIMEOUT_MS: Moment = 10 * 1000;
+    const RESOURCE_LINGER_TIMEOUT_MS: Moment = 5 * 1000;
+    const INTER_SERVICE_TIMEOUT_MS: Moment = 5 * 1000;
+
+    const CAPACITY: i32 = 1024;
+    const MANY_TO_ONE_RING_BUFFER_LENGTH: i32 = CAPACITY + ring_buffer::TRAILER_LENGTH;
+    const BROADCAST_BUFFER_LENGTH: i32 = CAPACITY + broadcast_buffer_descriptor::TRAILER_LENGTH;
+    const COUNTER_VALUES_BUFFER_LENGTH: i32 = 64 * 1024;
+    const LOG_LENGTH: i32 = TERM_MIN_LENGTH * 3 + log_buffer_descriptor::LOG_META_DATA_LENGTH;
+
+    fn on_new_publication_handler(_channel: CString, _stream_id: i32, _session_id: i32, _correlation_id: i64) {}
+
+    fn on_new_subscription_handler(_channel: CString, _stream_id: i32, _correlation_id: i64) {}
+
+    fn error_handler(err: AeronError) {
+        println!("Got error: {:?}", err);
+    }
+
+    fn on_available_counter_handler(_counters_reader: &CountersReader, _registration_id: i64, _counter_id: i32) {}
+
+    fn on_unavailable_counter_handler(_counters_reader: &CountersReader, _registration_id: i64, _counter_id: i32) {}
+
+    fn on_close_client_handler() {}
+
+    #[allow(dead_code)]
+    struct SubscriptionTest {
+        to_driver: AlignedBuffer,
+        to_clients: AlignedBuffer,
+        counter_metadata: AlignedBuffer,
+        counter_values: AlignedBuffer,
+        logs: Vec<AlignedBuffer>,
+        log_buffers: Vec<Arc<LogBuffers>>,
+        conductor: Arc<Mutex<ClientConductor>>,
+        subscription: Subscription,
+    }
+
+    impl SubscriptionTest {
+        pub fn new() -> Self {
+            let to_driver = AlignedBuffer::with_capacity(MANY_TO_ONE_RING_BUFFER_LENGTH);
+            let to_clients = AlignedBuffer::with_capacity(BROADCAST_BUFFER_LENGTH);
+            let counter_metadata = AlignedBuffer::with_capacity(COUNTER_VALUES_BUFFER_LENGTH);
+            let counter_values = AlignedBuffer::with_capacity(COUNTER_VALUES_BUFFER_LENGTH);
+
+            let to_driver_buffer = AtomicBuffer::from_aligned(&to_driver);
+            let to_clients_buffer = AtomicBuffer::from_aligned(&to_clients);
+
+            let to_driver_ring_buffer =
+                Arc::new(ManyToOneRingBuffer::new(to_driver_buffer).expect("Failed to create RingBuffer"));
+            let to_clients_broadcast_receiver = Arc::new(Mutex::new(
+                BroadcastReceiver::new(to_clients_buffer).expect("Failed to create BroadcastReceiver"),
+            ));
+            let driver_proxy = Arc::new(DriverProxy::new(to_driver_ring_buffer));
+            let copy_broadcast_receiver = Arc::new(Mutex::new(CopyBroadcastReceiver::new(to_clients_broadcast_receiver)));
+
+            let conductor = ClientConductor::new(
+                unix_time_ms,
+                driver_proxy,
+                copy_broadcast_receiver,
+                AtomicBuffer::from_aligned(&counter_metadata),
+                AtomicBuffer::from_aligned(&counter_values),
+                on_new_publication_handler,
+                on_new_publication_handler,
+                on_new_subscription_handler,
+                error_handler,
+                on_available_counter_handler,
+                on_unavailable_counter_handler,
+                on_close_client_handler,
+                DRIVER_TIMEOUT_MS,
+                RESOURCE_LINGER_TIMEOUT_MS,
+                INTER_SERVICE_TIMEOUT_MS,
+                false,
+            );
+
+            let subscription = Subscription::new(
+                conductor.clone(),
+                REGISTRATION_ID,
+                CString::new(CHANNEL).unwrap(),
+                STREAM_ID,
+                0,
+            );
+
+            Self {
+                to_driver,
+                to_clients,
+                counter_metadata,
+                counter_values,
+                logs: vec![],
+                log_buffers: vec![],
+                conductor,
+                subscription,
+            }
+        }
+
+        /// Creates an Image over a fresh log buffer and adds it to the subscription.
+        fn add_image(&mut self, session_id: i32) {
+            let log = AlignedBuffer::with_capacity(LOG_LENGTH);
+            let log_buffers = Arc::new(unsafe { LogBuffers::new(log.ptr, log.len as isize, TERM_MIN_LENGTH) });
+            let log_meta_data_buffer = log_buffers.atomic_buffer(log_buffer_descriptor::LOG_META_DATA_SECTION_INDEX);
+
+            log_meta_data_buffer.put::<i32>(*log_buffer_descriptor::LOG_MTU_LENGTH_OFFSET, 1024);
+            log_meta_data_buffer.put::<i32>(*log_buffer_descriptor::LOG_TERM_LENGTH_OFFSET, TERM_MIN_LENGTH);
+            log_meta_data_buffer.put::<i32>(*log_buffer_descriptor::LOG_PAGE_SIZE_OFFSET, AERON_PAGE_MIN_SIZE);
+            log_meta_data_buffer.put::<i32>(*log_buffer_descriptor::LOG_INITIAL_TERM_ID_OFFSET, INITIAL_TERM_ID);
+            log_buffer_descriptor::set_end_of_stream_position(&log_meta_data_buffer, i64::max_value());
+
+            let position = UnsafeBufferPosition::new(AtomicBuffer::from_aligned(&self.counter_values), self.logs.len() as i32);
+            position.set(0);
+
+            let image = Image::create(
+                session_id,
+                session_id as i64,
+                REGISTRATION_ID,
+                CString::new("source").unwrap(),
+                &position,
+                log_buffers.clone(),
+                error_handler,
+            );
+
+            self.subscription.add_image(image);
+            self.logs.push(log);
+            self.log_buffers.push(log_buffers);
+        }
+
+        /// Appends an unfragmented message to the first term of the image log at given index.
+        fn append_message(&self, image_index: usize, frame_index: Index) -> i64 {
+            self.append_fragment(image_index, frame_index, frame_descriptor::UNFRAGMENTED)
+        }
+
+        /// Appends a fragment with given flags to the first term of the image log at given index.
+        fn append_fragment(&self, image_index: usize, frame_index: Index, flags: u8) -> i64 {
+            let term_buffer = self.log_buffers[image_index].atomic_buffer(0);
+            let aligned_length = align(data_frame_header::LENGTH + MSG_LENGTH, frame_descriptor::FRAME_ALIGNMENT);
+            let offset = frame_index * aligned_length;
+            let frame = term_buffer.overlay_struct::<DataFrameHeaderDefn>(offset);
+
+            unsafe {
+                (*frame).frame_length = data_frame_header::LENGTH + MSG_LENGTH;
+                (*frame).version = data_frame_header::CURRENT_VERSION;
+                (*frame).flags = flags;
+                (*frame).frame_type = data_frame_header::HDR_TYPE_DATA;
+                (*frame).term_offset = offset;
+                (*frame).session_id = self.subscription.images()[image_index].session_id();
+                (*frame).stream_id = STREAM_ID;
+                (*frame).term_id = INITIAL_TERM_ID;
+            }
+
+            (offset + aligned_length) as i64
+        }
+
+        fn set_end_of_stream(&self, image_index: usize, position: i64) {
+            let log_meta_data_buffer =
+                self.log_buffers[image_index].atomic_buffer(log_buffer_descriptor::LOG_META_DATA_SECTION_INDEX);
+            log_buffer_descriptor::set_end_of_stream_position(&log_meta_data_buffer, position);
+        }
+    }
+
+    #[test]
+    fn should_report_channel_given_at_creation() {
+        let test = SubscriptionTest::new();
+        assert_eq!(test.subscription.channel(), CString::new(CHANNEL).unwrap());
+        assert_eq!(test.subscription.channel_str(), CHANNEL);
+    }
+
+    #[test]
+    fn should_poll_until_all_images_reach_end_of_stream() {
+        let mut test = SubscriptionTest::new();
+        test.add_image(1);
+        test.add_image(2);
+
+        test.append_message(0, 0);
+        let eos_position_0 = test.append_message(0, 1);
+        let eos_position_1 = test.append_message(1, 0);
+        test.set_end_of_stream(0, eos_position_0);
+        test.set_end_of_stream(1, eos_position_1);
+
+        let mut received = 0;
+        let mut polls = 0;
+        let total = test.subscription.poll_until(
+            &mut |_buffer: &AtomicBuffer, _offset: Index, length: Index, _header: &Header| {
+                assert_eq!(length, MSG_LENGTH);
+                received += 1;
+            },
+            1,
+            |_fragments_read| {
+                polls += 1;
+                polls > 10
+            },
+        );
+
+        assert_eq!(total, 3);
+        assert_eq!(received, 3);
+        assert!(polls < 10);
+        assert!(test.subscription.all_images_reached_end_of_stream());
+        assert!(test.subscription.images().iter().all(|image| image.is_end_of_stream()));
+    }
+
+    #[test]
+    fn should_observe_end_of_stream_set_from_another_thread() {
+        let mut test = SubscriptionTest::new();
+        test.add_image(1);
+        let eos_position = test.append_message(0, 0);
+
+        let log_meta_data_buffer = test.log_buffers[0].atomic_buffer(log_buffer_descriptor::LOG_META_DATA_SECTION_INDEX);
+        let address = log_meta_data_buffer.buffer() as usize;
+        let length = log_meta_data_buffer.capacity();
+        let driver = thread::spawn(move || {
+            let log_meta_data_buffer = AtomicBuffer::new(address as *mut u8, length);
+            log_buffer_descriptor::set_end_of_stream_position(&log_meta_data_buffer, eos_position);
+        });
+
+        let max_polls = 10_000_000;
+        let mut polls = 0;
+        let total = test.subscription.poll_until(
+            &mut |_buffer: &AtomicBuffer, _offset: Index, _length: Index, _header: &Header| {},
+            10,
+            |_fragments_read| {
+                polls += 1;
+                thread::yield_now();
+                polls >= max_polls
+            },
+        );
+        driver.join().unwrap();
+
+        assert_eq!(total, 1);
+        assert!(polls < max_polls);
+        assert!(test.subscription.all_images_reached_end_of_stream());
+    }
+
+    #[test]
+    fn should_reassemble_fragmented_messages_when_polled_through_fragment_assembler() {
+        let mut test = SubscriptionTest::new();
+        test.add_image(1);
+
+        test.append_fragment(0, 0, frame_descriptor::BEGIN_FRAG);
+        test.append_fragment(0, 1, 0);
+        test.append_fragment(0, 2, frame_descriptor::END_FRAG);
+        test.append_message(0, 3);
+
+        let mut lengths = vec![];
+        let mut fragment_handler = |_buffer: &AtomicBuffer, _offset: Index, length: Index, _header: &Header| {
+            lengths.push(length);
+        };
+        let mut assembler = FragmentAssembler::new(&mut fragment_handler, None);
+
+        assert_eq!(assembler.poll(&mut test.subscription, 2).unwrap(), 2);
+        assert_eq!(assembler.poll(&mut test.subscription, 10).unwrap(), 2);
+        assert_eq!(assembler.poll(&mut test.subscription, 10).unwrap(), 0);
+        drop(assembler);
+
+        assert_eq!(lengths, vec![MSG_LENGTH * 3, MSG_LENGTH]);
+    }
+
+    #[test]
+    fn should_stop_poll_until_when_stop_condition_is_met() {
+        let mut test = SubscriptionTest::new();
+        test.add_image(1);
+        test.append_message(0, 0);
+
+        let mut polls = 0;
+        let total = test.subscription.poll_until(
+            &mut |_buffer: &AtomicBuffer, _offset: Index, _length: Index, _header: &Header| {},
+            10,
+            |_fragments_read| {
+                polls += 1;
+                polls == 3
+            },
+        );
+
+        assert_eq!(total, 1);
+        assert_eq!(polls, 3);
+        assert!(!test.subscription.all_images_reached_end_of_stream());
+    }
+
+    #[test]
+    fn should_start_at_min_limit_and_normalise_bounds() {
+        let limit = AdaptivePollLimit::new(0, -5);
+
+        assert_eq!(limit.min_limit(), 1);
+        assert_eq!(limit.max_limit(), 1);
+        assert_eq!(limit.limit(), 1);
+    }
+
+    #[test]
+    fn should_grow_up_to_max_limit_on_bursty_sequence() {
+        let mut limit = AdaptivePollLimit::new(2, 20);
+        let mut observed = vec![];
+
+        for _ in 0..6 {
+            let fragments_read = limit.limit(); // every poll saturates the limit
+            limit.on_poll(fragments_read);
+            observed.push(limit.limit());
+        }
+
+        assert_eq!(observed, vec![4, 8, 16, 20, 20, 20]);
+    }
+
+    #[test]
+    fn should_shrink_down_to_min_limit_on_idle_sequence() {
+        let mut limit = AdaptivePollLimit::new(3, 64);
+
+        for _ in 0..5 {
+            limit.on_poll(limit.limit());
+        }
+        assert_eq!(limit.limit(), 64);
+
+        let mut observed = vec![];
+        for _ in 0..6 {
+            limit.on_poll(0);
+            observed.push(limit.limit());
+        }
+
+        assert_eq!(observed, vec![32, 16, 8, 4, 3, 3]);
+    }
+
+    #[test]
+    fn should_keep_limit_when_poll_is_partially_filled() {
+        let mut limit = AdaptivePollLimit::new(1, 100);
+        limit.on_poll(1);
+        limit.on_poll(2);
+        assert_eq!(limit.limit(), 4);
+
+        limit.on_poll(3);
+        assert_eq!(limit.limit(), 4);
+    }
+
+    #[test]
+    fn should_stay_within_bounds_on_mixed_sequence() {
+        let mut limit = AdaptivePollLimit::new(4, 32);
+
+        for fragments_read in &[4, 8, 0, 100, 16, 32, 0, 0, 0, 0, 0, 7, 4] {
+            limit.on_poll(*fragments_read);
+            assert!(limit.limit() >= 4 && limit.limit() <= 32);
+        }
+    }
+
+    #[test]
+    fn should_pass_source_image_with_each_message() {
+        let mut test = SubscriptionTest::new();
+        test.add_image(1);
+        test.add_image(2);
+
+        test.append_message(0, 0);
+        test.append_message(0, 1);
+        test.append_message(1, 0);
+
+        let mut sources = Vec::new();
+        let fragments_read = test.subscription.poll_with_image(
+            &mut |image: &Image, _buffer: &AtomicBuffer, _offset: Index, length: Index, header: &Header| {
+                assert_eq!(length, MSG_LENGTH);
+                assert_eq!(image.session_id(), header.session_id());
+                sources.push(image.correlation_id());
+            },
+            10,
+        );
+
+        assert_eq!(fragments_read, 3);
+        sources.sort_unstable();
+        assert_eq!(sources, vec![1, 1, 2]);
+    }
+
+    #[test]
+    fn should_iterate_fragments_from_a_single_poll() {
+        let mut test = SubscriptionTest::new();
+        test.add_image(1);
+        test.add_image(2);
+
+        test.append_message(0, 0);
+        let position = test.append_message(0, 1);
+        test.append_message(1, 0);
+
+        let fragments: Vec<_> = test.subscription.fragments(10).collect();
+
+        assert_eq!(fragments.len(), 3);
+        assert!(fragments.iter().all(|(payload, header)| payload.len() as Index == MSG_LENGTH
+            && header.frame_length == data_frame_header::LENGTH + MSG_LENGTH
+            && header.stream_id == STREAM_ID));
+        assert_eq!(
+            fragments.iter().filter(|(_payload, header)| header.session_id == 1).count(),
+            2
+        );
+        assert!(fragments
+            .iter()
+            .any(|(_payload, header)| header.session_id == 1 && header.position == position));
+
+        assert_eq!(test.subscription.fragments(10).count(), 0);
+    }
+}
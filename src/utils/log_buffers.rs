@@ -1,13 +1,21 @@
 use std::ffi::OsString;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
 use std::path::Path;
 
 use crate::{
     concurrent::{
-        atomic_buffer::AtomicBuffer,
-        logbuffer::log_buffer_descriptor::{self, check_page_size, check_term_length, page_size, term_length, PARTITION_COUNT},
+        atomic_buffer::{AlignedBuffer, AtomicBuffer},
+        logbuffer::log_buffer_descriptor::{
+            self, check_page_size, check_term_length, compute_log_length, page_size, term_length, PARTITION_COUNT,
+        },
     },
     ttrace,
-    utils::{errors::AeronError, memory_mapped_file::MemoryMappedFile, types::Index},
+    utils::{
+        errors::AeronError,
+        memory_mapped_file::MemoryMappedFile,
+        types::{to_i32, Index},
+    },
 };
 
 #[allow(dead_code)]
@@ -38,9 +46,19 @@ impl LogBuffers {
         }
     }
 
+    /**
+     * Map an existing log file created by the media driver. The length of the file is verified against the
+     * term length and page size held in its meta data before the file is mapped.
+     *
+     * @param file_path       of the log file.
+     * @param pre_touch       true if the pages of the terms should be touched after mapping.
+     * @param registration_id of the publication or image the log belongs to, reported with any error.
+     * @return the mapped log buffers or ChannelEndpointException if the file is truncated or corrupt.
+     */
     pub(crate) fn from_existing<P: std::fmt::Display + AsRef<Path> + Into<OsString>>(
         file_path: P,
         pre_touch: bool,
+        registration_id: i64,
     ) -> Result<Self, AeronError> {
         assert_eq!(log_buffer_descriptor::PARTITION_COUNT, 3);
 
@@ -48,10 +66,14 @@ impl LogBuffers {
 
         let log_len = MemoryMappedFile::get_file_size(&file_path)?;
 
+        Self::verify_log_length(&file_path, log_len, registration_id)?;
+
+        let log_length = to_i32(log_len as isize)?;
+
         let memory_mapped_file = MemoryMappedFile::map_existing(file_path, false).expect("todo");
 
         let meta_buffer = memory_mapped_file.atomic_buffer(
-            (log_len as Index) - log_buffer_descriptor::LOG_META_DATA_LENGTH,
+            log_length - log_buffer_descriptor::LOG_META_DATA_LENGTH,
             log_buffer_descriptor::LOG_META_DATA_LENGTH,
         );
 
@@ -95,4 +117,119 @@ impl LogBuffers {
     pub fn atomic_buffer(&self, index: Index) -> AtomicBuffer {
         self.buffers[index as usize]
     }
+
+    fn verify_log_length<P: std::fmt::Display + AsRef<Path>>(
+        file_path: &P,
+        log_len: u64,
+        registration_id: i64,
+    ) -> Result<(), AeronError> {
+        let meta_data_length = log_buffer_descriptor::LOG_META_DATA_LENGTH;
+
+        if log_len < meta_data_length as u64 {
+            return Err(AeronError::ChannelEndpointException((
+                registration_id,
+                format!("log file too short for meta data: file={} length={}", file_path, log_len),
+            )));
+        }
+
+        let meta_data = AlignedBuffer::with_capacity(meta_data_length);
+        let mut meta_buffer = AtomicBuffer::from_aligned(&meta_data);
+
+        let mut file = File::open(file_path).map_err(AeronError::MemMappedFileError)?;
+        file.seek(SeekFrom::Start(log_len - meta_data_length as u64))
+            .map_err(AeronError::MemMappedFileError)?;
+        file.read_exact(meta_buffer.as_mutable_slice())
+            .map_err(AeronError::MemMappedFileError)?;
+
+        let term_length = term_length(&meta_buffer) as Index;
+        let page_size = page_size(&meta_buffer);
+
+        if check_term_length(term_length).is_err()
+            || check_page_size(page_size).is_err()
+            || log_len as i64 != compute_log_length(term_length, page_size)
+        {
+            return Err(AeronError::ChannelEndpointException((
+                registration_id,
+                format!(
+                    "log file length does not match meta data: file={} length={} term_length={} page_size={}",
+                    file_path, log_len, term_length, page_size
+                ),
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::OpenOptions;
+    use std::io::Write;
+
+    use super::*;
+
+    const TERM_LENGTH: Index = log_buffer_descriptor::TERM_MIN_LENGTH;
+    const PAGE_SIZE: Index = log_buffer_descriptor::AERON_PAGE_MIN_SIZE;
+    const REGISTRATION_ID: i64 = 42;
+
+    fn write_log_file(file_path: &Path, log_length: i64) {
+        let meta_data = AlignedBuffer::with_capacity(log_buffer_descriptor::LOG_META_DATA_LENGTH);
+        let mut meta_buffer = AtomicBuffer::from_aligned(&meta_data);
+        meta_buffer.put::<i32>(*log_buffer_descriptor::LOG_TERM_LENGTH_OFFSET, TERM_LENGTH);
+        meta_buffer.put::<i32>(*log_buffer_descriptor::LOG_PAGE_SIZE_OFFSET, PAGE_SIZE);
+
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .open(file_path)
+            .unwrap();
+        file.set_len(log_length as u64).unwrap();
+        file.seek(SeekFrom::Start(
+            log_length as u64 - log_buffer_descriptor::LOG_META_DATA_LENGTH as u64,
+        ))
+        .unwrap();
+        file.write_all(meta_buffer.as_mutable_slice()).unwrap();
+    }
+
+    #[test]
+    fn should_map_log_file_of_expected_length() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let file_path = tmp_dir.path().join("complete.logbuffer");
+        write_log_file(&file_path, compute_log_length(TERM_LENGTH, PAGE_SIZE));
+
+        let log_buffers = LogBuffers::from_existing(file_path.to_str().unwrap(), false, REGISTRATION_ID).unwrap();
+
+        assert_eq!(log_buffers.atomic_buffer(0).capacity(), TERM_LENGTH);
+        assert_eq!(
+            term_length(&log_buffers.atomic_buffer(log_buffer_descriptor::LOG_META_DATA_SECTION_INDEX)),
+            TERM_LENGTH
+        );
+    }
+
+    #[test]
+    fn should_reject_truncated_log_file() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let file_path = tmp_dir.path().join("truncated.logbuffer");
+        write_log_file(&file_path, compute_log_length(TERM_LENGTH, PAGE_SIZE) - TERM_LENGTH as i64);
+
+        match LogBuffers::from_existing(file_path.to_str().unwrap(), false, REGISTRATION_ID) {
+            Err(AeronError::ChannelEndpointException((registration_id, _message))) => {
+                assert_eq!(registration_id, REGISTRATION_ID)
+            }
+            _ => panic!("expected ChannelEndpointException for truncated log file"),
+        }
+    }
+
+    #[test]
+    fn should_reject_log_file_shorter_than_meta_data() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let file_path = tmp_dir.path().join("empty.logbuffer");
+        File::create(&file_path).unwrap();
+
+        assert!(matches!(
+            LogBuffers::from_existing(file_path.to_str().unwrap(), false, REGISTRATION_ID),
+            Err(AeronError::ChannelEndpointException(_))
+        ));
+    }
 }
@@ -15,11 +15,15 @@
  */
 
 use std::alloc::{alloc_zeroed, dealloc, Layout};
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use cache_line_size::CACHE_LINE_SIZE;
+use lazy_static::lazy_static;
 
-use crate::utils::types::{Index, Moment};
+use crate::utils::{
+    errors::AeronError,
+    types::{Index, Moment},
+};
 
 pub const CACHE_LINE_LENGTH: Index = CACHE_LINE_SIZE as Index;
 
@@ -40,6 +44,34 @@ pub fn unix_time_ns() -> Moment {
     since_the_epoch.as_secs() * 1_000_000_000 + since_the_epoch.subsec_nanos() as u64
 }
 
+lazy_static! {
+    static ref MONOTONIC_CLOCK_START: Instant = Instant::now();
+}
+
+/// Get monotonic time in milliseconds (ms) since the first call. Unlike unix_time_ms it never steps backwards
+/// or jumps when the system clock is adjusted, so it suits measuring timeouts.
+pub fn monotonic_time_ms() -> Moment {
+    MONOTONIC_CLOCK_START.elapsed().as_millis() as Moment
+}
+
+/// Spin until is_done returns true, yielding the thread between checks. Gives up when is_aborted returns true or
+/// the timeout has elapsed, measured on the monotonic clock. Returns true if is_done returned true.
+pub fn spin_until(timeout: Duration, is_done: impl Fn() -> bool, is_aborted: impl Fn() -> bool) -> bool {
+    let deadline = Instant::now() + timeout;
+
+    loop {
+        if is_done() {
+            return true;
+        }
+
+        if is_aborted() || Instant::now() >= deadline {
+            return false;
+        }
+
+        std::thread::yield_now();
+    }
+}
+
 /// Accepts Aeron style ASCII string (without zero termination). Outputs Rust String.
 pub unsafe fn aeron_str_to_rust(raw_str: *const u8, length: i32) -> String {
     let str_slice = std::slice::from_raw_parts(raw_str, length as usize);
@@ -74,9 +106,30 @@ pub fn semantic_version_to_string(version: i32) -> String {
     )
 }
 
+/// Parse a size which may carry a k, m or g suffix, in either case, for kilobytes, megabytes or gigabytes as
+/// accepted by the media driver for channel parameters such as mtu and term-length.
+pub fn parse_size(name: &str, value: &str) -> Result<i64, AeronError> {
+    let invalid = || AeronError::IllegalArgumentException(format!("invalid {}: {}", name, value));
+
+    let (digits, multiplier) = match value.chars().last() {
+        Some('k') | Some('K') => (&value[..value.len() - 1], 1024),
+        Some('m') | Some('M') => (&value[..value.len() - 1], 1024 * 1024),
+        Some('g') | Some('G') => (&value[..value.len() - 1], 1024 * 1024 * 1024),
+        _ => (value, 1),
+    };
+
+    let size = digits.parse::<i64>().map_err(|_| invalid())?;
+    size.checked_mul(multiplier).ok_or_else(invalid)
+}
+
 #[allow(dead_code)]
 /// Allocate a buffer aligned on the cache size
 pub fn alloc_buffer_aligned(size: Index) -> *mut u8 {
+    // Zero sized allocations are not allowed, an empty buffer gets a dangling pointer which is never dereferenced
+    if size == 0 {
+        return std::ptr::NonNull::dangling().as_ptr();
+    }
+
     unsafe {
         let layout = Layout::from_size_align_unchecked(size as usize, CACHE_LINE_SIZE);
         alloc_zeroed(layout)
@@ -86,6 +139,10 @@ pub fn alloc_buffer_aligned(size: Index) -> *mut u8 {
 /// Deallocate a buffer aligned on a cache size
 #[allow(clippy::not_unsafe_ptr_arg_deref)]
 pub fn dealloc_buffer_aligned(buff_ptr: *mut u8, len: Index) {
+    if len == 0 {
+        return;
+    }
+
     unsafe {
         if cfg!(debug_assertions) {
             // dealloc markers for debug
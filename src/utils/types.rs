@@ -14,6 +14,8 @@
  * limitations under the License.
  */
 
+use crate::utils::errors::AeronError;
+
 /// Index type is used to express offset and size dimensions of data in buffers.
 /// It is i32 because there are many places where "length: Index" is written in to log file where it must be 32 bits long.
 /// DON'T USE THIS TYPE INSIDE PACKED STRUCTS AS ITS SIZE MAY CHANGE!!!
@@ -29,6 +31,19 @@ pub fn clamp_to_index(sz: usize) -> Index {
     sz.min(INDEX_MAX_USIZE) as Index
 }
 
+/// Convert a platform sized length or offset (e.g. a slice or file length) to the i32 written into frame fields.
+/// Returns IllegalArgumentException rather than silently truncating values outside i32 range.
+pub fn to_i32(value: isize) -> Result<i32, AeronError> {
+    if value < i32::MIN as isize || value > i32::MAX as isize {
+        return Err(AeronError::IllegalArgumentException(format!(
+            "value does not fit in i32: value={}",
+            value
+        )));
+    }
+
+    Ok(value as i32)
+}
+
 // Define commonly used sizeoffs to shorten main code. i32 type is most commonly used in calculations
 // with sizeof.
 pub const I32_SIZE: Index = std::mem::size_of::<i32>() as Index;
@@ -55,7 +70,8 @@ macro_rules! offset_of {
 
 #[cfg(test)]
 mod tests {
-    use crate::utils::types::Index;
+    use crate::utils::errors::AeronError;
+    use crate::utils::types::{to_i32, Index};
 
     #[repr(C, packed(4))]
     struct Foo {
@@ -72,4 +88,22 @@ mod tests {
         assert_eq!(offset_of!(Foo, c), 12);
         assert_eq!(offset_of!(Foo, d), 16);
     }
+
+    #[test]
+    fn should_convert_values_within_i32_range() {
+        assert_eq!(to_i32(0).unwrap(), 0);
+        assert_eq!(to_i32(i32::MAX as isize).unwrap(), i32::MAX);
+        assert_eq!(to_i32(i32::MIN as isize).unwrap(), i32::MIN);
+    }
+
+    #[test]
+    #[cfg(target_pointer_width = "64")]
+    fn should_error_rather_than_truncate_outside_i32_range() {
+        match to_i32(i32::MAX as isize + 1) {
+            Err(AeronError::IllegalArgumentException(_)) => {}
+            other => panic!("expected IllegalArgumentException, got {:?}", other),
+        }
+        assert!(to_i32(i32::MIN as isize - 1).is_err());
+        assert!(to_i32((1_isize << 32) + 7).is_err());
+    }
 }